@@ -8,9 +8,22 @@ use ratatui::prelude::Widget;
 use ratatui::style::Stylize;
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use crate::buffer_list_widget::BufferListWidget;
+use crate::fill_column_widget::FillColumnWidget;
+use crate::goto_line_widget::GotoLineWidget;
+use crate::indent_widget::IndentWidget;
+use crate::keymap::{Command, Context, Keymap};
+use crate::macro_widget::{MacroPrompt, MacroWidget};
+use crate::macros::macros_dir;
+use crate::marks_widget::MarksWidget;
 use crate::new_file_widget::NewFileWidget;
 use crate::open_file_widget::OpenFileWidget;
+use crate::pipe_widget::PipeWidget;
+use crate::snippet_widget::SnippetWidget;
+use crate::spell_widget::SpellWidget;
 use crate::state::State;
+use crate::stats_widget::StatsWidget;
+use crate::transform_widget::TransformWidget;
+use crate::whitespace_widget::WhitespaceWidget;
 
 pub trait ActionWidget: std::fmt::Debug {
     fn render(&self, area: Rect, buf: &mut Buffer);
@@ -21,17 +34,83 @@ pub trait ActionWidget: std::fmt::Debug {
     fn reset(&mut self);
 }
 
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ActionType {
     NewFile,
     OpenFile,
     ChangeBuffer,
     DeleteBuffer,
+    GotoLine,
+    Indentation,
+    ConvertIndentation,
+    Transform,
+    Pipe,
+    InsertFile,
+    WriteSelection,
+    FillColumn,
+    PlayMacro,
+    SaveMacro,
+    InsertSnippet,
+    Spelling,
+    Whitespace,
+    Marks,
+    Statistics,
     #[default]
     None
 }
 
-const ACTION: &str = "n: Create File | o: Open File\n b: Change Buffer | d: Close Buffer\n\nEsc: Close";
+// The menu rows; the keys come from the keymap so that rebinding an action updates the help.
+const ACTIONS: [&[(&str, ActionType)]; 5] = [
+    &[("Create File", ActionType::NewFile), ("Open File", ActionType::OpenFile), ("Marks", ActionType::Marks), ("Statistics", ActionType::Statistics)],
+    &[("Change Buffer", ActionType::ChangeBuffer), ("Close Buffer", ActionType::DeleteBuffer), ("Whitespace", ActionType::Whitespace)],
+    &[("Go to Line", ActionType::GotoLine), ("Indentation", ActionType::Indentation), ("Re-indent", ActionType::ConvertIndentation), ("Insert Snippet", ActionType::InsertSnippet)],
+    &[("Transform", ActionType::Transform), ("Pipe to Command", ActionType::Pipe), ("Play Macro", ActionType::PlayMacro), ("Save Macro", ActionType::SaveMacro)],
+    &[("Insert File", ActionType::InsertFile), ("Write Selection", ActionType::WriteSelection), ("Fill Column", ActionType::FillColumn), ("Spelling", ActionType::Spelling)],
+];
+
+// Actions without a binding are left out.
+fn action_help(keymap: &Keymap) -> String {
+    let rows: Vec<String> = ACTIONS
+        .iter()
+        .map(|row| {
+            row.iter()
+                .filter_map(|&(label, action)| {
+                    let keys = keymap.keys_for(Context::ActionBar, Command::Action(action));
+                    (!keys.is_empty()).then(|| format!("{}: {}", keys.join("/"), label))
+                })
+                .collect::<Vec<_>>()
+                .join(" | ")
+        })
+        .collect();
+    format!("{}\n\nEsc: Close", rows.join("\n"))
+}
+
+// Where the widget of each action is in `ActionBar::widgets`.
+fn widget_index(action: ActionType) -> Option<usize> {
+    let index = match action {
+        ActionType::NewFile => 0,
+        ActionType::OpenFile => 1,
+        ActionType::ChangeBuffer => 2,
+        ActionType::DeleteBuffer => 3,
+        ActionType::GotoLine => 4,
+        ActionType::Indentation => 5,
+        ActionType::ConvertIndentation => 6,
+        ActionType::Transform => 7,
+        ActionType::Pipe => 8,
+        ActionType::InsertFile => 9,
+        ActionType::WriteSelection => 10,
+        ActionType::FillColumn => 11,
+        ActionType::PlayMacro => 12,
+        ActionType::SaveMacro => 13,
+        ActionType::InsertSnippet => 14,
+        ActionType::Spelling => 15,
+        ActionType::Whitespace => 16,
+        ActionType::Marks => 17,
+        ActionType::Statistics => 18,
+        ActionType::None => return None,
+    };
+    Some(index)
+}
 
 #[derive(Debug)]
 pub struct ActionBar<'a> {
@@ -47,6 +126,21 @@ impl<'a> ActionBar<'a> {
         let open_file_widget = Box::new(OpenFileWidget::new(state.clone()));
         let buffer_navigation_widget = Box::new(BufferListWidget::for_navigation(state.clone()));
         let buffer_deletion_widget = Box::new(BufferListWidget::for_deletion(state.clone()));
+        let goto_line_widget = Box::new(GotoLineWidget::new(state.clone()));
+        let indent_widget = Box::new(IndentWidget::for_setting(state.clone()));
+        let convert_indent_widget = Box::new(IndentWidget::for_conversion(state.clone()));
+        let transform_widget = Box::new(TransformWidget::new(state.clone()));
+        let pipe_widget = Box::new(PipeWidget::new(state.clone()));
+        let insert_file_widget = Box::new(OpenFileWidget::for_insertion(state.clone()));
+        let write_selection_widget = Box::new(OpenFileWidget::for_writing_selection(state.clone()));
+        let fill_column_widget = Box::new(FillColumnWidget::new(state.clone()));
+        let play_macro_widget = Box::new(MacroWidget::new(state.clone(), MacroPrompt::Play, macros_dir()));
+        let save_macro_widget = Box::new(MacroWidget::new(state.clone(), MacroPrompt::Save, macros_dir()));
+        let snippet_widget = Box::new(SnippetWidget::new(state.clone()));
+        let spell_widget = Box::new(SpellWidget::new(state.clone()));
+        let whitespace_widget = Box::new(WhitespaceWidget::new(state.clone()));
+        let marks_widget = Box::new(MarksWidget::new(state.clone()));
+        let stats_widget = Box::new(StatsWidget::new(state.clone()));
 
         ActionBar {
             show,
@@ -57,6 +151,21 @@ impl<'a> ActionBar<'a> {
                 open_file_widget,
                 buffer_navigation_widget,
                 buffer_deletion_widget,
+                goto_line_widget,
+                indent_widget,
+                convert_indent_widget,
+                transform_widget,
+                pipe_widget,
+                insert_file_widget,
+                write_selection_widget,
+                fill_column_widget,
+                play_macro_widget,
+                save_macro_widget,
+                snippet_widget,
+                spell_widget,
+                whitespace_widget,
+                marks_widget,
+                stats_widget,
             ],
         }
     }
@@ -71,23 +180,15 @@ impl<'a> ActionBar<'a> {
             }
         }
 
-        match key.code {
-            KeyCode::Esc => {
+        let command = self.state.borrow().config.keymap.command_for(Context::ActionBar, &key);
+        match (key.code, command) {
+            (KeyCode::Esc, _) => {
                 self.current_action = ActionType::None;
                 self.reset();
                 self.show.set(false);
             }
-            KeyCode::Char('n') => {
-                self.current_action = ActionType::NewFile;
-            },
-            KeyCode::Char('o') => {
-                self.current_action = ActionType::OpenFile;
-            },
-            KeyCode::Char('b') => {
-                self.current_action = ActionType::ChangeBuffer;
-            },
-            KeyCode::Char('d') => {
-                self.current_action = ActionType::DeleteBuffer;
+            (_, Some(Command::Action(action))) => {
+                self.current_action = action;
             }
             _ => ()
         }
@@ -95,12 +196,8 @@ impl<'a> ActionBar<'a> {
     }
 
     fn handle_active_widget_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
-        let widget_index = match self.current_action {
-            ActionType::NewFile => 0,
-            ActionType::OpenFile => 1,
-            ActionType::ChangeBuffer => 2,
-            ActionType::DeleteBuffer => 3,
-            ActionType::None => return Ok(()),
+        let Some(widget_index) = widget_index(self.current_action) else {
+            return Ok(());
         };
 
         let has_error = self.widgets[widget_index].has_error();
@@ -125,16 +222,24 @@ impl<'a> ActionBar<'a> {
         Ok(())
     }
 
+    // The widget shown for `action`, if it has one.
+    pub fn widget(&self, action: ActionType) -> Option<&Box<dyn ActionWidget + 'a>> {
+        widget_index(action).map(|index| &self.widgets[index])
+    }
+
     fn get_active_widget(&self) -> Option<&Box<dyn ActionWidget + 'a>> {
-        match self.current_action {
-            ActionType::NewFile => Some(&self.widgets[0]),
-            ActionType::OpenFile => Some(&self.widgets[1]),
-            ActionType::ChangeBuffer => Some(&self.widgets[2]),
-            ActionType::DeleteBuffer => Some(&self.widgets[3]),
-            ActionType::None => None,
-        }
+        self.widget(self.current_action)
     }
     
+    pub fn open(&mut self, action: ActionType) {
+        self.reset();
+        for widget in self.widgets.iter_mut() {
+            widget.init_action();
+        }
+        self.current_action = action;
+        self.show.set(true);
+    }
+
     pub fn reset(&mut self) {
         for i in 0..self.widgets.len() {
             self.widgets[i].reset();
@@ -148,13 +253,13 @@ impl Widget for &ActionBar<'_> {
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Fill(1),
-                Constraint::Max(6),
+                Constraint::Max(9),
             ])
             .split(area);
 
         let block = Block::default().borders(Borders::ALL);
 
-        let action_message = Paragraph::new(ACTION)
+        let action_message = Paragraph::new(action_help(&self.state.borrow().config.keymap))
             .block(block)
             .centered()
             .bold();
@@ -175,6 +280,7 @@ mod tests {
     use std::cell::{RefCell, Cell};
     use std::rc::Rc;
     use crossterm::event::{KeyCode, KeyModifiers};
+    use crate::keymap::parse_sequence;
 
     #[test]
     fn test_action_bar_new_defaults() {
@@ -213,10 +319,49 @@ mod tests {
 
         action_bar.current_action = ActionType::None;
 
+        // Test Go to Line
+        let key = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        action_bar.handle_input(key).unwrap();
+        assert_eq!(action_bar.current_action, ActionType::GotoLine);
+
+        action_bar.current_action = ActionType::None;
+
+        // Test Transform
+        let key = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE);
+        action_bar.handle_input(key).unwrap();
+        assert_eq!(action_bar.current_action, ActionType::Transform);
+
+        action_bar.current_action = ActionType::None;
+
         // Test Escape
         let key = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
         action_bar.handle_input(key).unwrap();
         assert_eq!(action_bar.current_action, ActionType::None);
         assert_eq!(show.get(), false);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_open_shows_requested_action() {
+        let state = Rc::new(RefCell::new(State::default()));
+        let show = Rc::new(Cell::new(false));
+        let mut action_bar = ActionBar::new(Rc::clone(&show), Rc::clone(&state));
+
+        action_bar.open(ActionType::GotoLine);
+
+        assert_eq!(action_bar.current_action, ActionType::GotoLine);
+        assert!(show.get());
+    }
+
+    #[test]
+    fn test_action_help_follows_keymap() {
+        let mut keymap = Keymap::default();
+        assert!(action_help(&keymap).contains("g: Go to Line"));
+
+        keymap.bind(Context::ActionBar, Command::Action(ActionType::GotoLine), vec![parse_sequence("j").unwrap()]);
+        keymap.bind(Context::ActionBar, Command::Action(ActionType::Spelling), vec![]);
+        let help = action_help(&keymap);
+        assert!(help.contains("j: Go to Line"));
+        assert!(!help.contains("g: Go to Line"));
+        assert!(!help.contains("Spelling"));
+    }
+}
@@ -1,14 +1,23 @@
 use crate::action_bar::{ActionBar, ActionType};
 use crate::editor::Editor;
 use crate::home::Home;
+use crate::config::Config;
+use crate::keymap::{Command, Context, Lookup};
+use crate::macros::{Playback, Repeat, MAX_PLAYBACK};
+use crate::marks::{load_session, save_session, session_file};
 use crate::state::State;
-use crossterm::event::{DisableMouseCapture, Event, KeyCode, KeyModifiers};
+use crossterm::event::{DisableMouseCapture, Event, KeyCode, KeyEvent};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
 use crossterm::{event, execute};
 use ratatui::{DefaultTerminal, Frame};
+use log::{log, Level};
 use std::cell::{Cell, RefCell};
 use std::io;
 use std::rc::Rc;
+use std::time::Instant;
+
+const NO_MACRO_RECORDED: &str = "No macro recorded (Alt+: to record)";
+const MACRO_WHILE_RECORDING: &str = "Stop recording before playing the macro";
 
 #[derive(Debug)]
 pub struct App<'a> {
@@ -17,6 +26,9 @@ pub struct App<'a> {
     pub action_bar: ActionBar<'a>,
     pub state: Rc<RefCell<State<'a>>>,
     pub show_action_bar: Rc<Cell<bool>>,
+    // Keys of a multi-key sequence typed so far.
+    pub pending_keys: Vec<KeyEvent>,
+    pub last_autosave: Instant,
 }
 
 impl Default for App<'_> {
@@ -29,6 +41,8 @@ impl Default for App<'_> {
             action_bar: ActionBar::new(show_action_bar.clone(), state.clone()),
             state,
             show_action_bar,
+            pending_keys: Vec::new(),
+            last_autosave: Instant::now(),
         }
     }
 }
@@ -46,6 +60,11 @@ impl App<'_> {
             self.state.borrow_mut().current_screen = CurrentScreen::Editor;
         }
 
+        {
+            let mut state = self.state.borrow_mut();
+            state.session_file = session_file();
+            state.global_marks = state.session_file.as_deref().map(load_session).unwrap_or_default();
+        }
         self.editor.init(file)?;
 
         while !self.state.borrow().exit {
@@ -53,9 +72,24 @@ impl App<'_> {
             self.handle_events()?;
         }
 
+        save_session(&mut self.state.borrow_mut());
         Ok(())
     }
 
+    // Installs the loaded settings; problems with them are logged and shown in the status bar.
+    pub fn configure(&mut self, config: Config, errors: Vec<String>) {
+        let mut state = self.state.borrow_mut();
+        state.config = config;
+        for error in &errors {
+            log!(Level::Warn, "Config: {}", error);
+        }
+        state.status_error = match errors.as_slice() {
+            [] => None,
+            [error] => Some(error.clone()),
+            [error, rest @ ..] => Some(format!("{} (+{} more in the log)", error, rest.len())),
+        };
+    }
+
     pub fn init(terminal: &mut DefaultTerminal) -> Result<(), io::Error> {
         enable_raw_mode()?;
         execute!(
@@ -88,36 +122,310 @@ impl App<'_> {
     }
 
     fn handle_events(&mut self) -> io::Result<()> {
-        if let Event::Key(key) = event::read()? {
-            // Global shortcuts
-            if key.code == KeyCode::Char('q') && key.modifiers == KeyModifiers::CONTROL {
-                self.state.borrow_mut().exit = true;
+        // With autosave on, a key is only waited for until the next save is due.
+        let autosave = self.state.borrow().config.autosave;
+        if let Some(interval) = autosave {
+            let elapsed = self.last_autosave.elapsed();
+            if elapsed >= interval || !event::poll(interval - elapsed)? {
+                self.editor.autosave();
+                self.last_autosave = Instant::now();
                 return Ok(());
             }
+        }
+
+        if let Event::Key(key) = event::read()? {
+            self.handle_key(key)?;
+            self.play_pending_macro()?;
+        }
+        Ok(())
+    }
 
-            if key.code == KeyCode::Char(' ') && key.modifiers == KeyModifiers::CONTROL {
+    // The keymap context for the focused screen; open action widgets take raw keys.
+    fn key_context(&self) -> Option<Context> {
+        if self.show_action_bar.get() {
+            (self.action_bar.current_action == ActionType::None).then_some(Context::ActionBar)
+        } else if self.state.borrow().current_screen == CurrentScreen::Editor {
+            Some(Context::Editor)
+        } else {
+            None
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        let before = self.cursor();
+        self.dispatch_key(key)?;
+        self.skip_folds(before);
+        Ok(())
+    }
+
+    fn dispatch_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        self.pending_keys.push(key);
+        let lookup = self.state.borrow().config.keymap.lookup(self.key_context(), &self.pending_keys);
+        if lookup == Lookup::Prefix {
+            return Ok(());
+        }
+        let keys = std::mem::take(&mut self.pending_keys);
+
+        if let Lookup::Command(_, command @ (Command::ToggleMacroRecording | Command::PlayLastMacro)) = lookup {
+            if self.key_context() == Some(Context::Editor) {
+                self.handle_macro_command(command);
+            }
+            return Ok(());
+        }
+        for key in &keys {
+            self.state.borrow_mut().macros.record(*key);
+        }
+
+        match lookup {
+            Lookup::Command(context, command) => self.run_command(context, command),
+            _ => {
+                // Keys of an unfinished sequence are typed as they are.
+                for key in keys {
+                    self.handle_raw_key(key)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn run_command(&mut self, context: Context, command: Command) -> io::Result<()> {
+        let on_editor = self.state.borrow().current_screen == CurrentScreen::Editor;
+        match command {
+            Command::Quit => self.state.borrow_mut().exit = true,
+            Command::ToggleActionBar => {
                 self.action_bar.current_action = ActionType::None;
                 self.show_action_bar.set(!self.show_action_bar.get());
                 self.action_bar.reset();
-                return Ok(());
+                self.state.borrow_mut().cut_buffer.stop_accumulating();
             }
+            Command::Action(action) if context == Context::ActionBar || on_editor => self.action_bar.open(action),
+            _ if on_editor && !self.show_action_bar.get() => self.editor.run_command(command)?,
+            _ => (),
+        }
+        Ok(())
+    }
 
-            // Handle input based on current context
-            if self.show_action_bar.get() {
-                if key.code == KeyCode::Esc {
-                    self.show_action_bar.set(false);
-                    self.action_bar.current_action = ActionType::None;
-                } else {
-                    self.action_bar.handle_input(key)?;
-                }
+    fn handle_raw_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        if self.show_action_bar.get() {
+            if key.code == KeyCode::Esc {
+                self.show_action_bar.set(false);
+                self.action_bar.current_action = ActionType::None;
             } else {
-                let current_screen = self.state.borrow().current_screen.clone();
-                match current_screen {
-                    CurrentScreen::Home => self.home.handle_input(key)?,
-                    CurrentScreen::Editor => self.editor.handle_input(key)?,
-                }
+                self.action_bar.handle_input(key)?;
+            }
+        } else {
+            let current_screen = self.state.borrow().current_screen.clone();
+            match current_screen {
+                CurrentScreen::Home => self.home.handle_input(key)?,
+                CurrentScreen::Editor => self.editor.handle_input(key)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_macro_command(&mut self, command: Command) {
+        let mut state = self.state.borrow_mut();
+        if command == Command::ToggleMacroRecording {
+            state.macros.toggle_recording();
+        } else if state.macros.is_recording() {
+            state.status_error = Some(MACRO_WHILE_RECORDING.to_string());
+        } else if state.macros.last.is_empty() {
+            state.status_error = Some(NO_MACRO_RECORDED.to_string());
+        } else {
+            let keys = state.macros.last.clone();
+            state.macros.pending = Some(Playback { keys, repeat: Repeat::Times(1) });
+        }
+    }
+
+    // The current buffer and its cursor.
+    fn cursor(&self) -> Option<(usize, (usize, usize))> {
+        let state = self.state.borrow();
+        state.buffer_list.get(state.current_buffer).map(|buffer| (state.current_buffer, buffer.input.cursor()))
+    }
+
+    // Keeps the cursor out of folded lines, whatever moved it there.
+    fn skip_folds(&self, before: Option<(usize, (usize, usize))>) {
+        let mut state = self.state.borrow_mut();
+        let current = state.current_buffer;
+        let Some(buffer) = state.buffer_list.get_mut(current) else {
+            return;
+        };
+        match before {
+            Some((index, cursor)) if index == current => buffer.skip_folds(cursor),
+            _ => buffer.folds.reveal(buffer.input.cursor().0),
+        }
+    }
+
+    // The cursor row and the line count of the current buffer.
+    fn position(&self) -> Option<(usize, usize)> {
+        let state = self.state.borrow();
+        state.buffer_list.get(state.current_buffer).map(|buffer| (buffer.input.cursor().0, buffer.input.lines().len()))
+    }
+
+    fn play_pending_macro(&mut self) -> io::Result<()> {
+        let Some(playback) = self.state.borrow_mut().macros.pending.take() else {
+            return Ok(());
+        };
+
+        let times = match playback.repeat {
+            Repeat::Times(times) => times,
+            Repeat::UntilEnd => MAX_PLAYBACK,
+        };
+        for _ in 0..times {
+            let before = self.position();
+            for key in &playback.keys {
+                self.handle_key(*key)?;
+            }
+            if self.state.borrow().exit {
+                break;
+            }
+            // A run makes progress by moving down a line or by removing lines. The run on the
+            // last line still edits it; it is the first that cannot move on, so playback stops.
+            let advanced = matches!((before, self.position()), (Some((row, count)), Some((new_row, new_count))) if new_row > row || new_count < count);
+            if playback.repeat == Repeat::UntilEnd && !advanced {
+                break;
             }
         }
+        // A macro cannot start another one.
+        self.state.borrow_mut().macros.pending = None;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crossterm::event::KeyModifiers;
+    use tui_textarea::TextArea;
+
+    fn create_app(lines: &[&str]) -> App<'static> {
+        let app = App::default();
+        let lines = lines.iter().map(|line| line.to_string()).collect();
+        let mut state = app.state.borrow_mut();
+        state.push_buffer(Buffer::new(TextArea::new(lines), None));
+        state.current_screen = CurrentScreen::Editor;
+        drop(state);
+        app
+    }
+
+    fn press(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+        app.handle_key(KeyEvent::new(code, modifiers)).unwrap();
+        app.play_pending_macro().unwrap();
+    }
+
+    fn parse(content: &str) -> toml::Table {
+        toml::from_str(content).unwrap()
+    }
+
+    fn lines(app: &App) -> Vec<String> {
+        app.state.borrow().buffer_list[0].input.lines().to_vec()
+    }
+
+    #[test]
+    fn test_record_and_play_macro() {
+        let mut app = create_app(&["a", "b", "c"]);
+        press(&mut app, KeyCode::Char(':'), KeyModifiers::ALT);
+        press(&mut app, KeyCode::Char('-'), KeyModifiers::NONE);
+        press(&mut app, KeyCode::Home, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Down, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Char(':'), KeyModifiers::ALT);
+        assert_eq!(app.state.borrow().macros.last.len(), 3);
+
+        press(&mut app, KeyCode::Char(';'), KeyModifiers::ALT);
+        assert_eq!(lines(&app), vec!["-a", "-b", "c"]);
+    }
+
+    #[test]
+    fn test_play_macro_until_end_of_buffer() {
+        let mut app = create_app(&["a", "b", "c", "d"]);
+        let keys = vec![
+            KeyEvent::new(KeyCode::Home, KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('-'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Down, KeyModifiers::NONE),
+        ];
+        app.state.borrow_mut().macros.pending = Some(Playback { keys, repeat: Repeat::UntilEnd });
+
+        app.play_pending_macro().unwrap();
+        assert_eq!(lines(&app), vec!["-a", "-b", "-c", "-d"]);
+    }
+
+    #[test]
+    fn test_play_line_removing_macro_until_end() {
+        let mut app = create_app(&["a", "b", "c"]);
+        let keys = vec![KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL)];
+        app.state.borrow_mut().macros.pending = Some(Playback { keys, repeat: Repeat::UntilEnd });
+
+        app.play_pending_macro().unwrap();
+        assert_eq!(lines(&app), vec![""]);
+        assert_eq!(app.state.borrow().cut_buffer.text, "a\nb\nc");
+    }
+
+    #[test]
+    fn test_play_without_macro() {
+        let mut app = create_app(&["a"]);
+        press(&mut app, KeyCode::Char(';'), KeyModifiers::ALT);
+        assert_eq!(app.state.borrow().status_error, Some(NO_MACRO_RECORDED.to_string()));
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, KeyCode::Char(c), KeyModifiers::NONE);
+        }
+    }
+
+    #[test]
+    fn test_file_errors_keep_the_app_running() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("binary");
+        std::fs::write(&binary, [0xff, 0xfe, 0x00]).unwrap();
+        let mut app = create_app(&["a"]);
+
+        for path in [dir.path().join("missing.txt"), binary] {
+            press(&mut app, KeyCode::Char('r'), KeyModifiers::CONTROL);
+            type_text(&mut app, path.to_str().unwrap());
+            press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+            assert!(app.action_bar.widget(ActionType::InsertFile).unwrap().has_error());
+            press(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+        }
+
+        app.state.borrow_mut().buffer_list[0].input.select_all();
+        press(&mut app, KeyCode::Char(' '), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('w'), KeyModifiers::NONE);
+        type_text(&mut app, dir.path().join("missing/out.txt").to_str().unwrap());
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert!(app.action_bar.widget(ActionType::WriteSelection).unwrap().has_error());
+
+        assert!(!app.state.borrow().exit);
+        assert_eq!(lines(&app), vec!["a"]);
+    }
+
+    #[test]
+    fn test_key_sequence_from_keymap() {
+        let mut app = create_app(&["a"]);
+        let errors = app.state.borrow_mut().config.keymap.apply_overrides(&parse("[editor]\nsave = \"C-x C-s\"\nredo = \"\"\n[global]\nquit = \"C-x C-c\""));
+        assert!(errors.is_empty());
+
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::CONTROL);
+        assert_eq!(app.pending_keys.len(), 1);
+        press(&mut app, KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert!(app.state.borrow().exit);
+
+        // An unbound sequence passes its keys on to the editor.
+        app.state.borrow_mut().exit = false;
+        press(&mut app, KeyCode::Char('x'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('b'), KeyModifiers::NONE);
+        assert!(app.pending_keys.is_empty());
+        assert_eq!(lines(&app), vec!["ba"]);
+    }
+
+    #[test]
+    fn test_action_bar_keys_from_keymap() {
+        let mut app = create_app(&["a"]);
+        app.state.borrow_mut().config.keymap.apply_overrides(&parse("[action_bar]\ngoto_line = \"j\""));
+        press(&mut app, KeyCode::Char(' '), KeyModifiers::CONTROL);
+        assert!(app.show_action_bar.get());
+        press(&mut app, KeyCode::Char('j'), KeyModifiers::NONE);
+        assert_eq!(app.action_bar.current_action, ActionType::GotoLine);
+    }
+}
@@ -0,0 +1,138 @@
+const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+const QUOTES: [char; 3] = ['"', '\'', '`'];
+const MAX_SCAN_LINES: usize = 5000;
+
+pub fn closing_bracket(c: char) -> Option<char> {
+    PAIRS.iter().find(|(open, _)| *open == c).map(|(_, close)| *close)
+}
+
+pub fn opening_bracket(c: char) -> Option<char> {
+    PAIRS.iter().find(|(_, close)| *close == c).map(|(open, _)| *open)
+}
+
+// Closing character for a bracket or quote.
+pub fn closer_for(c: char) -> Option<char> {
+    closing_bracket(c).or(QUOTES.contains(&c).then_some(c))
+}
+
+pub fn is_closer(c: char) -> bool {
+    opening_bracket(c).is_some() || QUOTES.contains(&c)
+}
+
+// Closing character to insert after typing `c` between `prev` and `next`, if any.
+pub fn auto_close(c: char, prev: Option<char>, next: Option<char>) -> Option<char> {
+    let next_is_free = next.is_none_or(|n| n.is_whitespace() || opening_bracket(n).is_some());
+    if let Some(close) = closing_bracket(c) {
+        return next_is_free.then_some(close);
+    }
+    if QUOTES.contains(&c) {
+        let prev_is_free = prev.is_none_or(|p| !p.is_alphanumeric() && p != c);
+        return (next_is_free && prev_is_free).then_some(c);
+    }
+    None
+}
+
+// Bracket at or just before the cursor, with the position of its match.
+pub fn find_matching(lines: &[String], cursor: (usize, usize)) -> Option<((usize, usize), (usize, usize))> {
+    let (row, col) = cursor;
+    let line: Vec<char> = lines.get(row)?.chars().collect();
+    let candidates = [Some(col), col.checked_sub(1)];
+
+    for col in candidates.into_iter().flatten() {
+        let Some(&c) = line.get(col) else { continue };
+        let target = if let Some(close) = closing_bracket(c) {
+            scan_forward(lines, (row, col), c, close)
+        } else if let Some(open) = opening_bracket(c) {
+            scan_backward(lines, (row, col), open, c)
+        } else {
+            continue;
+        };
+        return target.map(|target| ((row, col), target));
+    }
+    None
+}
+
+fn scan_forward(lines: &[String], from: (usize, usize), open: char, close: char) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let last_row = (from.0 + MAX_SCAN_LINES).min(lines.len());
+    for (row, line) in lines.iter().enumerate().take(last_row).skip(from.0) {
+        let skip = if row == from.0 { from.1 } else { 0 };
+        for (col, c) in line.chars().enumerate().skip(skip) {
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((row, col));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn scan_backward(lines: &[String], from: (usize, usize), open: char, close: char) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let first_row = from.0.saturating_sub(MAX_SCAN_LINES);
+    for row in (first_row..=from.0).rev() {
+        let chars: Vec<char> = lines[row].chars().collect();
+        let end = if row == from.0 { from.1 + 1 } else { chars.len() };
+        for col in (0..end).rev() {
+            if chars[col] == close {
+                depth += 1;
+            } else if chars[col] == open {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((row, col));
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_find_matching_forward() {
+        let text = lines(&["fn a() {", "    (b[0])", "}"]);
+        assert_eq!(find_matching(&text, (0, 7)), Some(((0, 7), (2, 0))));
+        assert_eq!(find_matching(&text, (1, 4)), Some(((1, 4), (1, 9))));
+    }
+
+    #[test]
+    fn test_find_matching_backward() {
+        let text = lines(&["fn a() {", "    (b[0])", "}"]);
+        assert_eq!(find_matching(&text, (2, 0)), Some(((2, 0), (0, 7))));
+        assert_eq!(find_matching(&text, (1, 8)), Some(((1, 8), (1, 6))));
+    }
+
+    #[test]
+    fn test_find_matching_before_cursor() {
+        let text = lines(&["(a)"]);
+        assert_eq!(find_matching(&text, (0, 3)), Some(((0, 2), (0, 0))));
+    }
+
+    #[test]
+    fn test_find_matching_none() {
+        let text = lines(&["(a", "b"]);
+        assert_eq!(find_matching(&text, (0, 0)), None);
+        assert_eq!(find_matching(&text, (1, 0)), None);
+    }
+
+    #[test]
+    fn test_auto_close() {
+        assert_eq!(auto_close('(', None, None), Some(')'));
+        assert_eq!(auto_close('{', Some(' '), Some(' ')), Some('}'));
+        assert_eq!(auto_close('(', None, Some('x')), None);
+        assert_eq!(auto_close('"', Some(' '), None), Some('"'));
+        assert_eq!(auto_close('\'', Some('n'), Some(' ')), None);
+        assert_eq!(auto_close('x', None, None), None);
+    }
+}
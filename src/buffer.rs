@@ -1,20 +1,97 @@
+use crate::brackets::{auto_close, closer_for, find_matching, is_closer};
+use crate::config::Config;
+use crate::editorconfig::{properties_for, Charset, IndentStyle, LineEnding, MaxLineLength, Properties};
+use crate::folding::{fold_ranges, range_at, FoldMethod, Folds};
+use crate::history::{Change, Checkpoint, History};
+use crate::completion::word_prefix;
+use crate::indent::{convert_indent, dedent_len, detect_indent, has_mixed_indent, leading_whitespace, Indent};
+use crate::marks::shift_row;
+use crate::justify::{justify_lines, paragraph_bounds, split_line, wrap_point, DEFAULT_FILL_COLUMN};
+use crate::stats::word_count;
+use crate::snippets::{end_of, expand, load_snippets, snippets_dir, Pos, Snippet, SnippetSession};
+use crate::whitespace::Cleanup;
+use crate::text_view::{display_width, line_cells_with_end, wrap_cells, SoftWrap, Viewport};
+use chrono::Local;
+use crossterm::event::KeyEvent;
 use ratatui::prelude::{Color, Style};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
 use std::fs::OpenOptions;
-use std::io::{BufRead, BufReader};
+use std::hash::{Hash, Hasher};
+use std::io::Read;
 use std::io;
 use std::path::Path;
-use tui_textarea::TextArea;
+use tui_textarea::{CursorMove, TextArea};
+
+const SCRATCH_NAME: &str = "[scratch]";
 
 #[derive(Debug, Clone, Default)]
 pub struct Buffer<'a>{
     pub input: TextArea<'a>,
     pub filename: Option<String>,
-    pub path: Option<String>
+    pub path: Option<String>,
+    pub viewport: Viewport,
+    pub indent: Indent,
+    pub mixed_indent: bool,
+    pub auto_closers: Vec<(usize, usize)>,
+    pub history: History,
+    pub soft_wrap: SoftWrap,
+    pub fill_column: usize,
+    pub hard_wrap: bool,
+    pub snippets: Vec<Snippet>,
+    pub snippet_session: Option<SnippetSession>,
+    pub spell_check: bool,
+    pub cleanup: Cleanup,
+    // Hash of the lines last read from or written to the file.
+    pub saved_hash: u64,
+    pub line_ending: LineEnding,
+    pub charset: Charset,
+    // Named marks and the global marks in this file, kept on their lines as text is edited.
+    pub marks: BTreeMap<char, (usize, usize)>,
+    pub folds: Folds,
+    pub fold_method: FoldMethod,
+    // Words when the buffer was opened, where the word goal starts counting.
+    pub session_words: usize,
 }
 
 impl<'a> Buffer<'a> {
-    pub fn new(input: TextArea<'a>, path: Option<String>) -> Buffer<'a> {
-        Buffer { input, filename: None, path }
+    pub fn new(mut input: TextArea<'a>, path: Option<String>) -> Buffer<'a> {
+        input.set_max_histories(0);
+        let session_words = word_count(input.lines());
+        Buffer {
+            input,
+            filename: None,
+            path,
+            viewport: Viewport::default(),
+            indent: Indent::default(),
+            mixed_indent: false,
+            auto_closers: Vec::new(),
+            history: History::default(),
+            soft_wrap: SoftWrap::default(),
+            fill_column: DEFAULT_FILL_COLUMN,
+            hard_wrap: false,
+            snippets: Vec::new(),
+            snippet_session: None,
+            spell_check: false,
+            cleanup: Cleanup::default(),
+            saved_hash: 0,
+            line_ending: LineEnding::default(),
+            charset: Charset::default(),
+            marks: BTreeMap::new(),
+            folds: Folds::default(),
+            fold_method: FoldMethod::default(),
+            session_words,
+        }
+    }
+
+    // An empty buffer using the configured defaults, ready for `init`.
+    pub fn with_config(config: &Config) -> Buffer<'a> {
+        Buffer {
+            indent: Indent::Spaces(config.tab_width),
+            fold_method: config.fold_method,
+            cleanup: config.cleanup,
+            ..Buffer::new(TextArea::default(), None)
+        }
     }
 
     pub fn init(&mut self, path: &str) -> Result<(), io::Error> {
@@ -25,31 +102,928 @@ impl<'a> Buffer<'a> {
             .unwrap()
             .to_string();
 
-        let file = OpenOptions::new()
+        let mut file = OpenOptions::new()
             .create(true)
             .truncate(false)
             .read(true)
             .write(true)
             .open(path)?;
 
-        let result = BufReader::new(file).lines().collect::<io::Result<_>>()?;
-        self.input = self.custom_text_area(result);
+        let properties = properties_for(Path::new(path));
+        self.charset = properties.charset.unwrap_or_default();
+        self.line_ending = properties.end_of_line.unwrap_or_default();
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let mut content = self.charset.decode(&bytes)?;
+        if self.line_ending == LineEnding::Cr {
+            content = content.replace('\r', "\n");
+        }
+
+        let result: Vec<String> = content.lines().map(String::from).collect();
+        if let Some(indent) = detect_indent(&result, self.indent.width()) {
+            self.indent = indent;
+        }
+        self.mixed_indent = has_mixed_indent(&result);
         self.path = Some(String::from(path));
         self.filename = Some(filename);
+        self.snippets = snippets_dir().map(|dir| load_snippets(&dir, self.file_type())).unwrap_or_default();
+        self.cleanup = self.cleanup.for_file_type(self.file_type());
+        self.apply_editorconfig(&properties);
+        self.input = self.custom_text_area(result);
+        self.session_words = word_count(self.input.lines());
+        self.mark_saved();
         Ok(())
     }
 
+    // EditorConfig settings win over the detected indentation and the file type defaults.
+    fn apply_editorconfig(&mut self, properties: &Properties) {
+        let width = self.indent.width();
+        self.indent = match (properties.indent_style, self.indent) {
+            (Some(IndentStyle::Tab), _) | (None, Indent::Tabs(_)) => Indent::Tabs(properties.tab_width().unwrap_or(width)),
+            (Some(IndentStyle::Space), _) | (None, Indent::Spaces(_)) => Indent::Spaces(properties.indent_size().unwrap_or(width)),
+        };
+        if let Some(trim) = properties.trim_trailing_whitespace {
+            self.cleanup.trim_trailing = trim;
+        }
+        if let Some(final_newline) = properties.insert_final_newline {
+            self.cleanup.final_newline = final_newline;
+        }
+        match properties.max_line_length {
+            Some(MaxLineLength::Columns(columns)) => self.fill_column = columns,
+            Some(MaxLineLength::Off) => self.hard_wrap = false,
+            None => (),
+        }
+    }
+
+    // The text written to the file, in its line ending and charset.
+    pub fn file_bytes(&self, lines: &[String]) -> Vec<u8> {
+        let content = self.cleanup.file_content(lines);
+        let content = match self.line_ending {
+            LineEnding::Lf => content,
+            ending => content.replace('\n', ending.as_str()),
+        };
+        self.charset.encode(&content)
+    }
+
+    fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.input.lines().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved_hash = self.content_hash();
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.content_hash() != self.saved_hash
+    }
+
+    // The file extension, used to pick snippets and what to spell check.
+    pub fn file_type(&self) -> Option<&str> {
+        self.path.as_deref().and_then(|path| Path::new(path).extension()).and_then(|extension| extension.to_str())
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.filename.as_deref().or(self.path.as_deref()).unwrap_or(SCRATCH_NAME)
+    }
+
     pub fn custom_text_area(&self, lines: Vec<String>) -> TextArea<'a>{
         let mut text_area = TextArea::new(lines);
         text_area.set_cursor_line_style(Style::default());
         text_area.set_line_number_style(Style::default().fg(Color::DarkGray));
+        text_area.set_tab_length(self.indent.width());
+        text_area.set_hard_tab_indent(self.indent.is_hard_tab());
+        text_area.set_max_histories(0);
         text_area
     }
+
+    pub fn set_indent(&mut self, indent: Indent) {
+        self.indent = indent;
+        self.input.set_tab_length(indent.width());
+        self.input.set_hard_tab_indent(indent.is_hard_tab());
+    }
+
+    pub fn replace_lines(&mut self, first: usize, last: usize, lines: &[String]) {
+        self.input.cancel_selection();
+        self.move_cursor_to(first, 0);
+        self.input.start_selection();
+        self.move_cursor_to(last, 0);
+        self.input.move_cursor(CursorMove::End);
+        self.input.insert_str(lines.join("\n"));
+    }
+
+    pub fn convert_indent(&mut self, indent: Indent) {
+        let from = self.indent;
+        let converted: Vec<String> = self.input.lines().iter().map(|line| convert_indent(line, from, indent)).collect();
+        let changed: Vec<usize> = (0..converted.len()).filter(|&row| converted[row] != self.input.lines()[row]).collect();
+
+        if let (Some(&first), Some(&last)) = (changed.first(), changed.last()) {
+            let checkpoint = self.checkpoint((first, last));
+            let (row, col) = self.input.cursor();
+            let old_len = self.input.lines()[row].chars().count();
+            let new_len = converted[row].chars().count();
+            self.replace_lines(first, last, &converted[first..=last]);
+            self.move_cursor_to(row, (col + new_len).saturating_sub(old_len));
+            self.commit(checkpoint);
+        }
+
+        self.set_indent(indent);
+        self.mixed_indent = false;
+    }
+
+    // Rows covered by the selection, or the cursor row. A selection ending at the
+    // head of a line does not include that line.
+    pub fn selected_rows(&self) -> (usize, usize) {
+        match self.input.selection_range() {
+            Some(((start_row, _), (end_row, 0))) if end_row > start_row => (start_row, end_row - 1),
+            Some(((start_row, _), (end_row, _))) => (start_row, end_row),
+            None => {
+                let row = self.input.cursor().0;
+                (row, row)
+            }
+        }
+    }
+
+    fn selection_anchor(&self) -> Option<(usize, usize)> {
+        let (start, end) = self.input.selection_range()?;
+        Some(if start == self.input.cursor() { end } else { start })
+    }
+
+    pub fn restore_selection(&mut self, anchor: Option<(usize, usize)>, cursor: (usize, usize)) {
+        self.input.cancel_selection();
+        if let Some((row, col)) = anchor {
+            self.move_cursor_to(row, col);
+            self.input.start_selection();
+        }
+        self.move_cursor_to(cursor.0, cursor.1);
+    }
+
+    pub fn indent_lines(&mut self) {
+        let (first, last) = self.selected_rows();
+        let unit = self.indent.unit();
+        let width = unit.chars().count();
+        let anchor = self.selection_anchor();
+        let cursor = self.input.cursor();
+
+        let checkpoint = self.checkpoint((first, last));
+        let mut indented = Vec::new();
+        self.input.cancel_selection();
+        for row in first..=last {
+            if self.input.lines()[row].is_empty() {
+                continue;
+            }
+            self.move_cursor_to(row, 0);
+            self.input.insert_str(&unit);
+            indented.push(row);
+        }
+
+        let shift = |(row, col): (usize, usize)| {
+            if indented.contains(&row) && (anchor.is_none() || col > 0) {
+                (row, col + width)
+            } else {
+                (row, col)
+            }
+        };
+        self.restore_selection(anchor.map(shift), shift(cursor));
+        self.commit(checkpoint);
+    }
+
+    pub fn dedent_lines(&mut self) {
+        let (first, last) = self.selected_rows();
+        let anchor = self.selection_anchor();
+        let cursor = self.input.cursor();
+
+        let checkpoint = self.checkpoint((first, last));
+        let mut removed = Vec::new();
+        self.input.cancel_selection();
+        for row in first..=last {
+            let len = dedent_len(&self.input.lines()[row], self.indent);
+            if len > 0 {
+                self.move_cursor_to(row, 0);
+                self.input.delete_str(len);
+                removed.push((row, len));
+            }
+        }
+
+        let shift = |(row, col): (usize, usize)| match removed.iter().find(|(r, _)| *r == row) {
+            Some((_, len)) => (row, col.saturating_sub(*len)),
+            None => (row, col),
+        };
+        self.restore_selection(anchor.map(shift), shift(cursor));
+        self.commit(checkpoint);
+    }
+
+    fn set_lines(&mut self, first: usize, last: usize, lines: &[String], anchor: Option<(usize, usize)>, cursor: (usize, usize)) {
+        let checkpoint = self.checkpoint((first, last));
+        self.replace_lines(first, last, lines);
+        self.restore_selection(anchor, cursor);
+        self.commit(checkpoint);
+    }
+
+    pub fn move_lines_up(&mut self) {
+        let (first, last) = self.selected_rows();
+        if first == 0 {
+            return;
+        }
+        let mut lines = self.input.lines()[first..=last].to_vec();
+        lines.push(self.input.lines()[first - 1].clone());
+
+        let up = |(row, col): (usize, usize)| (row - 1, col);
+        let anchor = self.selection_anchor().map(up);
+        let cursor = up(self.input.cursor());
+        self.set_lines(first - 1, last, &lines, anchor, cursor);
+    }
+
+    pub fn move_lines_down(&mut self) {
+        let (first, last) = self.selected_rows();
+        if last + 1 >= self.input.lines().len() {
+            return;
+        }
+        let mut lines = vec![self.input.lines()[last + 1].clone()];
+        lines.extend_from_slice(&self.input.lines()[first..=last]);
+
+        let down = |(row, col): (usize, usize)| (row + 1, col);
+        let anchor = self.selection_anchor().map(down);
+        let cursor = down(self.input.cursor());
+        self.set_lines(first, last + 1, &lines, anchor, cursor);
+    }
+
+    pub fn duplicate_lines(&mut self) {
+        let (first, last) = self.selected_rows();
+        let block = &self.input.lines()[first..=last];
+        let lines = [block, block].concat();
+
+        let count = last - first + 1;
+        let shift = |(row, col): (usize, usize)| (row + count, col);
+        let anchor = self.selection_anchor().map(shift);
+        let cursor = shift(self.input.cursor());
+        self.set_lines(first, last, &lines, anchor, cursor);
+    }
+
+    pub fn delete_lines(&mut self) {
+        let (first, last) = self.selected_rows();
+        let lines = self.input.lines();
+        let col = self.input.cursor().1;
+        if last + 1 < lines.len() {
+            let next = lines[last + 1].clone();
+            self.set_lines(first, last + 1, &[next], None, (first, col));
+        } else if first > 0 {
+            let previous = lines[first - 1].clone();
+            self.set_lines(first - 1, last, &[previous], None, (first - 1, col));
+        } else {
+            self.set_lines(first, last, &[String::new()], None, (0, 0));
+        }
+    }
+
+    // Joins the selected lines, or the current line with the next one, the way `J` does in vi.
+    pub fn join_lines(&mut self) {
+        let (first, last) = self.selected_rows();
+        let last = if first == last { last + 1 } else { last };
+        if last >= self.input.lines().len() {
+            return;
+        }
+
+        let mut joined = self.input.lines()[first].clone();
+        let mut col = joined.chars().count();
+        for line in &self.input.lines()[first + 1..=last] {
+            let line = line.trim_start();
+            if !joined.is_empty() && !line.is_empty() && !joined.ends_with(char::is_whitespace) {
+                joined.push(' ');
+            }
+            col = joined.chars().count();
+            joined.push_str(line);
+        }
+        self.set_lines(first, last, &[joined], None, (first, col));
+    }
+
+    // Reflows the selected lines, or the paragraph under the cursor, to the fill column.
+    pub fn justify(&mut self) {
+        let tab_length = self.input.tab_length() as usize;
+        let (first, last) = if self.input.is_selecting() {
+            self.selected_rows()
+        } else {
+            match paragraph_bounds(self.input.lines(), self.input.cursor().0) {
+                Some(bounds) => bounds,
+                None => return,
+            }
+        };
+
+        let lines = justify_lines(&self.input.lines()[first..=last], self.fill_column, tab_length);
+        let cursor = (first + lines.len() - 1, lines[lines.len() - 1].chars().count());
+        self.set_lines(first, last, &lines, None, cursor);
+    }
+
+    // Breaks the cursor line at the fill column while typing, keeping its prefix on the new line.
+    pub fn hard_wrap_line(&mut self) {
+        let (row, col) = self.input.cursor();
+        let line = &self.input.lines()[row];
+        let Some((kept, moved)) = wrap_point(line, self.fill_column, self.input.tab_length() as usize) else {
+            return;
+        };
+
+        self.auto_closers.clear();
+        let continuation = split_line(line).continuation();
+        let lines = [line[..kept].to_string(), format!("{}{}", continuation, &line[moved..])];
+        let moved_col = line[..moved].chars().count();
+        let cursor = if col >= moved_col {
+            (row + 1, continuation.chars().count() + col - moved_col)
+        } else {
+            (row, col.min(lines[0].chars().count()))
+        };
+        self.set_lines(row, row, &lines, None, cursor);
+    }
+
+    // Replaces the text between `start_col` and the cursor with `word`.
+    pub fn complete_word(&mut self, start_col: usize, word: &str) {
+        let (row, col) = self.input.cursor();
+        let line: Vec<char> = self.input.lines()[row].chars().collect();
+        let start_col = start_col.min(col);
+        let before: String = line[..start_col].iter().collect();
+        let after: String = line[col..].iter().collect();
+        let cursor = (row, start_col + word.chars().count());
+        self.set_lines(row, row, &[format!("{}{}{}", before, word, after)], None, cursor);
+    }
+
+    // Applies the whitespace cleanup to the buffer as one undoable change.
+    pub fn clean_whitespace(&mut self) {
+        let lines = self.input.lines();
+        let cleaned = self.cleanup.apply(lines);
+        let Some(first) = (0..lines.len()).find(|&row| cleaned.get(row) != Some(&lines[row])) else {
+            return;
+        };
+        let first = first.min(cleaned.len() - 1);
+        let last = lines.len() - 1;
+        let row = self.input.cursor().0.min(cleaned.len() - 1);
+        let col = self.input.cursor().1.min(cleaned[row].chars().count());
+        self.set_lines(first, last, &cleaned[first..], None, (row, col));
+    }
+
+    // Expands the snippet whose trigger is the word before the cursor.
+    pub fn expand_snippet_at_cursor(&mut self) -> bool {
+        let (row, col) = self.input.cursor();
+        let (start_col, word) = word_prefix(&self.input.lines()[row], col);
+        let Some(snippet) = self.snippets.iter().find(|snippet| !word.is_empty() && snippet.trigger == word) else {
+            return false;
+        };
+        let body = snippet.body.clone();
+        self.input.cancel_selection();
+        self.insert_snippet(&body, (row, start_col));
+        true
+    }
+
+    // Inserts a snippet in place of the selection, or of the text between `from` and the cursor.
+    pub fn insert_snippet(&mut self, body: &str, from: Pos) {
+        let selection = self.selected_text().unwrap_or_default();
+        let (start, end) = self.input.selection_range().unwrap_or((from, self.input.cursor()));
+        let filename = self.display_name().to_string();
+        let variable = |name: &str| match name {
+            "DATE" => Some(Local::now().format("%Y-%m-%d").to_string()),
+            "TIME" => Some(Local::now().format("%H:%M").to_string()),
+            "FILENAME" => Some(filename.clone()),
+            "SELECTION" => Some(selection.clone()),
+            _ => None,
+        };
+
+        let lines = self.input.lines();
+        let line_indent = leading_whitespace(&lines[start.0]).to_string();
+        let expansion = expand(body, variable, &self.indent.unit(), &line_indent);
+        let before: String = lines[start.0].chars().take(start.1).collect();
+        let after: String = lines[end.0].chars().skip(end.1).collect();
+
+        let mut new_lines = expansion.lines;
+        new_lines[0].insert_str(0, &before);
+        new_lines.last_mut().unwrap().push_str(&after);
+        let place = |(row, col): Pos| if row == 0 { (start.0, start.1 + col) } else { (start.0 + row, col) };
+        let mut stops = expansion.stops;
+        for range in stops.iter_mut().flat_map(|stop| stop.ranges.iter_mut()) {
+            *range = (place(range.0), place(range.1));
+        }
+
+        self.set_lines(start.0, end.0, &new_lines, None, start);
+        self.enter_snippet_stop(SnippetSession { stops, current: 0 });
+    }
+
+    // Selects the placeholder of the session's current stop, ending the session at the last one.
+    fn enter_snippet_stop(&mut self, session: SnippetSession) {
+        let (start, end) = session.stops[session.current].ranges[0];
+        let anchor = (start != end).then_some(start);
+        self.restore_selection(anchor, end);
+        self.snippet_session = (session.current + 1 < session.stops.len()).then_some(session);
+    }
+
+    // Copies the current placeholder to its mirrors and moves to the next stop.
+    pub fn next_snippet_stop(&mut self) -> bool {
+        let Some(mut session) = self.snippet_session.take() else {
+            return false;
+        };
+        let current = session.current;
+        let (start, old_end) = session.stops[current].ranges[0];
+        // Typing replaced the placeholder, so it now ends at the cursor.
+        let new_end = if self.input.is_selecting() { old_end } else { self.input.cursor() };
+        if new_end < start {
+            return false;
+        }
+        session.shift_after(old_end, new_end, Some((current, 0)));
+        session.stops[current].ranges[0].1 = new_end;
+
+        let text = self.text_in_range(start, new_end);
+        let checkpoint = self.checkpoint(session.rows());
+        for mirror in 1..session.stops[current].ranges.len() {
+            let (mirror_start, mirror_end) = session.stops[current].ranges[mirror];
+            self.input.cancel_selection();
+            self.move_cursor_to(mirror_start.0, mirror_start.1);
+            if mirror_end != mirror_start {
+                self.input.start_selection();
+                self.move_cursor_to(mirror_end.0, mirror_end.1);
+            }
+            self.input.insert_str(&text);
+            let mirror_new_end = end_of(mirror_start, &text);
+            session.shift_after(mirror_end, mirror_new_end, Some((current, mirror)));
+            session.stops[current].ranges[mirror].1 = mirror_new_end;
+        }
+        self.commit(checkpoint);
+
+        session.current += 1;
+        self.enter_snippet_stop(session);
+        true
+    }
+
+    // Replaces the text between two positions as one undoable change.
+    pub fn replace_range(&mut self, start: Pos, end: Pos, text: &str, cursor: Pos) {
+        let lines = self.input.lines();
+        let before: String = lines[start.0].chars().take(start.1).collect();
+        let after: String = lines[end.0].chars().skip(end.1).collect();
+        let replaced: Vec<String> = format!("{}{}{}", before, text, after).split('\n').map(String::from).collect();
+        self.set_lines(start.0, end.0, &replaced, None, cursor);
+    }
+
+    pub fn insert_line_above(&mut self) {
+        let row = self.input.cursor().0;
+        let lines = [String::new(), self.input.lines()[row].clone()];
+        self.set_lines(row, row, &lines, None, (row, 0));
+    }
+
+    pub fn insert_line_below(&mut self) {
+        let row = self.input.cursor().0;
+        let lines = [self.input.lines()[row].clone(), String::new()];
+        self.set_lines(row, row, &lines, None, (row + 1, 0));
+    }
+
+    pub fn transform_lines(&mut self, transform: impl FnOnce(&[String]) -> Vec<String>) {
+        let (first, last) = self.selected_rows();
+        let mut lines = transform(&self.input.lines()[first..=last]);
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+
+        let end = first + lines.len() - 1;
+        if self.input.is_selecting() {
+            let end_col = lines[lines.len() - 1].chars().count();
+            self.set_lines(first, last, &lines, Some((first, 0)), (end, end_col));
+        } else {
+            let cursor = (self.input.cursor().0.min(end), self.input.cursor().1);
+            self.set_lines(first, last, &lines, None, cursor);
+        }
+    }
+
+    // Replaces the selected text, or the current line, keeping the result selected.
+    pub fn transform_text(&mut self, transform: impl FnOnce(&str) -> String) {
+        let selecting = self.input.is_selecting();
+        let cursor = self.input.cursor();
+        let (start, end) = self.input.selection_range().unwrap_or_else(|| {
+            let row = cursor.0;
+            ((row, 0), (row, self.input.lines()[row].chars().count()))
+        });
+        let text = self.text_in_range(start, end);
+        let replaced = transform(&text);
+        if replaced == text {
+            return;
+        }
+
+        let checkpoint = self.checkpoint((start.0, end.0));
+        self.input.cancel_selection();
+        self.move_cursor_to(start.0, start.1);
+        self.input.start_selection();
+        self.move_cursor_to(end.0, end.1);
+        self.input.insert_str(&replaced);
+        if selecting {
+            let end = self.input.cursor();
+            self.restore_selection(Some(start), end);
+        } else {
+            self.move_cursor_to(cursor.0, cursor.1);
+        }
+        self.commit(checkpoint);
+    }
+
+    // The cursor row and its column on screen, with tabs and wide characters expanded.
+    pub fn display_cursor(&self) -> (usize, usize) {
+        let (row, col) = self.input.cursor();
+        let before: String = self.input.lines()[row].chars().take(col).collect();
+        (row, display_width(&before, self.input.tab_length() as usize))
+    }
+
+    pub fn selected_text(&self) -> Option<String> {
+        let (start, end) = self.input.selection_range()?;
+        Some(self.text_in_range(start, end))
+    }
+
+    pub fn text_in_range(&self, start: (usize, usize), end: (usize, usize)) -> String {
+        let lines = self.input.lines();
+        if start.0 == end.0 {
+            return lines[start.0].chars().skip(start.1).take(end.1.saturating_sub(start.1)).collect();
+        }
+        let mut text: String = lines[start.0].chars().skip(start.1).collect();
+        for line in &lines[start.0 + 1..end.0] {
+            text.push('\n');
+            text.push_str(line);
+        }
+        text.push('\n');
+        text.extend(lines[end.0].chars().take(end.1));
+        text
+    }
+
+    pub fn insert_newline_with_indent(&mut self) {
+        let checkpoint = self.checkpoint(self.edited_rows());
+        if self.input.is_selecting() {
+            self.input.delete_str(0);
+        }
+        let (row, col) = self.input.cursor();
+        let indent: String = leading_whitespace(&self.input.lines()[row]).chars().take(col).collect();
+        self.input.insert_newline();
+        self.input.insert_str(indent);
+        self.commit(checkpoint);
+    }
+
+    // Backspace inside space indentation removes a whole indent level.
+    pub fn delete_indent_backward(&mut self) -> bool {
+        if self.input.is_selecting() || self.indent.is_hard_tab() {
+            return false;
+        }
+        let (row, col) = self.input.cursor();
+        let line = &self.input.lines()[row];
+        if col == 0 || !line.chars().take(col).all(|c| c == ' ') {
+            return false;
+        }
+
+        let width = self.indent.width().max(1) as usize;
+        let len = match col % width {
+            0 => width,
+            remainder => remainder,
+        };
+        let checkpoint = self.checkpoint((row, row));
+        self.move_cursor_to(row, col - len);
+        self.input.delete_str(len);
+        self.commit(checkpoint);
+        true
+    }
+
+    pub fn move_cursor_to(&mut self, row: usize, col: usize) {
+        let row = row.min(self.input.lines().len() - 1);
+        if let (Ok(row), Ok(col)) = (u16::try_from(row), u16::try_from(col)) {
+            self.input.move_cursor(CursorMove::Jump(row, col));
+            return;
+        }
+
+        self.input.move_cursor(CursorMove::Top);
+        for _ in 0..row {
+            self.input.move_cursor(CursorMove::Down);
+        }
+        self.input.move_cursor(CursorMove::Head);
+        for _ in 0..col.min(self.input.lines()[row].chars().count()) {
+            self.input.move_cursor(CursorMove::Forward);
+        }
+    }
+
+    // Pages move by lines on screen, so folded lines are not counted.
+    pub fn page_down(&mut self) {
+        let height = self.viewport.height.get().max(1);
+        let (row, col) = self.input.cursor();
+        let line_count = self.input.lines().len();
+        let top = self.viewport.top_row.get();
+        let down = |row: usize| self.folds.visible_rows(row, line_count).take(height + 1).last().unwrap_or(row);
+        let (top, row) = (down(top), down(row));
+        self.viewport.top_row.set(top);
+        self.move_cursor_to(row, col);
+    }
+
+    pub fn page_up(&mut self) {
+        let height = self.viewport.height.get().max(1);
+        let (row, col) = self.input.cursor();
+        let top = self.viewport.top_row.get();
+        let up = |row: usize| self.folds.visible_rows_up(row).take(height + 1).last().unwrap_or(row);
+        let (top, row) = (up(top), up(row));
+        self.viewport.top_row.set(top);
+        self.move_cursor_to(row, col);
+    }
+
+    // Moves the cursor one row up or down on screen, following soft-wrapped lines and
+    // skipping folded ones.
+    pub fn move_visual_line(&mut self, down: bool) {
+        let width = self.viewport.width.get();
+        let line_count = self.input.lines().len();
+        let (row, col) = self.input.cursor();
+        let (next_row, prev_row) = (self.folds.next_visible(row, line_count), self.folds.prev_visible(row));
+        if (self.soft_wrap == SoftWrap::Off || width == 0) && self.folds.is_empty() {
+            self.input.move_cursor(if down { CursorMove::Down } else { CursorMove::Up });
+            return;
+        }
+        if self.soft_wrap == SoftWrap::Off || width == 0 {
+            if let Some(row) = if down { next_row } else { prev_row } {
+                self.move_cursor_to(row, col);
+            }
+            return;
+        }
+
+        let tab_length = self.input.tab_length() as usize;
+        let wrap = |line: &str| {
+            let cells = line_cells_with_end(line, tab_length, Style::default());
+            let segments = wrap_cells(&cells, width, self.soft_wrap);
+            (cells, segments)
+        };
+
+        let (cells, segments) = wrap(&self.input.lines()[row]);
+        let index = segments.iter().position(|segment| segment.contains(&col)).unwrap_or(segments.len() - 1);
+        let offset: usize = cells[segments[index].start..col.min(cells.len())].iter().map(|cell| cell.width).sum();
+
+        let (target_row, target_cells, target) = if down && index + 1 < segments.len() {
+            (row, cells, segments[index + 1].clone())
+        } else if let Some(next_row) = next_row.filter(|_| down) {
+            let (cells, segments) = wrap(&self.input.lines()[next_row]);
+            (next_row, cells, segments[0].clone())
+        } else if !down && index > 0 {
+            (row, cells, segments[index - 1].clone())
+        } else if let Some(prev_row) = prev_row.filter(|_| !down) {
+            let (cells, segments) = wrap(&self.input.lines()[prev_row]);
+            (prev_row, cells, segments[segments.len() - 1].clone())
+        } else {
+            return;
+        };
+
+        let mut used = 0;
+        let mut target_col = target.end - 1;
+        for cell in &target_cells[target.clone()] {
+            if used + cell.width > offset {
+                target_col = cell.col;
+                break;
+            }
+            used += cell.width;
+        }
+        self.move_cursor_to(target_row, target_col);
+    }
+
+    pub fn jump_to_matching_bracket(&mut self) {
+        if let Some((_, (row, col))) = find_matching(self.input.lines(), self.input.cursor()) {
+            self.input.cancel_selection();
+            self.move_cursor_to(row, col);
+        }
+    }
+
+    // Closes the innermost open block at the cursor, returning false when there is none.
+    pub fn fold_at_cursor(&mut self) -> bool {
+        let (row, col) = self.input.cursor();
+        let mut ranges = fold_ranges(self.input.lines(), self.fold_method, self.input.tab_length() as usize);
+        ranges.retain(|&fold| !self.folds.is_closed(fold));
+        let Some(fold) = range_at(&ranges, row) else {
+            return false;
+        };
+        self.folds.close(fold);
+        self.input.cancel_selection();
+        if self.folds.is_hidden(row) {
+            let header = self.folds.prev_visible(row).unwrap_or(fold.start);
+            self.move_cursor_to(header, col);
+        }
+        true
+    }
+
+    pub fn unfold_at_cursor(&mut self) -> bool {
+        self.folds.open(self.input.cursor().0)
+    }
+
+    pub fn toggle_fold(&mut self) -> bool {
+        self.unfold_at_cursor() || self.fold_at_cursor()
+    }
+
+    pub fn fold_all(&mut self) {
+        let ranges = fold_ranges(self.input.lines(), self.fold_method, self.input.tab_length() as usize);
+        self.folds.close_all(ranges);
+        let (row, col) = self.input.cursor();
+        if let Some(header) = self.folds.prev_visible(row).filter(|_| self.folds.is_hidden(row)) {
+            self.input.cancel_selection();
+            self.move_cursor_to(header, col);
+        }
+    }
+
+    // Moves the cursor out of a fold it was moved into from `before`: stepping into a fold
+    // goes on to the next visible line, anything else, like a jump, opens the fold.
+    pub fn skip_folds(&mut self, before: (usize, usize)) {
+        let (row, _) = self.input.cursor();
+        if !self.folds.is_hidden(row) {
+            return;
+        }
+        let line_count = self.input.lines().len();
+        let header = self.folds.prev_visible(row);
+        let next = self.folds.next_visible(row, line_count);
+        if before.0 + 1 == row && header == Some(before.0) {
+            match next {
+                Some(next) => self.move_cursor_to(next, 0),
+                None => self.move_cursor_to(before.0, before.1),
+            }
+        } else if row + 1 == before.0 && next == Some(before.0) {
+            let header = header.unwrap_or(0);
+            self.move_cursor_to(header, self.input.lines()[header].chars().count());
+        } else {
+            self.folds.reveal(row);
+        }
+    }
+
+    pub fn insert_char_with_pairs(&mut self, c: char) {
+        let (row, col) = self.input.cursor();
+        let line = &self.input.lines()[row];
+        let next = line.chars().nth(col);
+        let prev = col.checked_sub(1).and_then(|prev| line.chars().nth(prev));
+
+        if is_closer(c) && next == Some(c) && self.auto_closers.last() == Some(&(row, col)) {
+            self.auto_closers.pop();
+            self.input.move_cursor(CursorMove::Forward);
+            return;
+        }
+
+        if self.input.is_selecting() {
+            self.auto_closers.clear();
+        }
+        let checkpoint = self.checkpoint(self.edited_rows());
+        let closer = auto_close(c, prev, next).filter(|_| !self.input.is_selecting());
+        let inserted = if closer.is_some() { 2 } else { 1 };
+        for position in self.auto_closers.iter_mut().filter(|(r, c)| *r == row && *c >= col) {
+            position.1 += inserted;
+        }
+
+        self.input.insert_char(c);
+        if let Some(closer) = closer {
+            self.input.insert_char(closer);
+            self.input.move_cursor(CursorMove::Back);
+            self.auto_closers.push((row, col + 1));
+        }
+        self.commit(checkpoint);
+    }
+
+    // Replaces the character under the cursor; at the end of the line it inserts.
+    pub fn overwrite_char(&mut self, c: char) {
+        let checkpoint = self.checkpoint(self.edited_rows());
+        let (row, col) = self.input.cursor();
+        if !self.input.is_selecting() && col < self.input.lines()[row].chars().count() {
+            self.input.delete_next_char();
+        }
+        self.input.insert_char(c);
+        self.commit(checkpoint);
+    }
+
+    // A plain Backspace, moving the auto-inserted closers after the cursor along with the text.
+    pub fn delete_char_backward(&mut self, key: KeyEvent) {
+        let (row, col) = self.input.cursor();
+        if col == 0 || self.input.is_selecting() {
+            self.auto_closers.clear();
+        }
+        for position in self.auto_closers.iter_mut().filter(|(r, c)| *r == row && *c >= col) {
+            position.1 -= 1;
+        }
+        self.handle_key(key);
+    }
+
+    // Backspace between an opener and its auto-inserted closer removes both.
+    pub fn delete_auto_pair(&mut self) -> bool {
+        let (row, col) = self.input.cursor();
+        if col == 0 || self.auto_closers.last() != Some(&(row, col)) {
+            return false;
+        }
+        let line = &self.input.lines()[row];
+        let prev = line.chars().nth(col - 1);
+        let next = line.chars().nth(col);
+        if prev.and_then(closer_for).is_none_or(|closer| Some(closer) != next) {
+            return false;
+        }
+
+        let checkpoint = self.checkpoint((row, row));
+        self.auto_closers.pop();
+        self.input.move_cursor(CursorMove::Back);
+        self.input.delete_str(2);
+        self.commit(checkpoint);
+        true
+    }
+
+    pub fn cut_line(&mut self) -> String {
+        let checkpoint = self.checkpoint(self.edited_rows());
+        if !self.input.is_selecting() {
+            let (row, _) = self.input.cursor();
+            self.input.move_cursor(CursorMove::Head);
+            self.input.start_selection();
+            if row + 1 < self.input.lines().len() {
+                self.input.move_cursor(CursorMove::Down);
+                self.input.move_cursor(CursorMove::Head);
+            } else {
+                self.input.move_cursor(CursorMove::End);
+            }
+        }
+
+        let text = if self.input.cut() { self.input.yank_text() } else { String::new() };
+        self.commit(checkpoint);
+        text
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let checkpoint = self.checkpoint(self.edited_rows());
+        let modified = self.input.input(key);
+        self.commit(checkpoint);
+        modified
+    }
+
+    pub fn replace_selection_or_all(&mut self, text: &str) {
+        if !self.input.is_selecting() {
+            self.input.select_all();
+        }
+        let checkpoint = self.checkpoint(self.edited_rows());
+        self.input.insert_str(text);
+        self.commit(checkpoint);
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        self.input.cancel_selection();
+        let checkpoint = self.checkpoint(self.edited_rows());
+        self.input.insert_str(text);
+        self.commit(checkpoint);
+    }
+
+    // Rows an edit at the cursor or over the selection can touch.
+    fn edited_rows(&self) -> (usize, usize) {
+        match self.input.selection_range() {
+            Some(((start_row, _), (end_row, _))) => (start_row, end_row),
+            None => (self.input.cursor().0, self.input.cursor().0),
+        }
+    }
+
+    // Every change between `checkpoint` and `commit` must stay within `rows`
+    // plus one line on each side. It is then undone as a single step.
+    fn checkpoint(&self, rows: (usize, usize)) -> Checkpoint {
+        let line_count = self.input.lines().len();
+        let first = rows.0.saturating_sub(1);
+        let last = (rows.1 + 1).min(line_count - 1);
+        Checkpoint {
+            first,
+            last,
+            line_count,
+            before: self.input.lines()[first..=last].to_vec(),
+            cursor: self.input.cursor(),
+        }
+    }
+
+    fn commit(&mut self, checkpoint: Checkpoint) {
+        let Checkpoint { first, last, line_count, before, cursor } = checkpoint;
+        let lines = self.input.lines();
+        let end = (last + 1 + lines.len()).saturating_sub(line_count).max(first + 1);
+        let after = lines[first..end].to_vec();
+        if after != before {
+            self.follow_change(first, &before, &after);
+            let cursor_after = self.input.cursor();
+            self.history.push(Change { row: first, before, after, cursor_before: cursor, cursor_after });
+        }
+    }
+
+    // Keeps marks and folds on their lines when lines `row..row + before.len()` become `after`.
+    fn follow_change(&mut self, row: usize, before: &[String], after: &[String]) {
+        self.folds.shift(row, before, after);
+        let marks = std::mem::take(&mut self.marks);
+        self.marks = marks
+            .into_iter()
+            .filter_map(|(mark, (mark_row, col))| Some((mark, (shift_row(mark_row, row, before, after)?, col))))
+            .collect();
+    }
+
+    fn apply_change(&mut self, row: usize, count: usize, lines: &[String], cursor: (usize, usize)) {
+        self.replace_lines(row, row + count - 1, lines);
+        self.move_cursor_to(cursor.0, cursor.1);
+        self.auto_closers.clear();
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(change) = self.history.undo() {
+            self.follow_change(change.row, &change.after, &change.before);
+            self.apply_change(change.row, change.after.len(), &change.before, change.cursor_before);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(change) = self.history.redo() {
+            self.follow_change(change.row, &change.before, &change.after);
+            self.apply_change(change.row, change.before.len(), &change.after, change.cursor_after);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests{
     use super::*;
+    use std::fs;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -79,6 +1053,230 @@ mod tests{
         assert_eq!(buffer.input.lines(), vec!["Hello, world!".to_string()]);
     }
 
+    #[test]
+    fn test_move_cursor_to() {
+        let lines = (0..10).map(|i| format!("line {}", i)).collect();
+        let mut buffer = Buffer::new(TextArea::new(lines), None);
+
+        buffer.move_cursor_to(4, 2);
+        assert_eq!(buffer.input.cursor(), (4, 2));
+
+        buffer.move_cursor_to(100, 100);
+        assert_eq!(buffer.input.cursor(), (9, 6));
+    }
+
+    #[test]
+    fn test_page_down_and_up() {
+        let lines = (0..30).map(|i| i.to_string()).collect();
+        let mut buffer = Buffer::new(TextArea::new(lines), None);
+        buffer.viewport.height.set(10);
+
+        buffer.page_down();
+        assert_eq!(buffer.input.cursor().0, 10);
+        assert_eq!(buffer.viewport.top_row.get(), 10);
+
+        buffer.page_up();
+        assert_eq!(buffer.input.cursor().0, 0);
+        assert_eq!(buffer.viewport.top_row.get(), 0);
+    }
+
+    fn create_buffer(lines: &[&str]) -> Buffer<'static> {
+        let lines = lines.iter().map(|line| line.to_string()).collect();
+        Buffer::new(TextArea::new(lines), None)
+    }
+
+    #[test]
+    fn test_set_indent_configures_tab_key() {
+        let mut buffer = create_buffer(&["x"]);
+        buffer.set_indent(Indent::Tabs(8));
+        buffer.input.insert_tab();
+        assert_eq!(buffer.input.lines(), vec!["\tx"]);
+
+        buffer.set_indent(Indent::Spaces(2));
+        buffer.input.insert_tab();
+        assert_eq!(buffer.input.lines(), vec!["\t  x"]);
+    }
+
+    #[test]
+    fn test_insert_newline_with_indent() {
+        let mut buffer = create_buffer(&["    foo()"]);
+        buffer.input.move_cursor(CursorMove::End);
+        buffer.insert_newline_with_indent();
+        buffer.input.insert_str("bar");
+
+        assert_eq!(buffer.input.lines(), vec!["    foo()", "    bar"]);
+        assert_eq!(buffer.input.cursor(), (1, 7));
+    }
+
+    #[test]
+    fn test_insert_newline_inside_indentation() {
+        let mut buffer = create_buffer(&["\t\tfoo"]);
+        buffer.move_cursor_to(0, 1);
+        buffer.insert_newline_with_indent();
+        assert_eq!(buffer.input.lines(), vec!["\t", "\t\tfoo"]);
+    }
+
+    #[test]
+    fn test_indent_and_dedent_selected_lines() {
+        let mut buffer = create_buffer(&["a", "", "b", "c"]);
+        buffer.input.start_selection();
+        buffer.move_cursor_to(2, 1);
+
+        buffer.indent_lines();
+        assert_eq!(buffer.input.lines(), vec!["    a", "", "    b", "c"]);
+        assert_eq!(buffer.input.selection_range(), Some(((0, 0), (2, 5))));
+
+        buffer.dedent_lines();
+        assert_eq!(buffer.input.lines(), vec!["a", "", "b", "c"]);
+        assert_eq!(buffer.input.selection_range(), Some(((0, 0), (2, 1))));
+    }
+
+    #[test]
+    fn test_dedent_current_line() {
+        let mut buffer = create_buffer(&["\t\tfoo"]);
+        buffer.set_indent(Indent::Tabs(4));
+        buffer.move_cursor_to(0, 3);
+
+        buffer.dedent_lines();
+        assert_eq!(buffer.input.lines(), vec!["\tfoo"]);
+        assert_eq!(buffer.input.cursor(), (0, 2));
+    }
+
+    #[test]
+    fn test_delete_indent_backward() {
+        let mut buffer = create_buffer(&["      foo"]);
+        buffer.move_cursor_to(0, 6);
+
+        assert!(buffer.delete_indent_backward());
+        assert_eq!(buffer.input.lines(), vec!["    foo"]);
+        assert!(buffer.delete_indent_backward());
+        assert_eq!(buffer.input.lines(), vec!["foo"]);
+        assert!(!buffer.delete_indent_backward());
+    }
+
+    #[test]
+    fn test_init_detects_indentation() {
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        write!(temp_file, "a\n\tb\n  c\n").expect("Failed to write to temp file");
+        let path = temp_file.path().to_str().unwrap().to_string();
+
+        let mut buffer = Buffer::default();
+        buffer.init(&path).unwrap();
+
+        assert_eq!(buffer.indent, Indent::Tabs(4));
+        assert!(buffer.input.hard_tab_indent());
+        assert!(buffer.mixed_indent);
+    }
+
+    #[test]
+    fn test_init_applies_editorconfig() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".editorconfig"), "root = true\n[*.txt]\nindent_style = tab\ntab_width = 8\nend_of_line = crlf\ncharset = latin1\ninsert_final_newline = false\nmax_line_length = 60\n").unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(&path, b"caf\xE9\r\n    b\r\n").unwrap();
+
+        let mut buffer = Buffer::default();
+        buffer.init(path.to_str().unwrap()).unwrap();
+        assert_eq!(buffer.input.lines(), vec!["café", "    b"]);
+        assert_eq!(buffer.indent, Indent::Tabs(8));
+        assert_eq!(buffer.fill_column, 60);
+        assert_eq!(buffer.file_bytes(buffer.input.lines()), b"caf\xE9\r\n    b");
+    }
+
+    #[test]
+    fn test_convert_indent_whole_buffer() {
+        let mut buffer = create_buffer(&["a", "\tb", "\t\tc", "  d"]);
+        buffer.set_indent(Indent::Tabs(4));
+        buffer.mixed_indent = true;
+        buffer.move_cursor_to(2, 3);
+
+        buffer.convert_indent(Indent::Spaces(2));
+
+        assert_eq!(buffer.input.lines(), vec!["a", "  b", "    c", "  d"]);
+        assert_eq!(buffer.input.cursor(), (2, 5));
+        assert_eq!(buffer.indent, Indent::Spaces(2));
+        assert!(!buffer.mixed_indent);
+    }
+
+    #[test]
+    fn test_insert_char_with_pairs() {
+        let mut buffer = create_buffer(&[""]);
+        buffer.insert_char_with_pairs('(');
+        buffer.insert_char_with_pairs('"');
+        buffer.insert_char_with_pairs('a');
+        assert_eq!(buffer.input.lines(), vec!["(\"a\")"]);
+
+        buffer.insert_char_with_pairs('"');
+        buffer.insert_char_with_pairs(')');
+        assert_eq!(buffer.input.lines(), vec!["(\"a\")"]);
+        assert_eq!(buffer.input.cursor(), (0, 5));
+
+        buffer.insert_char_with_pairs(')');
+        assert_eq!(buffer.input.lines(), vec!["(\"a\"))"]);
+    }
+
+    #[test]
+    fn test_display_cursor_expands_tabs() {
+        let mut buffer = create_buffer(&["\tab"]);
+        buffer.move_cursor_to(0, 2);
+        assert_eq!(buffer.display_cursor(), (0, 5));
+    }
+
+    #[test]
+    fn test_overwrite_char() {
+        let mut buffer = create_buffer(&["ab|", "cd"]);
+        for c in "xyz!".chars() {
+            buffer.overwrite_char(c);
+        }
+        assert_eq!(buffer.input.lines(), vec!["xyz!", "cd"]);
+        buffer.undo();
+        assert_eq!(buffer.input.lines(), vec!["xyz", "cd"]);
+    }
+
+    #[test]
+    fn test_delete_auto_pair() {
+        let mut buffer = create_buffer(&[""]);
+        buffer.insert_char_with_pairs('[');
+        assert!(buffer.delete_auto_pair());
+        assert_eq!(buffer.input.lines(), vec![""]);
+        assert!(!buffer.delete_auto_pair());
+    }
+
+    #[test]
+    fn test_jump_to_matching_bracket() {
+        let mut buffer = create_buffer(&["if (a) {", "}"]);
+        buffer.move_cursor_to(0, 7);
+        buffer.jump_to_matching_bracket();
+        assert_eq!(buffer.input.cursor(), (1, 0));
+        buffer.jump_to_matching_bracket();
+        assert_eq!(buffer.input.cursor(), (0, 7));
+    }
+
+    #[test]
+    fn test_cut_line() {
+        let textarea = TextArea::new(vec!["one".to_string(), "two".to_string()]);
+        let mut buffer = Buffer::new(textarea, None);
+
+        assert_eq!(buffer.cut_line(), "one\n");
+        assert_eq!(buffer.input.lines(), vec!["two".to_string()]);
+
+        assert_eq!(buffer.cut_line(), "two");
+        assert_eq!(buffer.input.lines(), vec!["".to_string()]);
+
+        assert_eq!(buffer.cut_line(), "");
+    }
+
+    #[test]
+    fn test_cut_line_with_selection() {
+        let textarea = TextArea::new(vec!["hello world".to_string()]);
+        let mut buffer = Buffer::new(textarea, None);
+        buffer.input.start_selection();
+        buffer.input.move_cursor(CursorMove::WordForward);
+
+        assert_eq!(buffer.cut_line(), "hello ");
+        assert_eq!(buffer.input.lines(), vec!["world".to_string()]);
+    }
+
     #[test]
     fn test_custom_text_area() {
         let buffer = Buffer::default();
@@ -87,4 +1285,293 @@ mod tests{
         assert_eq!(text_area.lines(), lines);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_with_config_and_modified() {
+        let config = Config { tab_width: 2, ..Config::default() };
+        let mut buffer = Buffer::with_config(&config);
+        assert_eq!(buffer.indent, Indent::Spaces(2));
+
+        buffer.mark_saved();
+        assert!(!buffer.is_modified());
+        buffer.input.insert_char('x');
+        assert!(buffer.is_modified());
+    }
+
+    #[test]
+    fn test_move_lines() {
+        let mut buffer = create_buffer(&["a", "b", "c"]);
+        buffer.move_cursor_to(1, 0);
+        buffer.move_lines_up();
+        assert_eq!(buffer.input.lines(), vec!["b", "a", "c"]);
+        assert_eq!(buffer.input.cursor(), (0, 0));
+        buffer.move_lines_up();
+        assert_eq!(buffer.input.lines(), vec!["b", "a", "c"]);
+
+        buffer.move_lines_down();
+        buffer.move_lines_down();
+        assert_eq!(buffer.input.lines(), vec!["a", "c", "b"]);
+        assert_eq!(buffer.input.cursor(), (2, 0));
+    }
+
+    #[test]
+    fn test_move_selected_lines_keeps_selection() {
+        let mut buffer = create_buffer(&["a", "b", "c", "d"]);
+        buffer.input.start_selection();
+        buffer.move_cursor_to(1, 1);
+        buffer.move_lines_down();
+        assert_eq!(buffer.input.lines(), vec!["c", "a", "b", "d"]);
+        assert_eq!(buffer.input.selection_range(), Some(((1, 0), (2, 1))));
+    }
+
+    #[test]
+    fn test_duplicate_lines() {
+        let mut buffer = create_buffer(&["a", "b"]);
+        buffer.move_cursor_to(0, 1);
+        buffer.duplicate_lines();
+        assert_eq!(buffer.input.lines(), vec!["a", "a", "b"]);
+        assert_eq!(buffer.input.cursor(), (1, 1));
+    }
+
+    #[test]
+    fn test_delete_lines() {
+        let mut buffer = create_buffer(&["a", "b", "c"]);
+        buffer.move_cursor_to(1, 0);
+        buffer.delete_lines();
+        assert_eq!(buffer.input.lines(), vec!["a", "c"]);
+        buffer.move_cursor_to(1, 0);
+        buffer.delete_lines();
+        assert_eq!(buffer.input.lines(), vec!["a"]);
+        buffer.delete_lines();
+        assert_eq!(buffer.input.lines(), vec![""]);
+    }
+
+    #[test]
+    fn test_join_lines() {
+        let mut buffer = create_buffer(&["let a =", "    1;", "", "b"]);
+        buffer.join_lines();
+        assert_eq!(buffer.input.lines(), vec!["let a = 1;", "", "b"]);
+        assert_eq!(buffer.input.cursor(), (0, 8));
+
+        buffer.input.start_selection();
+        buffer.move_cursor_to(2, 1);
+        buffer.join_lines();
+        assert_eq!(buffer.input.lines(), vec!["let a = 1; b"]);
+    }
+
+    #[test]
+    fn test_insert_blank_lines() {
+        let mut buffer = create_buffer(&["a"]);
+        buffer.insert_line_below();
+        assert_eq!(buffer.input.lines(), vec!["a", ""]);
+        assert_eq!(buffer.input.cursor(), (1, 0));
+        buffer.move_cursor_to(0, 0);
+        buffer.insert_line_above();
+        assert_eq!(buffer.input.lines(), vec!["", "a", ""]);
+        assert_eq!(buffer.input.cursor(), (0, 0));
+    }
+
+    #[test]
+    fn test_line_commands_undo_in_one_step() {
+        let mut buffer = create_buffer(&["a", "b", "c"]);
+        buffer.move_cursor_to(1, 1);
+        buffer.move_lines_up();
+        buffer.duplicate_lines();
+        buffer.join_lines();
+        assert_eq!(buffer.input.lines(), vec!["b", "b a", "c"]);
+
+        buffer.undo();
+        assert_eq!(buffer.input.lines(), vec!["b", "b", "a", "c"]);
+        buffer.undo();
+        assert_eq!(buffer.input.lines(), vec!["b", "a", "c"]);
+        buffer.undo();
+        assert_eq!(buffer.input.lines(), vec!["a", "b", "c"]);
+        assert_eq!(buffer.input.cursor(), (1, 1));
+
+        buffer.redo();
+        assert_eq!(buffer.input.lines(), vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn test_fold_and_move_over_folds() {
+        let mut buffer = create_buffer(&["a", "    b", "    c", "d"]);
+        assert!(buffer.fold_at_cursor());
+        buffer.move_visual_line(true);
+        assert_eq!(buffer.input.cursor(), (3, 0));
+        buffer.move_visual_line(false);
+        assert_eq!(buffer.input.cursor(), (0, 0));
+
+        // Stepping right off the end of the first line goes past the fold.
+        buffer.move_cursor_to(0, 1);
+        buffer.input.move_cursor(CursorMove::Forward);
+        buffer.skip_folds((0, 1));
+        assert_eq!(buffer.input.cursor(), (3, 0));
+        buffer.input.move_cursor(CursorMove::Back);
+        buffer.skip_folds((3, 0));
+        assert_eq!(buffer.input.cursor(), (0, 1));
+
+        // Jumping into it opens it.
+        buffer.move_cursor_to(2, 0);
+        buffer.skip_folds((0, 1));
+        assert!(buffer.folds.is_empty());
+
+        buffer.fold_all();
+        assert_eq!(buffer.input.cursor(), (0, 0));
+        assert!(buffer.unfold_at_cursor());
+        assert!(buffer.folds.is_empty());
+        assert!(buffer.toggle_fold());
+        assert!(buffer.folds.is_hidden(1));
+    }
+
+    #[test]
+    fn test_marks_follow_edits() {
+        let mut buffer = create_buffer(&["a", "b", "c"]);
+        buffer.marks.insert('b', (1, 1));
+        buffer.marks.insert('c', (2, 0));
+        buffer.move_cursor_to(0, 0);
+        buffer.insert_line_above();
+        assert_eq!(buffer.marks, BTreeMap::from([('b', (2, 1)), ('c', (3, 0))]));
+
+        buffer.move_cursor_to(2, 0);
+        buffer.move_lines_down();
+        assert_eq!(buffer.marks, BTreeMap::from([('b', (3, 1)), ('c', (2, 0))]));
+        buffer.undo();
+        assert_eq!(buffer.marks, BTreeMap::from([('b', (2, 1)), ('c', (3, 0))]));
+
+        buffer.move_cursor_to(2, 0);
+        buffer.delete_lines();
+        assert_eq!(buffer.marks, BTreeMap::from([('c', (2, 0))]));
+    }
+
+    #[test]
+    fn test_indent_lines_undo_in_one_step() {
+        let mut buffer = create_buffer(&["a", "b"]);
+        buffer.input.select_all();
+        buffer.indent_lines();
+        assert_eq!(buffer.input.lines(), vec!["    a", "    b"]);
+        buffer.undo();
+        assert_eq!(buffer.input.lines(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_transform_lines_keeps_selection() {
+        let mut buffer = create_buffer(&["c", "a", "b", "x"]);
+        buffer.input.start_selection();
+        buffer.move_cursor_to(2, 1);
+        buffer.transform_lines(|lines| lines.iter().rev().cloned().collect());
+        assert_eq!(buffer.input.lines(), vec!["b", "a", "c", "x"]);
+        assert_eq!(buffer.input.selection_range(), Some(((0, 0), (2, 1))));
+
+        buffer.undo();
+        assert_eq!(buffer.input.lines(), vec!["c", "a", "b", "x"]);
+    }
+
+    #[test]
+    fn test_transform_text_on_selection_and_line() {
+        let mut buffer = create_buffer(&["foo bar", "baz"]);
+        buffer.move_cursor_to(0, 4);
+        buffer.input.start_selection();
+        buffer.move_cursor_to(1, 1);
+        assert_eq!(buffer.selected_text(), Some("bar\nb".to_string()));
+
+        buffer.transform_text(|text| text.to_uppercase());
+        assert_eq!(buffer.input.lines(), vec!["foo BAR", "Baz"]);
+        assert_eq!(buffer.input.selection_range(), Some(((0, 4), (1, 1))));
+
+        buffer.input.cancel_selection();
+        buffer.transform_text(|text| text.to_lowercase());
+        assert_eq!(buffer.input.lines(), vec!["foo BAR", "baz"]);
+        assert_eq!(buffer.input.cursor(), (1, 1));
+    }
+
+    #[test]
+    fn test_move_visual_line() {
+        let mut buffer = create_buffer(&["abcdefgh", "xy"]);
+        buffer.soft_wrap = SoftWrap::Chars;
+        buffer.viewport.width.set(4);
+        buffer.move_cursor_to(0, 1);
+
+        buffer.move_visual_line(true);
+        assert_eq!(buffer.input.cursor(), (0, 5));
+        buffer.move_visual_line(true);
+        assert_eq!(buffer.input.cursor(), (0, 8));
+        buffer.move_visual_line(true);
+        assert_eq!(buffer.input.cursor(), (1, 0));
+        buffer.move_visual_line(false);
+        assert_eq!(buffer.input.cursor(), (0, 8));
+        buffer.move_visual_line(false);
+        assert_eq!(buffer.input.cursor(), (0, 4));
+    }
+
+    #[test]
+    fn test_clean_whitespace_is_undoable() {
+        let mut buffer = create_buffer(&["a ", "b\t", "", "  "]);
+        buffer.cleanup = Cleanup { trim_trailing: true, final_newline: true, collapse_blank_lines: true };
+        buffer.move_cursor_to(3, 2);
+
+        buffer.clean_whitespace();
+        assert_eq!(buffer.input.lines(), vec!["a", "b"]);
+        assert_eq!(buffer.input.cursor(), (1, 1));
+
+        buffer.undo();
+        assert_eq!(buffer.input.lines(), vec!["a ", "b\t", "", "  "]);
+    }
+
+    #[test]
+    fn test_justify_paragraph_under_cursor() {
+        let mut buffer = create_buffer(&["intro", "", "> one two", "> three four five", "", "end"]);
+        buffer.fill_column = 12;
+        buffer.move_cursor_to(3, 0);
+
+        buffer.justify();
+        assert_eq!(buffer.input.lines(), vec!["intro", "", "> one two", "> three four", "> five", "", "end"]);
+        assert_eq!(buffer.input.cursor(), (4, 6));
+
+        buffer.undo();
+        assert_eq!(buffer.input.lines(), vec!["intro", "", "> one two", "> three four five", "", "end"]);
+    }
+
+    #[test]
+    fn test_hard_wrap_line() {
+        let mut buffer = create_buffer(&["// aaa bbb ccc"]);
+        buffer.fill_column = 10;
+        buffer.move_cursor_to(0, 14);
+
+        buffer.hard_wrap_line();
+        assert_eq!(buffer.input.lines(), vec!["// aaa bbb", "// ccc"]);
+        assert_eq!(buffer.input.cursor(), (1, 6));
+    }
+
+    #[test]
+    fn test_snippet_tab_stops_and_mirrors() {
+        let mut buffer = create_buffer(&["  fn"]);
+        buffer.snippets = vec![Snippet { trigger: "fn".to_string(), body: "fn ${1:name}() {\n\t$2\n} // $1".to_string() }];
+        buffer.move_cursor_to(0, 4);
+
+        assert!(buffer.expand_snippet_at_cursor());
+        assert_eq!(buffer.input.lines(), vec!["  fn name() {", "      ", "  } // name"]);
+        assert_eq!(buffer.selected_text(), Some("name".to_string()));
+
+        buffer.input.insert_str("main");
+        assert!(buffer.next_snippet_stop());
+        assert_eq!(buffer.input.lines(), vec!["  fn main() {", "      ", "  } // main"]);
+        assert_eq!(buffer.input.cursor(), (1, 6));
+
+        assert!(buffer.next_snippet_stop());
+        assert_eq!(buffer.input.cursor(), (2, 11));
+        assert_eq!(buffer.snippet_session, None);
+        assert!(!buffer.next_snippet_stop());
+    }
+
+    #[test]
+    fn test_snippet_wraps_selection() {
+        let mut buffer = create_buffer(&["a word here"]);
+        buffer.move_cursor_to(0, 2);
+        buffer.input.start_selection();
+        buffer.move_cursor_to(0, 6);
+
+        buffer.insert_snippet("**${SELECTION}**$0", (0, 2));
+        assert_eq!(buffer.input.lines(), vec!["a **word** here"]);
+        assert_eq!(buffer.input.cursor(), (0, 10));
+        assert_eq!(buffer.snippet_session, None);
+    }
+}
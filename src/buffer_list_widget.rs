@@ -1,4 +1,5 @@
 use crate::action_bar::ActionWidget;
+use crate::marks::sync_global_marks;
 use crate::popup::popup_area;
 use crate::state::State;
 use crossterm::event::{KeyCode, KeyEvent};
@@ -92,6 +93,7 @@ impl<'a> BufferListWidget<'a> {
                     state.current_buffer -= 1;
                 }
 
+                sync_global_marks(state);
                 state.buffer_list.remove(buffer_index);
 
                 return Ok(true);
@@ -109,10 +111,9 @@ impl<'a> BufferListWidget<'a> {
         let mut chars = ALPHABET.chars();
 
         for (index, buffer) in state.buffer_list.iter().enumerate() {
-            if let Some(path) = buffer.path.clone() {
-                let buffer_char = chars.next().unwrap_or('-');
-                self.items.push(BufferItem::new(path, index, buffer_char));
-            }
+            let path = buffer.path.clone().unwrap_or_else(|| buffer.display_name().to_string());
+            let buffer_char = chars.next().unwrap_or('-');
+            self.items.push(BufferItem::new(path, index, buffer_char));
         }
     }
 
@@ -0,0 +1,136 @@
+use ratatui::layout::Rect;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+pub const MAX_CANDIDATES: usize = 8;
+pub const AUTO_COMPLETE_CHARS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub row: usize,
+    pub start_col: usize,
+    pub candidates: Vec<String>,
+    pub selected: usize,
+}
+
+impl Completion {
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.candidates.len();
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.checked_sub(1).unwrap_or(self.candidates.len() - 1);
+    }
+
+    pub fn selected_word(&self) -> &str {
+        &self.candidates[self.selected]
+    }
+}
+
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// Start column and text of the word ending at `col`.
+pub fn word_prefix(line: &str, col: usize) -> (usize, String) {
+    let before: Vec<char> = line.chars().take(col).collect();
+    let start = before.iter().rposition(|&c| !is_word_char(c)).map_or(0, |i| i + 1);
+    (start, before[start..].iter().collect())
+}
+
+// Words of `line` with their starting column.
+fn words(line: &str) -> Vec<(usize, String)> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (col, c) in line.chars().chain([' ']).enumerate() {
+        if is_word_char(c) {
+            current.push(c);
+        } else if !current.is_empty() {
+            let start = col - current.chars().count();
+            words.push((start, std::mem::take(&mut current)));
+        }
+    }
+    words
+}
+
+// Words starting with `prefix`, nearest to the cursor first, then from other buffers, most frequent first.
+pub fn candidates(prefix: &str, lines: &[String], cursor: (usize, usize), other_buffers: &[&[String]]) -> Vec<String> {
+    let (cursor_row, start_col) = (cursor.0, cursor.1);
+    let mut scores: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut add = |word: String, distance: usize| {
+        let score = scores.entry(word).or_insert((usize::MAX, 0));
+        score.0 = score.0.min(distance);
+        score.1 += 1;
+    };
+
+    for (row, line) in lines.iter().enumerate() {
+        for (col, word) in words(line) {
+            let typed = row == cursor_row && col == start_col;
+            if !typed && word.len() > prefix.len() && word.starts_with(prefix) {
+                add(word, row.abs_diff(cursor_row));
+            }
+        }
+    }
+    for line in other_buffers.iter().flat_map(|lines| lines.iter()) {
+        for (_, word) in words(line) {
+            if word.len() > prefix.len() && word.starts_with(prefix) {
+                add(word, usize::MAX);
+            }
+        }
+    }
+
+    let mut ranked: Vec<(String, (usize, usize))> = scores.into_iter().collect();
+    ranked.sort_by(|(a, (a_distance, a_count)), (b, (b_distance, b_count))| {
+        (a_distance, Reverse(a_count), a).cmp(&(b_distance, Reverse(b_count), b))
+    });
+    ranked.into_iter().take(MAX_CANDIDATES).map(|(word, _)| word).collect()
+}
+
+// Popup below the word being completed, or above it when there is no room below.
+pub fn popup_rect(word_start: (u16, u16), width: u16, height: u16, bounds: Rect) -> Rect {
+    let width = width.min(bounds.width);
+    let height = height.min(bounds.height);
+    let (x, y) = word_start;
+    let x = x.saturating_sub(1).min(bounds.right().saturating_sub(width)).max(bounds.x);
+    let y = if y + 1 + height <= bounds.bottom() { y + 1 } else { y.saturating_sub(height).max(bounds.y) };
+    Rect { x, y, width, height }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_word_prefix() {
+        assert_eq!(word_prefix("let foo_ba = 1", 10), (4, "foo_ba".to_string()));
+        assert_eq!(word_prefix("x.", 2), (2, String::new()));
+    }
+
+    #[test]
+    fn test_candidates_ranking() {
+        let current = lines(&["format formula", "for", "form fo", "formula"]);
+        let other = lines(&["fortune fortune"]);
+        let ranked = candidates("fo", &current, (2, 5), &[&other]);
+        assert_eq!(ranked, vec!["form", "formula", "for", "format", "fortune"]);
+    }
+
+    #[test]
+    fn test_completion_selection_wraps() {
+        let mut completion = Completion { row: 0, start_col: 0, candidates: lines(&["a", "b"]), selected: 0 };
+        completion.select_previous();
+        assert_eq!(completion.selected_word(), "b");
+        completion.select_next();
+        assert_eq!(completion.selected_word(), "a");
+    }
+
+    #[test]
+    fn test_popup_rect() {
+        let bounds = Rect::new(0, 0, 40, 10);
+        assert_eq!(popup_rect((5, 2), 10, 4, bounds), Rect::new(4, 3, 10, 4));
+        assert_eq!(popup_rect((38, 8), 10, 4, bounds), Rect::new(30, 4, 10, 4));
+    }
+}
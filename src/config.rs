@@ -0,0 +1,297 @@
+use crate::folding::FoldMethod;
+use crate::indent::DEFAULT_TAB_WIDTH;
+use crate::keymap::Keymap;
+use crate::paths::config_dir;
+use crate::theme::Theme;
+use crate::whitespace::Cleanup;
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use toml::{Table, Value};
+
+const CONFIG_FILE: &str = "config.toml";
+const PROJECT_FILE: &str = ".divitext.toml";
+const DEFAULT_LOG_FILE: &str = "divino_editor_log";
+const MAX_TAB_WIDTH: i64 = 16;
+// Settings a project file may not change, so that opening a file in an untrusted
+// checkout cannot choose where divitext writes.
+const USER_ONLY: [&str; 1] = ["log_file"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub tab_width: u8,
+    pub line_numbers: bool,
+    pub theme: Theme,
+    // How often modified buffers are saved, off when None.
+    pub autosave: Option<Duration>,
+    pub log_file: PathBuf,
+    pub keymap: Keymap,
+    pub fold_method: FoldMethod,
+    // Live word count in the status bar.
+    pub word_count: bool,
+    // Words to write in each buffer this session, none when None.
+    pub word_goal: Option<usize>,
+    // Columns after which a vertical guide is drawn, sorted.
+    pub guides: Vec<usize>,
+    // Whitespace cleanup on save, off unless a setting turns it on.
+    pub cleanup: Cleanup,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            tab_width: DEFAULT_TAB_WIDTH,
+            line_numbers: true,
+            theme: Theme::default(),
+            autosave: None,
+            log_file: PathBuf::from(DEFAULT_LOG_FILE),
+            keymap: Keymap::default(),
+            fold_method: FoldMethod::default(),
+            word_count: false,
+            word_goal: None,
+            guides: Vec::new(),
+            cleanup: Cleanup::default(),
+        }
+    }
+}
+
+// One setting of a layer; the values are checked further by `Config::set`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    tab_width: Option<i64>,
+    line_numbers: Option<bool>,
+    theme: Option<String>,
+    autosave: Option<i64>,
+    log_file: Option<PathBuf>,
+    fold_method: Option<String>,
+    word_count: Option<bool>,
+    word_goal: Option<i64>,
+    guides: Option<Columns>,
+    trim_trailing_whitespace: Option<bool>,
+    insert_final_newline: Option<bool>,
+    collapse_blank_lines: Option<bool>,
+    keymap: Option<Table>,
+}
+
+// One column or a list of them.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Columns {
+    One(i64),
+    Many(Vec<i64>),
+}
+
+// Each column is at least 1.
+fn columns(columns: Columns) -> Result<Vec<usize>, String> {
+    let values = match columns {
+        Columns::One(column) => vec![column],
+        Columns::Many(columns) => columns,
+    };
+    let mut columns = values
+        .into_iter()
+        .map(|column| usize::try_from(column).ok().filter(|column| *column > 0).ok_or_else(|| "guides must be positive".to_string()))
+        .collect::<Result<Vec<usize>, String>>()?;
+    columns.sort();
+    columns.dedup();
+    Ok(columns)
+}
+
+// "line 3: ..." on one line, for errors with a position in `content`.
+fn parse_error(content: &str, error: &toml::de::Error) -> String {
+    let message = error.message().lines().collect::<Vec<_>>().join(", ");
+    match error.span() {
+        Some(span) => format!("line {}: {}", content[..span.start].matches('\n').count() + 1, message),
+        None => message,
+    }
+}
+
+impl Config {
+    fn set(&mut self, settings: Settings) -> Result<(), String> {
+        if let Some(width) = settings.tab_width {
+            if !(1..=MAX_TAB_WIDTH).contains(&width) {
+                return Err(format!("tab_width must be between 1 and {}", MAX_TAB_WIDTH));
+            }
+            self.tab_width = width as u8;
+        }
+        if let Some(line_numbers) = settings.line_numbers {
+            self.line_numbers = line_numbers;
+        }
+        if let Some(name) = settings.theme {
+            self.theme = Theme::by_name(&name).ok_or_else(|| format!("unknown theme {}", name))?;
+        }
+        if let Some(seconds) = settings.autosave {
+            let seconds = u64::try_from(seconds).map_err(|_| "autosave must not be negative".to_string())?;
+            self.autosave = (seconds > 0).then(|| Duration::from_secs(seconds));
+        }
+        if let Some(path) = settings.log_file {
+            self.log_file = path;
+        }
+        if let Some(name) = settings.fold_method {
+            self.fold_method = FoldMethod::by_name(&name).ok_or_else(|| format!("unknown fold method {}", name))?;
+        }
+        if let Some(word_count) = settings.word_count {
+            self.word_count = word_count;
+        }
+        if let Some(goal) = settings.word_goal {
+            let goal = usize::try_from(goal).map_err(|_| "word_goal must not be negative".to_string())?;
+            self.word_goal = (goal > 0).then_some(goal);
+        }
+        if let Some(guides) = settings.guides {
+            self.guides = columns(guides)?;
+        }
+        if let Some(trim) = settings.trim_trailing_whitespace {
+            self.cleanup.trim_trailing = trim;
+        }
+        if let Some(final_newline) = settings.insert_final_newline {
+            self.cleanup.final_newline = final_newline;
+        }
+        if let Some(collapse) = settings.collapse_blank_lines {
+            self.cleanup.collapse_blank_lines = collapse;
+        }
+        Ok(())
+    }
+
+    // Applies one layer of settings; invalid ones keep the value of the layer below,
+    // so each is read on its own rather than failing the whole layer.
+    pub fn apply(&mut self, table: &Table) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (key, value) in table {
+            let setting = Value::Table(Table::from_iter([(key.clone(), value.clone())]));
+            match setting.try_into::<Settings>() {
+                Ok(Settings { keymap: Some(sections), .. }) => errors.extend(self.keymap.apply_overrides(&sections)),
+                Ok(settings) => errors.extend(self.set(settings).err()),
+                Err(error) => errors.push(format!("{}: {}", key, error.message())),
+            }
+        }
+        errors
+    }
+
+    fn apply_file(&mut self, path: &Path, content: &str, project: bool) -> Vec<String> {
+        let errors = match toml::from_str::<Table>(content) {
+            Ok(mut table) => {
+                let mut errors = Vec::new();
+                if project {
+                    for key in USER_ONLY.iter().filter(|key| table.remove(**key).is_some()) {
+                        errors.push(format!("{} can only be set in the user config or on the command line", key));
+                    }
+                }
+                errors.extend(self.apply(&table));
+                errors
+            }
+            Err(error) => vec![parse_error(content, &error)],
+        };
+        errors.into_iter().map(|error| format!("{}: {}", path.display(), error)).collect()
+    }
+}
+
+pub fn user_config_file() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(CONFIG_FILE))
+}
+
+// The nearest .divitext.toml in the current directory or above it.
+pub fn project_config_file() -> Option<PathBuf> {
+    let current = env::current_dir().ok()?;
+    current.ancestors().map(|dir| dir.join(PROJECT_FILE)).find(|path| path.is_file())
+}
+
+// Defaults, overridden by each file in turn, then by the project file and then by the
+// command line flags. A missing file is only an error when it was asked for with --config.
+pub fn load(files: &[PathBuf], project: Option<&Path>, required: Option<&Path>, flags: &Table) -> (Config, Vec<String>) {
+    let mut config = Config::default();
+    let mut errors = Vec::new();
+    let layers = files.iter().map(|path| (path.as_path(), false)).chain(project.map(|path| (path, true)));
+    for (path, is_project) in layers {
+        match fs::read_to_string(path) {
+            Ok(content) => errors.extend(config.apply_file(path, &content, is_project)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound && required != Some(path) => (),
+            Err(error) => errors.push(format!("{}: {}", path.display(), error)),
+        }
+    }
+    errors.extend(config.apply(flags).into_iter().map(|error| format!("command line: {}", error)));
+    errors.extend(config.keymap.conflicts());
+    (config, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::{Command, Context};
+    use crate::keys::parse_key;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_apply_settings() {
+        let mut config = Config::default();
+        let table = toml::from_str::<Table>("tab_width = 2\nline_numbers = false\ntheme = \"light\"\nautosave = 30\nlog_file = \"/tmp/log\"\nfold_method = \"brackets\"\nword_count = true\nword_goal = 500\nguides = [100, 80]\ntrim_trailing_whitespace = true\ninsert_final_newline = true\n").unwrap();
+        assert!(config.apply(&table).is_empty());
+        assert_eq!(config.tab_width, 2);
+        assert!(!config.line_numbers);
+        assert_eq!(config.theme, Theme::LIGHT);
+        assert_eq!(config.autosave, Some(Duration::from_secs(30)));
+        assert_eq!(config.log_file, PathBuf::from("/tmp/log"));
+        assert_eq!(config.fold_method, FoldMethod::Brackets);
+        assert!(config.word_count);
+        assert_eq!(config.word_goal, Some(500));
+        assert_eq!(config.guides, vec![80, 100]);
+        assert_eq!(config.cleanup, Cleanup { trim_trailing: true, final_newline: true, collapse_blank_lines: false });
+    }
+
+    #[test]
+    fn test_invalid_settings_keep_previous_value() {
+        let mut config = Config::default();
+        let table = toml::from_str::<Table>("tab_width = 0\nline_numbers = \"yes\"\ntheme = \"neon\"\nautosave = -1\ncolour = 1\nguides = [80, 0]\n").unwrap();
+        assert_eq!(
+            config.apply(&table),
+            vec![
+                "autosave must not be negative",
+                "colour: unknown field `colour`, expected one of `tab_width`, `line_numbers`, `theme`, `autosave`, `log_file`, `fold_method`, `word_count`, `word_goal`, `guides`, `trim_trailing_whitespace`, `insert_final_newline`, `collapse_blank_lines`, `keymap`",
+                "guides must be positive",
+                "line_numbers: invalid type: string \"yes\", expected a boolean",
+                "tab_width must be between 1 and 16",
+                "unknown theme neon",
+            ]
+        );
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_load_layers() {
+        let dir = tempdir().unwrap();
+        let user = dir.path().join("config.toml");
+        let project = dir.path().join(".divitext.toml");
+        fs::write(&user, "tab_width = 2\ntheme = \"light\"\n[keymap.editor]\nsave = \"F2\"\n").unwrap();
+        fs::write(&project, "tab_width = 8\nline_numbers = \"no\"\nlog_file = \"/tmp/project.log\"\n").unwrap();
+        let flags = Table::from_iter([("theme".to_string(), Value::String("monochrome".to_string()))]);
+
+        let (config, errors) = load(&[user, dir.path().join("missing.toml")], Some(&project), None, &flags);
+        assert_eq!(
+            errors,
+            vec![
+                format!("{}: log_file can only be set in the user config or on the command line", project.display()),
+                format!("{}: line_numbers: invalid type: string \"no\", expected a boolean", project.display()),
+            ]
+        );
+        assert_eq!(config.tab_width, 8);
+        assert_eq!(config.log_file, PathBuf::from(DEFAULT_LOG_FILE));
+        assert_eq!(config.theme, Theme::MONOCHROME);
+        assert_eq!(config.keymap.command_for(Context::Editor, &parse_key("F2").unwrap()), Some(Command::Save));
+    }
+
+    #[test]
+    fn test_load_reports_unreadable_files() {
+        let dir = tempdir().unwrap();
+        let broken = dir.path().join("broken.toml");
+        let missing = dir.path().join("missing.toml");
+        fs::write(&broken, "tab_width = \n").unwrap();
+
+        let (config, errors) = load(&[broken.clone(), missing.clone()], None, Some(&missing), &Table::new());
+        assert_eq!(config, Config::default());
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with(&format!("{}: line 1: invalid string", broken.display())), "{}", errors[0]);
+        assert!(errors[1].starts_with(&missing.display().to_string()));
+    }
+}
@@ -0,0 +1,54 @@
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CutBuffer {
+    pub text: String,
+    pub accumulate: bool,
+}
+
+impl CutBuffer {
+    pub fn push(&mut self, text: &str) {
+        if !self.accumulate {
+            self.text.clear();
+        }
+        self.text.push_str(text);
+        self.accumulate = true;
+    }
+
+    pub fn stop_accumulating(&mut self) {
+        self.accumulate = false;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_replaces_when_not_accumulating() {
+        let mut cut_buffer = CutBuffer::default();
+        cut_buffer.push("first\n");
+        cut_buffer.stop_accumulating();
+        cut_buffer.push("second\n");
+        assert_eq!(cut_buffer.text, "second\n");
+    }
+
+    #[test]
+    fn test_consecutive_push_accumulates() {
+        let mut cut_buffer = CutBuffer::default();
+        cut_buffer.push("first\n");
+        cut_buffer.push("second\n");
+        assert_eq!(cut_buffer.text, "first\nsecond\n");
+        assert!(cut_buffer.accumulate);
+    }
+
+    #[test]
+    fn test_is_empty() {
+        let mut cut_buffer = CutBuffer::default();
+        assert!(cut_buffer.is_empty());
+        cut_buffer.push("line");
+        assert!(!cut_buffer.is_empty());
+    }
+}
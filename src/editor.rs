@@ -1,11 +1,18 @@
 use crate::buffer::Buffer;
+use crate::completion::{candidates, is_word_char, popup_rect, word_prefix, Completion, AUTO_COMPLETE_CHARS};
+use crate::folding::FoldMethod;
+use crate::keymap::{Command, Context};
+use crate::marks::{jump_to_mark, set_mark, MarkCommand};
 use crate::state::State;
+use crate::stats::{word_count, Stats};
+use crate::text_view::{SoftWrap, TextView};
+use crate::vi::{Mode, Outcome, Vi};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::{Constraint, Direction, Flex, Layout, Rect};
 use ratatui::prelude::Widget;
-use ratatui::style::Stylize;
-use ratatui::text::Text;
-use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Clear, List, ListState, Paragraph, StatefulWidget};
 use std::cell::RefCell;
 use std::fs::OpenOptions;
 use std::io;
@@ -13,11 +20,30 @@ use std::io::Write;
 use std::rc::Rc;
 
 const FILE_SUCCESSFULLY_SAVED:&str = "File saved successfully !";
+const SCRATCH_NOT_SAVED: &str = "Scratch buffers have no file to save to";
+const RECORDING_MACRO: &str = "Recording macro (Alt+: to stop)";
+const AUTOSAVE_FAILED: &str = "Autosave failed";
+const NO_DICTIONARY: &str = "No dictionary found, add one to the config directory";
+const SET_MARK: &str = "Set mark (a-z, A-Z across files)";
+const JUMP_TO_MARK: &str = "Jump to mark";
+const NOTHING_TO_FOLD: &str = "No block to fold here";
+const NO_FOLD: &str = "No fold on this line";
+const OVERWRITE: &str = "OVERWRITE ";
+const COMPLETION_SELECT_STYLE: Style = Style::new().bg(Color::White).fg(Color::Black);
 
 #[derive(Debug)]
 pub struct Editor<'a> {
     pub state: Rc<RefCell<State<'a>>>,
     pub show_success_save: bool,
+    pub auto_pair: bool,
+    pub auto_complete: bool,
+    // Typed characters replace the one under the cursor.
+    pub overwrite: bool,
+    pub completion: Option<Completion>,
+    // Modal vi-style editing, off when None.
+    pub vi: Option<Vi>,
+    // Set or jump to the mark named by the next key.
+    pub pending_mark: Option<MarkCommand>,
 }
 
 impl<'a> Editor<'a> {
@@ -25,6 +51,12 @@ impl<'a> Editor<'a> {
         Self {
             state,
             show_success_save: false,
+            auto_pair: false,
+            auto_complete: false,
+            overwrite: false,
+            completion: None,
+            vi: None,
+            pending_mark: None,
         }
     }
 
@@ -32,36 +64,378 @@ impl<'a> Editor<'a> {
         let mut state = self.state.borrow_mut();
 
         if let Some(filename) = file_path {
-            let mut buffer = Buffer::default();
+            let mut buffer = Buffer::with_config(&state.config);
             buffer.init(filename)?;
 
-            state.buffer_list.push(buffer);
-            state.current_buffer = state.buffer_list.len() - 1;
+            state.push_buffer(buffer);
         }
         Ok(())
     }
 
     pub fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
-        match key {
-            KeyEvent { code: KeyCode::Char('s'), modifiers: KeyModifiers::CONTROL, .. } => {
+        if let Some(mark_command) = self.pending_mark.take() {
+            self.handle_mark_key(mark_command, key);
+            return Ok(());
+        }
+        let command = self.state.borrow().config.keymap.command_for(Context::Editor, &key);
+        if let Some(command) = command {
+            return self.run_command(command);
+        }
+
+        self.clear_messages();
+        if self.handle_vi_key(key)? {
+            return Ok(());
+        }
+        if self.handle_completion_key(key) {
+            return Ok(());
+        }
+        self.handle_input_current_buffer(key);
+        self.update_completion(key);
+        Ok(())
+    }
+
+    // Any key that is not a letter cancels the mark command.
+    fn handle_mark_key(&mut self, mark_command: MarkCommand, key: KeyEvent) {
+        let KeyCode::Char(mark) = key.code else {
+            return;
+        };
+        let mut state = self.state.borrow_mut();
+        let result = match mark_command {
+            MarkCommand::Set => set_mark(&mut state, mark),
+            MarkCommand::Jump => jump_to_mark(&mut state, mark),
+        };
+        state.status_error = result.err().map(|error| error.to_string());
+        self.completion = None;
+    }
+
+    fn clear_messages(&mut self) {
+        self.show_success_save = false;
+        self.state.borrow_mut().status_error = None;
+    }
+
+    pub fn run_command(&mut self, command: Command) -> Result<(), io::Error> {
+        match command {
+            Command::Save => {
+                let mut state = self.state.borrow_mut();
+                state.cut_buffer.stop_accumulating();
+                if state.buffer_list[state.current_buffer].filename.is_none() {
+                    state.status_error = Some(SCRATCH_NOT_SAVED.to_string());
+                    return Ok(());
+                }
+                drop(state);
                 self.save_current_buffer()?;
                 self.show_success_save = true;
             }
-            _ => {
-                if self.show_success_save {
-                    self.show_success_save = false;
+            Command::ToggleAutoPair => {
+                self.auto_pair = !self.auto_pair;
+            }
+            Command::ToggleAutoComplete => {
+                self.auto_complete = !self.auto_complete;
+                self.completion = None;
+            }
+            Command::Complete => {
+                self.completion = self.find_completion(1);
+            }
+            Command::ToggleViMode => {
+                self.vi = match self.vi {
+                    Some(_) => None,
+                    None => Some(Vi::default()),
+                };
+                self.completion = None;
+            }
+            Command::ToggleSpellCheck => {
+                let mut state = self.state.borrow_mut();
+                let current = state.current_buffer;
+                let enable = !state.buffer_list[current].spell_check;
+                if enable && state.load_dictionary().is_none() {
+                    state.status_error = Some(NO_DICTIONARY.to_string());
+                    return Ok(());
+                }
+                state.buffer_list[current].spell_check = enable;
+            }
+            Command::SetMark => {
+                self.clear_messages();
+                self.pending_mark = Some(MarkCommand::Set);
+            }
+            Command::JumpToMark => {
+                self.clear_messages();
+                self.pending_mark = Some(MarkCommand::Jump);
+            }
+            Command::ToggleOverwrite => self.overwrite = !self.overwrite,
+            Command::ToggleWordCount => {
+                let mut state = self.state.borrow_mut();
+                state.config.word_count = !state.config.word_count;
+            }
+            Command::Quit => self.state.borrow_mut().exit = true,
+            // Handled by the app, which owns the action bar and macros.
+            Command::ToggleActionBar | Command::ToggleMacroRecording | Command::PlayLastMacro | Command::Action(_) => (),
+            command => {
+                self.clear_messages();
+                self.completion = None;
+                self.run_buffer_command(command);
+            }
+        }
+        Ok(())
+    }
+
+    fn run_buffer_command(&self, command: Command) {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        let buffer = &mut state.buffer_list[state.current_buffer];
+        buffer.auto_closers.clear();
+
+        match command {
+            Command::Cut => {
+                let text = buffer.cut_line();
+                if !text.is_empty() {
+                    state.cut_buffer.push(&text);
                 }
-                self.handle_input_current_buffer(key)
+                return;
             }
+            Command::Paste => buffer.paste(&state.cut_buffer.text),
+            Command::Undo => buffer.undo(),
+            Command::Redo => buffer.redo(),
+            Command::Indent => buffer.indent_lines(),
+            Command::Dedent => buffer.dedent_lines(),
+            Command::PageUp => buffer.page_up(),
+            Command::PageDown => buffer.page_down(),
+            Command::CycleSoftWrap => buffer.soft_wrap = buffer.soft_wrap.next(),
+            Command::Justify => buffer.justify(),
+            Command::ToggleHardWrap => buffer.hard_wrap = !buffer.hard_wrap,
+            Command::MatchingBracket => buffer.jump_to_matching_bracket(),
+            Command::MoveLinesUp => buffer.move_lines_up(),
+            Command::MoveLinesDown => buffer.move_lines_down(),
+            Command::DuplicateLines => buffer.duplicate_lines(),
+            Command::DeleteLines => buffer.delete_lines(),
+            Command::JoinLines => buffer.join_lines(),
+            Command::InsertLineBelow => buffer.insert_line_below(),
+            Command::InsertLineAbove => buffer.insert_line_above(),
+            Command::Fold | Command::ToggleFold => {
+                let folded = if command == Command::Fold { buffer.fold_at_cursor() } else { buffer.toggle_fold() };
+                if !folded {
+                    state.status_error = Some(NOTHING_TO_FOLD.to_string());
+                }
+            }
+            Command::Unfold if !buffer.unfold_at_cursor() => state.status_error = Some(NO_FOLD.to_string()),
+            Command::FoldAll => buffer.fold_all(),
+            Command::UnfoldAll => buffer.folds.open_all(),
+            Command::CycleFoldMethod => buffer.fold_method = buffer.fold_method.next(),
+            _ => (),
         }
+        state.cut_buffer.stop_accumulating();
+    }
 
-        Ok(())
+    // Returns whether vi mode consumed the key; keys typed in insert mode fall through.
+    fn handle_vi_key(&mut self, key: KeyEvent) -> Result<bool, io::Error> {
+        let Some(vi) = &mut self.vi else {
+            return Ok(false);
+        };
+        let was_inserting = vi.mode == Mode::Insert;
+        let outcome = {
+            let mut state = self.state.borrow_mut();
+            let current = state.current_buffer;
+            let buffer = &mut state.buffer_list[current];
+            let outcome = vi.handle_key(key, buffer);
+            if was_inserting && outcome == Outcome::Handled {
+                buffer.snippet_session = None;
+            }
+            outcome
+        };
+
+        match outcome {
+            Outcome::Ignored => Ok(false),
+            Outcome::Handled => {
+                self.completion = None;
+                Ok(true)
+            }
+            Outcome::Replay(keys) => {
+                for key in keys {
+                    self.handle_input(key)?;
+                }
+                Ok(true)
+            }
+        }
     }
 
     pub fn handle_input_current_buffer(&self, key: KeyEvent) {
         let mut state = self.state.borrow_mut();
-        let index = state.current_buffer;
-        state.buffer_list[index].input.input(key);
+        let state = &mut *state;
+        let buffer = &mut state.buffer_list[state.current_buffer];
+
+        // Only typing with auto-pairing on and plain Backspace keep track of the closers;
+        // any other edit could move them.
+        let pair_edit = match (key.code, key.modifiers) {
+            (KeyCode::Char(_), KeyModifiers::NONE | KeyModifiers::SHIFT) => self.auto_pair && !self.overwrite,
+            (KeyCode::Backspace, KeyModifiers::NONE) => true,
+            _ => false,
+        };
+        if !pair_edit {
+            buffer.auto_closers.clear();
+        }
+
+        match key {
+            KeyEvent { code: KeyCode::Enter, .. }
+            | KeyEvent { code: KeyCode::Char('m'), modifiers: KeyModifiers::CONTROL, .. } => {
+                buffer.insert_newline_with_indent();
+            }
+            KeyEvent { code: KeyCode::Tab, modifiers: KeyModifiers::NONE, .. }
+                if buffer.snippet_session.is_some() || !buffer.input.is_selecting() =>
+            {
+                if !buffer.next_snippet_stop() && !buffer.expand_snippet_at_cursor() {
+                    buffer.handle_key(key);
+                }
+            }
+            KeyEvent { code: KeyCode::Tab, modifiers: KeyModifiers::NONE, .. }
+                if buffer.selected_rows().0 != buffer.selected_rows().1 =>
+            {
+                buffer.indent_lines();
+            }
+            KeyEvent { code: KeyCode::Esc, .. } => {
+                buffer.snippet_session = None;
+            }
+            KeyEvent { code: KeyCode::BackTab, .. } => {
+                buffer.dedent_lines();
+            }
+            KeyEvent { code: KeyCode::Up | KeyCode::Down, modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT, .. }
+                if buffer.soft_wrap != SoftWrap::Off || !buffer.folds.is_empty() =>
+            {
+                if key.modifiers == KeyModifiers::SHIFT {
+                    if !buffer.input.is_selecting() {
+                        buffer.input.start_selection();
+                    }
+                } else {
+                    buffer.input.cancel_selection();
+                }
+                buffer.move_visual_line(key.code == KeyCode::Down);
+            }
+            KeyEvent { code: KeyCode::Char(c), modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT, .. }
+                if self.overwrite =>
+            {
+                buffer.overwrite_char(c);
+            }
+            KeyEvent { code: KeyCode::Char(c), modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT, .. }
+                if self.auto_pair =>
+            {
+                buffer.insert_char_with_pairs(c);
+            }
+            KeyEvent { code: KeyCode::Backspace, modifiers: KeyModifiers::NONE, .. } => {
+                if !(self.auto_pair && buffer.delete_auto_pair()) {
+                    if buffer.delete_indent_backward() {
+                        buffer.auto_closers.clear();
+                    } else {
+                        buffer.delete_char_backward(key);
+                    }
+                }
+            }
+            _ => {
+                buffer.handle_key(key);
+            }
+        }
+        if buffer.hard_wrap && matches!(key.code, KeyCode::Char(_)) && matches!(key.modifiers, KeyModifiers::NONE | KeyModifiers::SHIFT) {
+            buffer.hard_wrap_line();
+        }
+        state.cut_buffer.stop_accumulating();
+    }
+
+    // Completions for the word before the cursor, once it is at least `min_chars` long.
+    fn find_completion(&self, min_chars: usize) -> Option<Completion> {
+        let state = self.state.borrow();
+        let buffer = state.buffer_list.get(state.current_buffer)?;
+        let (row, col) = buffer.input.cursor();
+        let (start_col, prefix) = word_prefix(&buffer.input.lines()[row], col);
+        if prefix.chars().count() < min_chars.max(1) {
+            return None;
+        }
+
+        let others: Vec<&[String]> = state
+            .buffer_list
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != state.current_buffer)
+            .map(|(_, other)| other.input.lines())
+            .collect();
+        let candidates = candidates(&prefix, buffer.input.lines(), (row, start_col), &others);
+        (!candidates.is_empty()).then_some(Completion { row, start_col, candidates, selected: 0 })
+    }
+
+    // Keys that act on the open completion popup; returns whether the key was used.
+    fn handle_completion_key(&mut self, key: KeyEvent) -> bool {
+        let Some(completion) = &mut self.completion else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Tab | KeyCode::Enter if key.modifiers == KeyModifiers::NONE => {
+                let word = completion.selected_word().to_string();
+                let start_col = completion.start_col;
+                self.completion = None;
+                let mut state = self.state.borrow_mut();
+                let index = state.current_buffer;
+                state.buffer_list[index].complete_word(start_col, &word);
+            }
+            KeyCode::Down => completion.select_next(),
+            KeyCode::Up => completion.select_previous(),
+            KeyCode::Esc => self.completion = None,
+            _ => return false,
+        }
+        true
+    }
+
+    // Refreshes the popup while a word is typed, and opens it automatically when enabled.
+    fn update_completion(&mut self, key: KeyEvent) {
+        let typing_word = match key.code {
+            KeyCode::Char(c) => is_word_char(c) && matches!(key.modifiers, KeyModifiers::NONE | KeyModifiers::SHIFT),
+            KeyCode::Backspace => self.completion.is_some(),
+            _ => false,
+        };
+        self.completion = match (typing_word, self.completion.is_some()) {
+            (false, _) => None,
+            (true, true) => self.find_completion(1),
+            (true, false) if self.auto_complete => self.find_completion(AUTO_COMPLETE_CHARS),
+            (true, false) => None,
+        };
+    }
+
+    // The live word count, of the selection out of the buffer when there is one, and the
+    // progress towards the word goal, which turns green once reached.
+    fn word_count_spans(&self) -> Vec<Span<'static>> {
+        let state = self.state.borrow();
+        let Some(buffer) = state.buffer_list.get(state.current_buffer) else {
+            return Vec::new();
+        };
+        if !state.config.word_count && state.config.word_goal.is_none() {
+            return Vec::new();
+        }
+        let words = word_count(buffer.input.lines());
+        let mut spans = Vec::new();
+        if state.config.word_count {
+            let count = match buffer.selected_text() {
+                Some(selection) => format!("{}/{}", Stats::of_text(&selection).words, words),
+                None => words.to_string(),
+            };
+            spans.push(Span::raw(format!(" | {} words", count)));
+        }
+        if let Some(goal) = state.config.word_goal {
+            let written = words.saturating_sub(buffer.session_words);
+            let progress = Span::raw(format!(" | Goal {}/{}", written, goal));
+            spans.push(if written >= goal { progress.green() } else { progress });
+        }
+        spans
+    }
+
+    fn render_completion(&self, buffer: &Buffer, area: Rect, buf: &mut ratatui::buffer::Buffer) {
+        let (Some(completion), Some((x, y))) = (&self.completion, buffer.viewport.cursor_position.get()) else {
+            return;
+        };
+        let prefix_width = buffer.input.cursor().1.saturating_sub(completion.start_col) as u16;
+        let width = completion.candidates.iter().map(|word| word.chars().count()).max().unwrap_or(0) as u16 + 2;
+        let height = completion.candidates.len() as u16 + 2;
+        let popup = popup_rect((x.saturating_sub(prefix_width), y), width, height, area);
+
+        let list = List::new(completion.candidates.iter().map(String::as_str))
+            .block(Block::default().borders(Borders::ALL))
+            .highlight_style(COMPLETION_SELECT_STYLE);
+        Clear.render(popup, buf);
+        StatefulWidget::render(list, popup, buf, &mut ListState::default().with_selected(Some(completion.selected)));
     }
 
     pub fn get_current_buffer(&self) -> Buffer {
@@ -75,24 +449,46 @@ impl<'a> Editor<'a> {
     }
 
     pub fn save_current_buffer(&self) -> Result<(), io::Error> {
-        let state = self.state.borrow();
-
-        let content = state.buffer_list[state.current_buffer].input.lines().join("\n");
-        let filename = state.buffer_list[state.current_buffer]
-            .clone()
-            .filename
-            .unwrap()
-            .clone();
-
-        let mut file = OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .open(filename)?;
-
-        file.write_all(content.as_bytes())?;
+        let mut state = self.state.borrow_mut();
+        let current = state.current_buffer;
+        let buffer = &mut state.buffer_list[current];
+        buffer.clean_whitespace();
+        write_buffer(buffer, buffer.input.lines())?;
+        buffer.mark_saved();
         Ok(())
     }
+
+    // Saves every modified buffer that has a file. The cleanup is only applied to what is
+    // written, so text being typed is not trimmed under the cursor.
+    pub fn autosave(&self) {
+        let mut state = self.state.borrow_mut();
+        let mut failed = None;
+        for buffer in state.buffer_list.iter_mut().filter(|buffer| buffer.filename.is_some() && buffer.is_modified()) {
+            let lines = buffer.cleanup.apply(buffer.input.lines());
+            match write_buffer(buffer, &lines) {
+                Ok(()) => buffer.mark_saved(),
+                Err(error) => failed = Some(format!("{}: {}", AUTOSAVE_FAILED, error)),
+            }
+        }
+        if failed.is_some() {
+            state.status_error = failed;
+        }
+    }
+}
+
+fn write_buffer(buffer: &Buffer, lines: &[String]) -> Result<(), io::Error> {
+    let content = buffer.file_bytes(lines);
+    // The path the buffer was opened with, so files outside the current directory are
+    // saved in place rather than next to it.
+    let path = buffer.path.clone().or_else(|| buffer.filename.clone()).unwrap();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(path)?;
+
+    file.write_all(&content)?;
+    Ok(())
 }
 
 impl Widget for &Editor<'_> {
@@ -132,25 +528,68 @@ impl Widget for &Editor<'_> {
         let [right_status_bar] = horizontal.areas(right_status_bar_vertical);
 
         if !self.get_buffer_list().is_empty() {
-            let buffer = self.get_current_buffer();
-            buffer.input.render(layout[0], buf);
+            let state = self.state.borrow();
+            let buffer = &state.buffer_list[state.current_buffer];
+            TextView::new(buffer)
+                .with_config(&state.config)
+                .with_spelling(state.dictionary.as_ref())
+                .with_overwrite(self.overwrite)
+                .render(layout[0], buf);
+            self.render_completion(buffer, layout[0], buf);
 
             status_bar_block.render(layout[1], buf);
 
-            let buffer_name = Paragraph::new(buffer.filename.unwrap())
+            let indent = if buffer.mixed_indent {
+                format!("Mixed, {}", buffer.indent)
+            } else {
+                buffer.indent.to_string()
+            };
+            let pairs = if self.auto_pair { ", Auto-pair" } else { "" };
+            let complete = if self.auto_complete { ", Auto-complete" } else { "" };
+            let wrap = if buffer.soft_wrap != SoftWrap::Off { format!(", {}", buffer.soft_wrap) } else { String::new() };
+            let hard_wrap = if buffer.hard_wrap { format!(", Hard wrap {}", buffer.fill_column) } else { String::new() };
+            let spelling = if buffer.spell_check { ", Spelling" } else { "" };
+            let folding = if buffer.fold_method != FoldMethod::default() { format!(", {}", buffer.fold_method) } else { String::new() };
+            let buffer_name = Paragraph::new(format!("{} [{}{}{}{}{}{}{}]", buffer.display_name(), indent, pairs, complete, wrap, hard_wrap, spelling, folding))
                 .centered()
                 .bold();
             buffer_name.render(left_status_bar, buf);
         }
-        if self.show_success_save {
+        if let Some(error) = &self.state.borrow().status_error {
+            let message = Text::raw(error.as_str())
+                .bold()
+                .red()
+                .centered();
+            message.render(mid_status_bar, buf);
+        } else if let Some(mark_command) = self.pending_mark {
+            let prompt = match mark_command {
+                MarkCommand::Set => SET_MARK,
+                MarkCommand::Jump => JUMP_TO_MARK,
+            };
+            let message = Text::raw(prompt)
+                .bold()
+                .centered();
+            message.render(mid_status_bar, buf);
+        } else if self.show_success_save {
             let message = Text::raw(FILE_SUCCESSFULLY_SAVED)
                 .bold()
                 .centered();
             message.render(mid_status_bar, buf);
+        } else if self.state.borrow().macros.is_recording() {
+            let message = Text::raw(RECORDING_MACRO)
+                .bold()
+                .centered();
+            message.render(mid_status_bar, buf);
+        }
+        let cursor_position = self.get_current_buffer().display_cursor();
+        let mut mode = self.vi.as_ref().map(|vi| format!("{} ", vi.mode)).unwrap_or_default();
+        if self.overwrite {
+            mode.push_str(OVERWRITE);
         }
-        let cursor_position = self.get_current_buffer().input.cursor();
-        let cursor_position_string = format!("{}:{}", cursor_position.0 + 1, cursor_position.1 + 1);
-        let position_paragraph = Paragraph::new(cursor_position_string)
+        let cursor_position_string = format!("{}{}:{}", mode, cursor_position.0 + 1, cursor_position.1 + 1);
+        let mut position = vec![Span::raw(cursor_position_string)];
+        position.extend(self.word_count_spans());
+        let position_paragraph = Paragraph::new(Line::from(position))
             .bold()
             .centered();
         position_paragraph.render(right_status_bar, buf);
@@ -163,7 +602,10 @@ mod tests {
     use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
     use std::cell::RefCell;
     use std::fs::{self, File};
+    use std::path::Path;
     use std::rc::Rc;
+    use tempfile::tempdir;
+    use tui_textarea::TextArea;
 
     #[test]
     fn test_editor_init_with_file() {
@@ -224,6 +666,22 @@ mod tests {
         fs::remove_file(file_path).unwrap();
     }
 
+    #[test]
+    fn test_save_writes_to_buffer_path() {
+        let state = Rc::new(RefCell::new(State::default()));
+        let mut editor = Editor::new(Rc::clone(&state));
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test_save_path.txt");
+
+        File::create(&file_path).unwrap();
+        editor.init(Some(&file_path.display().to_string())).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('H'), KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)).unwrap();
+
+        assert!(fs::read_to_string(&file_path).unwrap().starts_with('H'));
+        assert!(!Path::new("test_save_path.txt").exists());
+    }
+
     #[test]
     fn test_handle_input_current_buffer() {
         let state = Rc::new(RefCell::new(State::default()));
@@ -265,4 +723,292 @@ mod tests {
         editor.handle_input(key_event).unwrap();
         assert!(!editor.show_success_save);
     }
+
+    fn create_editor_with_buffers(buffers: Vec<Vec<&str>>) -> Editor<'static> {
+        let state = Rc::new(RefCell::new(State::default()));
+        for (i, lines) in buffers.into_iter().enumerate() {
+            let lines = lines.into_iter().map(String::from).collect();
+            let buffer = Buffer::new(TextArea::new(lines), Some(format!("buffer{}.txt", i)));
+            state.borrow_mut().push_buffer(buffer);
+        }
+        state.borrow_mut().current_buffer = 0;
+        Editor::new(state)
+    }
+
+    #[test]
+    fn test_consecutive_cuts_accumulate() {
+        let mut editor = create_editor_with_buffers(vec![vec!["one", "two", "three"]]);
+        let cut = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL);
+        let paste = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL);
+
+        editor.handle_input(cut).unwrap();
+        editor.handle_input(cut).unwrap();
+        assert_eq!(editor.state.borrow().cut_buffer.text, "one\ntwo\n");
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["three"]);
+
+        editor.handle_input(KeyEvent::new(KeyCode::End, KeyModifiers::NONE)).unwrap();
+        editor.handle_input(paste).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["threeone", "two", ""]);
+    }
+
+    #[test]
+    fn test_cut_after_other_key_starts_new_cut() {
+        let mut editor = create_editor_with_buffers(vec![vec!["one", "two", "three"]]);
+        let cut = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL);
+
+        editor.handle_input(cut).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)).unwrap();
+        editor.handle_input(cut).unwrap();
+
+        assert_eq!(editor.state.borrow().cut_buffer.text, "three");
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["two", ""]);
+    }
+
+    #[test]
+    fn test_cut_buffer_is_shared_between_buffers() {
+        let mut editor = create_editor_with_buffers(vec![vec!["moved", "kept"], vec!["other"]]);
+
+        editor.handle_input(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL)).unwrap();
+        editor.state.borrow_mut().current_buffer = 1;
+        editor.handle_input(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL)).unwrap();
+
+        let buffer_list = editor.get_buffer_list();
+        assert_eq!(buffer_list[0].input.lines(), vec!["kept"]);
+        assert_eq!(buffer_list[1].input.lines(), vec!["moved", "other"]);
+    }
+
+    #[test]
+    fn test_undo_and_redo_use_alt_keys() {
+        let mut editor = create_editor_with_buffers(vec![vec!["one", "two"]]);
+
+        editor.handle_input(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::ALT)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["one", "two"]);
+
+        editor.handle_input(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::ALT)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["two"]);
+    }
+
+    #[test]
+    fn test_enter_and_tab_indentation() {
+        let mut editor = create_editor_with_buffers(vec![vec!["  foo"]]);
+
+        editor.handle_input(KeyEvent::new(KeyCode::End, KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["  foo", "    x"]);
+
+        editor.handle_input(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["  foo", "x"]);
+    }
+
+    #[test]
+    fn test_completion_popup() {
+        let mut editor = create_editor_with_buffers(vec![vec!["alpha", ""], vec!["alphabet"]]);
+        editor.state.borrow_mut().buffer_list[0].move_cursor_to(1, 0);
+        editor.handle_input(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.completion, None);
+
+        editor.handle_input(KeyEvent::new(KeyCode::Char('/'), KeyModifiers::ALT)).unwrap();
+        assert_eq!(editor.completion.as_ref().unwrap().candidates, vec!["alpha", "alphabet"]);
+
+        editor.handle_input(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["alpha", "alphabet"]);
+        assert_eq!(editor.completion, None);
+    }
+
+    #[test]
+    fn test_auto_completion_after_typed_chars() {
+        let mut editor = create_editor_with_buffers(vec![vec!["struct "]]);
+        editor.handle_input(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::ALT)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::End, KeyModifiers::NONE)).unwrap();
+        for c in ['s', 't'] {
+            editor.handle_input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).unwrap();
+        }
+        assert_eq!(editor.completion, None);
+
+        editor.handle_input(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.completion.as_ref().unwrap().candidates, vec!["struct"]);
+        editor.handle_input(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.completion, None);
+    }
+
+    #[test]
+    fn test_hard_wrap_while_typing() {
+        let mut editor = create_editor_with_buffers(vec![vec![""]]);
+        editor.handle_input(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::ALT)).unwrap();
+        editor.state.borrow_mut().buffer_list[0].fill_column = 8;
+
+        for c in "# one two three".chars() {
+            editor.handle_input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).unwrap();
+        }
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["# one", "# two", "# three"]);
+    }
+
+    #[test]
+    fn test_auto_pair_toggle_and_type_over() {
+        let mut editor = create_editor_with_buffers(vec![vec![""]]);
+        editor.handle_input(KeyEvent::new(KeyCode::Char('('), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["("]);
+
+        editor.handle_input(KeyEvent::new(KeyCode::Char('('), KeyModifiers::ALT)).unwrap();
+        assert!(editor.auto_pair);
+        for c in ['[', 'x', ']', ')'] {
+            editor.handle_input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).unwrap();
+        }
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["([x])"]);
+        assert_eq!(editor.get_current_buffer().input.cursor(), (0, 5));
+    }
+
+    #[test]
+    fn test_auto_pair_after_backspace() {
+        let mut editor = create_editor_with_buffers(vec![vec![""]]);
+        editor.auto_pair = true;
+        editor.handle_input(KeyEvent::new(KeyCode::Char('('), KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["()"]);
+        // The pair is empty again, so Backspace removes both brackets.
+        editor.handle_input(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec![""]);
+        editor.handle_input(KeyEvent::new(KeyCode::Char(')'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec![")"]);
+    }
+
+    #[test]
+    fn test_typing_between_auto_pair() {
+        let mut editor = create_editor_with_buffers(vec![vec![""]]);
+        editor.auto_pair = true;
+        for c in ['(', 'a', 'b', ')', ')'] {
+            editor.handle_input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).unwrap();
+        }
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["(ab))"]);
+
+        let mut editor = create_editor_with_buffers(vec![vec![""]]);
+        editor.auto_pair = true;
+        for c in ['(', 'a', 'b'] {
+            editor.handle_input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).unwrap();
+        }
+        editor.handle_input(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char(')'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["(a)"]);
+        assert_eq!(editor.get_current_buffer().input.cursor(), (0, 3));
+    }
+
+    #[test]
+    fn test_jump_to_matching_bracket_key() {
+        let mut editor = create_editor_with_buffers(vec![vec!["{", "  (a)", "}"]]);
+        editor.handle_input(KeyEvent::new(KeyCode::Char(']'), KeyModifiers::ALT)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.cursor(), (2, 0));
+    }
+
+    #[test]
+    fn test_line_commands_keys() {
+        let mut editor = create_editor_with_buffers(vec![vec!["a", "b"]]);
+        editor.handle_input(KeyEvent::new(KeyCode::Down, KeyModifiers::ALT)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Down, KeyModifiers::ALT | KeyModifiers::SHIFT)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::ALT)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["b", "a", "a", ""]);
+
+        editor.handle_input(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::ALT)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::ALT)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::ALT)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["b", "a", "a"]);
+    }
+
+    #[test]
+    fn test_scratch_buffer_is_not_saved() {
+        let mut editor = create_editor_with_buffers(vec![]);
+        editor.state.borrow_mut().push_buffer(Buffer::new(TextArea::default(), None));
+
+        editor.handle_input(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)).unwrap();
+        assert!(!editor.show_success_save);
+        assert_eq!(editor.state.borrow().status_error, Some(SCRATCH_NOT_SAVED.to_string()));
+
+        editor.handle_input(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.state.borrow().status_error, None);
+    }
+
+    #[test]
+    fn test_spell_check_toggle() {
+        let mut editor = create_editor_with_buffers(vec![vec!["text"]]);
+        editor.state.borrow_mut().dictionary = Some(crate::spell::Dictionary::from_words("text\n"));
+
+        editor.handle_input(KeyEvent::new(KeyCode::F(7), KeyModifiers::NONE)).unwrap();
+        assert!(editor.get_current_buffer().spell_check);
+        editor.handle_input(KeyEvent::new(KeyCode::F(7), KeyModifiers::NONE)).unwrap();
+        assert!(!editor.get_current_buffer().spell_check);
+    }
+
+    #[test]
+    fn test_set_and_jump_to_mark_keys() {
+        let mut editor = create_editor_with_buffers(vec![vec!["a", "b", "c"]]);
+        editor.handle_input(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::ALT)).unwrap();
+        assert_eq!(editor.pending_mark, Some(MarkCommand::Set));
+        editor.handle_input(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.get_current_buffer().marks.get(&'q'), Some(&(1, 0)));
+
+        editor.handle_input(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::ALT)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.cursor(), (1, 0));
+
+        editor.handle_input(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::ALT)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.state.borrow().status_error, Some("Mark not set".to_string()));
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_insert_key_toggles_overwrite() {
+        let mut editor = create_editor_with_buffers(vec![vec!["| 1 | 2 |"]]);
+        editor.handle_input(KeyEvent::new(KeyCode::Insert, KeyModifiers::NONE)).unwrap();
+        assert!(editor.overwrite);
+        editor.handle_input(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["|x1 | 2 |"]);
+
+        editor.handle_input(KeyEvent::new(KeyCode::Insert, KeyModifiers::NONE)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["|xy1 | 2 |"]);
+    }
+
+    #[test]
+    fn test_word_count_and_goal() {
+        let mut editor = create_editor_with_buffers(vec![vec!["one two", "three"]]);
+        assert!(editor.word_count_spans().is_empty());
+        editor.handle_input(KeyEvent::new(KeyCode::Char('#'), KeyModifiers::ALT)).unwrap();
+        assert_eq!(editor.word_count_spans(), vec![Span::raw(" | 3 words")]);
+
+        editor.state.borrow_mut().config.word_goal = Some(2);
+        editor.handle_input(KeyEvent::new(KeyCode::End, KeyModifiers::NONE)).unwrap();
+        for c in " four five".chars() {
+            editor.handle_input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).unwrap();
+        }
+        assert_eq!(editor.word_count_spans(), vec![Span::raw(" | 5 words"), Span::raw(" | Goal 2/2").green()]);
+    }
+
+    #[test]
+    fn test_vi_mode() {
+        let mut editor = create_editor_with_buffers(vec![vec!["one two", "three"]]);
+        editor.handle_input(KeyEvent::new(KeyCode::Char('m'), KeyModifiers::ALT)).unwrap();
+        assert_eq!(editor.vi.as_ref().unwrap().mode, Mode::Normal);
+
+        for c in "cwuno".chars() {
+            editor.handle_input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).unwrap();
+        }
+        editor.handle_input(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).unwrap();
+        for c in "j0.".chars() {
+            editor.handle_input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).unwrap();
+        }
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["uno two", "uno"]);
+
+        editor.handle_input(KeyEvent::new(KeyCode::Char('m'), KeyModifiers::ALT)).unwrap();
+        editor.handle_input(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(editor.get_current_buffer().input.lines(), vec!["uno two", "unxo"]);
+    }
 }
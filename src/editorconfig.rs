@@ -0,0 +1,458 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = ".editorconfig";
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+    Cr,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Charset {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    Latin1,
+    Utf16Be,
+    Utf16Le,
+}
+
+impl Charset {
+    pub fn decode(&self, bytes: &[u8]) -> io::Result<String> {
+        let invalid = |error| io::Error::new(io::ErrorKind::InvalidData, error);
+        let utf16 = |units: Vec<u16>| String::from_utf16(&units).map_err(|error| invalid(error.to_string()));
+        match self {
+            Charset::Utf8 | Charset::Utf8Bom => {
+                let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+                String::from_utf8(bytes.to_vec()).map_err(|error| invalid(error.to_string()))
+            }
+            Charset::Latin1 => Ok(bytes.iter().map(|&byte| byte as char).collect()),
+            Charset::Utf16Be => utf16(bytes.chunks(2).map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)])).collect()),
+            Charset::Utf16Le => utf16(bytes.chunks(2).map(|pair| u16::from_le_bytes([pair[0], *pair.get(1).unwrap_or(&0)])).collect()),
+        }
+    }
+
+    // Characters latin1 cannot hold are written as '?'.
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        match self {
+            Charset::Utf8 => text.as_bytes().to_vec(),
+            Charset::Utf8Bom => [UTF8_BOM, text.as_bytes()].concat(),
+            Charset::Latin1 => text.chars().map(|c| u8::try_from(c).unwrap_or(b'?')).collect(),
+            Charset::Utf16Be => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+            Charset::Utf16Le => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndentStyle {
+    Tab,
+    Space,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndentSize {
+    Columns(u8),
+    // Same as tab_width.
+    Tab,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MaxLineLength {
+    Columns(usize),
+    Off,
+}
+
+// The properties that apply to one file; None when no section sets them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Properties {
+    pub indent_style: Option<IndentStyle>,
+    pub indent_size: Option<IndentSize>,
+    pub tab_width: Option<u8>,
+    pub end_of_line: Option<LineEnding>,
+    pub charset: Option<Charset>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+    pub max_line_length: Option<MaxLineLength>,
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_width(value: &str) -> Option<u8> {
+    value.parse().ok().filter(|&width| width > 0)
+}
+
+impl Properties {
+    // Unknown properties and invalid values are ignored, and "unset" clears a property.
+    fn set(&mut self, key: &str, value: &str) {
+        let unset = value == "unset";
+        match key {
+            "indent_style" => {
+                self.indent_style = match value {
+                    "tab" => Some(IndentStyle::Tab),
+                    "space" => Some(IndentStyle::Space),
+                    _ if unset => None,
+                    _ => return,
+                }
+            }
+            "indent_size" => {
+                self.indent_size = match value {
+                    "tab" => Some(IndentSize::Tab),
+                    _ if unset => None,
+                    _ => match parse_width(value) {
+                        Some(width) => Some(IndentSize::Columns(width)),
+                        None => return,
+                    },
+                }
+            }
+            "tab_width" => {
+                self.tab_width = match parse_width(value) {
+                    Some(width) => Some(width),
+                    None if unset => None,
+                    None => return,
+                }
+            }
+            "end_of_line" => {
+                self.end_of_line = match value {
+                    "lf" => Some(LineEnding::Lf),
+                    "crlf" => Some(LineEnding::Crlf),
+                    "cr" => Some(LineEnding::Cr),
+                    _ if unset => None,
+                    _ => return,
+                }
+            }
+            "charset" => {
+                self.charset = match value {
+                    "utf-8" => Some(Charset::Utf8),
+                    "utf-8-bom" => Some(Charset::Utf8Bom),
+                    "latin1" => Some(Charset::Latin1),
+                    "utf-16be" => Some(Charset::Utf16Be),
+                    "utf-16le" => Some(Charset::Utf16Le),
+                    _ if unset => None,
+                    _ => return,
+                }
+            }
+            "trim_trailing_whitespace" => {
+                self.trim_trailing_whitespace = match parse_bool(value) {
+                    Some(on) => Some(on),
+                    None if unset => None,
+                    None => return,
+                }
+            }
+            "insert_final_newline" => {
+                self.insert_final_newline = match parse_bool(value) {
+                    Some(on) => Some(on),
+                    None if unset => None,
+                    None => return,
+                }
+            }
+            "max_line_length" => {
+                self.max_line_length = match value {
+                    "off" => Some(MaxLineLength::Off),
+                    _ if unset => None,
+                    _ => match value.parse() {
+                        Ok(columns) if columns > 0 => Some(MaxLineLength::Columns(columns)),
+                        _ => return,
+                    },
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // The tab width, which defaults to the indent size.
+    pub fn tab_width(&self) -> Option<u8> {
+        self.tab_width.or(match self.indent_size {
+            Some(IndentSize::Columns(width)) => Some(width),
+            _ => None,
+        })
+    }
+
+    pub fn indent_size(&self) -> Option<u8> {
+        match self.indent_size {
+            Some(IndentSize::Columns(width)) => Some(width),
+            Some(IndentSize::Tab) => self.tab_width,
+            None => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct EditorConfig {
+    root: bool,
+    // Glob and properties of each section, in file order.
+    sections: Vec<(String, Vec<(String, String)>)>,
+}
+
+fn parse(content: &str) -> EditorConfig {
+    let mut config = EditorConfig::default();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(glob) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            config.sections.push((glob.to_string(), Vec::new()));
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim().to_string();
+        match config.sections.last_mut() {
+            Some((_, properties)) => properties.push((key, value.to_lowercase())),
+            None if key == "root" => config.root = value.eq_ignore_ascii_case("true"),
+            None => (),
+        }
+    }
+    config
+}
+
+// The alternatives of a `{...}` group, split on top-level commas.
+fn alternatives(group: &[char]) -> Vec<Vec<char>> {
+    let mut items = vec![Vec::new()];
+    let mut depth = 0;
+    for &c in group {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(Vec::new());
+                continue;
+            }
+            _ => (),
+        }
+        items.last_mut().expect("starts with one item").push(c);
+    }
+    items
+}
+
+fn closing(pattern: &[char], open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    for (i, &c) in pattern.iter().enumerate() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+// `{n1..n2}` matches an integer in that range.
+fn number_range(group: &[char]) -> Option<(i64, i64)> {
+    let text: String = group.iter().collect();
+    let (start, end) = text.split_once("..")?;
+    Some((start.parse().ok()?, end.parse().ok()?))
+}
+
+fn glob_match(pattern: &[char], path: &[char]) -> bool {
+    let Some((&first, rest)) = pattern.split_first() else {
+        return path.is_empty();
+    };
+    match first {
+        '*' if rest.first() == Some(&'*') => {
+            let rest = &rest[1..];
+            (0..=path.len()).any(|i| glob_match(rest, &path[i..]))
+        }
+        '*' => {
+            let segment = path.iter().position(|&c| c == '/').unwrap_or(path.len());
+            (0..=segment).any(|i| glob_match(rest, &path[i..]))
+        }
+        '?' => path.first().is_some_and(|&c| c != '/') && glob_match(rest, &path[1..]),
+        '[' => {
+            let Some(end) = rest.iter().position(|&c| c == ']').filter(|&end| end > 0) else {
+                return path.first() == Some(&'[') && glob_match(rest, &path[1..]);
+            };
+            let (negated, class) = match rest[0] {
+                '!' => (true, &rest[1..end]),
+                _ => (false, &rest[..end]),
+            };
+            let Some(&c) = path.first() else {
+                return false;
+            };
+            let mut matched = false;
+            let mut i = 0;
+            while i < class.len() {
+                if i + 2 < class.len() && class[i + 1] == '-' {
+                    matched |= (class[i]..=class[i + 2]).contains(&c);
+                    i += 3;
+                } else {
+                    matched |= class[i] == c;
+                    i += 1;
+                }
+            }
+            matched != negated && c != '/' && glob_match(&rest[end + 1..], &path[1..])
+        }
+        '{' => {
+            let Some(end) = closing(pattern, '{', '}') else {
+                return path.first() == Some(&'{') && glob_match(rest, &path[1..]);
+            };
+            let group = &pattern[1..end];
+            let after = &pattern[end + 1..];
+            if let Some((low, high)) = number_range(group) {
+                let digits = path.iter().enumerate().take_while(|&(i, &c)| c.is_ascii_digit() || (i == 0 && c == '-')).count();
+                return (1..=digits).any(|len| {
+                    let number: String = path[..len].iter().collect();
+                    number.parse().is_ok_and(|n: i64| (low..=high).contains(&n)) && glob_match(after, &path[len..])
+                });
+            }
+            alternatives(group).into_iter().any(|mut alternative| {
+                alternative.extend_from_slice(after);
+                glob_match(&alternative, path)
+            })
+        }
+        '\\' if !rest.is_empty() => path.first() == Some(&rest[0]) && glob_match(&rest[1..], &path[1..]),
+        c => path.first() == Some(&c) && glob_match(rest, &path[1..]),
+    }
+}
+
+// Globs without a slash match the file name in any directory below the .editorconfig.
+fn section_matches(glob: &str, dir: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(dir) else {
+        return false;
+    };
+    let relative: Vec<char> = relative.to_string_lossy().replace('\\', "/").chars().collect();
+    let pattern: Vec<char> = if glob.contains('/') {
+        glob.trim_start_matches('/').chars().collect()
+    } else {
+        format!("{{,**/}}{}", glob).chars().collect()
+    };
+    glob_match(&pattern, &relative)
+}
+
+fn absolute(path: &Path) -> Option<PathBuf> {
+    if path.is_absolute() {
+        return Some(path.to_path_buf());
+    }
+    std::env::current_dir().ok().map(|dir| dir.join(path))
+}
+
+// Properties from every .editorconfig between the file and the first one marked root,
+// where nearer files override farther ones.
+pub fn properties_for(path: &Path) -> Properties {
+    let mut properties = Properties::default();
+    let Some(path) = absolute(path) else {
+        return properties;
+    };
+
+    let mut configs = Vec::new();
+    for dir in path.ancestors().skip(1) {
+        if let Ok(content) = fs::read_to_string(dir.join(FILE_NAME)) {
+            let config = parse(&content);
+            let root = config.root;
+            configs.push((dir.to_path_buf(), config));
+            if root {
+                break;
+            }
+        }
+    }
+
+    for (dir, config) in configs.iter().rev() {
+        for (glob, entries) in &config.sections {
+            if section_matches(glob, dir, &path) {
+                for (key, value) in entries {
+                    properties.set(key, value);
+                }
+            }
+        }
+    }
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn matches(glob: &str, path: &str) -> bool {
+        section_matches(glob, Path::new("/project"), &Path::new("/project").join(path))
+    }
+
+    #[test]
+    fn test_globs() {
+        assert!(matches("*", "a.rs"));
+        assert!(matches("*.rs", "src/deep/a.rs"));
+        assert!(!matches("*.rs", "a.rsx"));
+        assert!(matches("src/*.rs", "src/a.rs"));
+        assert!(!matches("src/*.rs", "src/deep/a.rs"));
+        assert!(matches("/src/**.rs", "src/deep/a.rs"));
+        assert!(matches("*.{js,ts}", "a.ts"));
+        assert!(matches("{package.json,.travis.yml}", ".travis.yml"));
+        assert!(matches("file[0-9].txt", "file7.txt"));
+        assert!(!matches("file[!0-9].txt", "file7.txt"));
+        assert!(matches("log{1..12}", "log10"));
+        assert!(!matches("log{1..12}", "log13"));
+        assert!(matches("?.md", "a.md"));
+    }
+
+    #[test]
+    fn test_properties() {
+        let mut properties = Properties::default();
+        properties.set("indent_style", "tab");
+        properties.set("indent_size", "tab");
+        properties.set("tab_width", "8");
+        properties.set("max_line_length", "off");
+        properties.set("charset", "klingon");
+        assert_eq!(properties.indent_size(), Some(8));
+        assert_eq!(properties.max_line_length, Some(MaxLineLength::Off));
+        assert_eq!(properties.charset, None);
+
+        properties.set("tab_width", "unset");
+        properties.set("indent_size", "2");
+        assert_eq!(properties.tab_width(), Some(2));
+    }
+
+    #[test]
+    fn test_properties_for_walks_up_to_root() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("project");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(dir.path().join(FILE_NAME), "[*]\ncharset = latin1\n").unwrap();
+        fs::write(project.join(FILE_NAME), "root = true\n\n[*]\nindent_style = space\nindent_size = 4\nend_of_line = crlf\n\n[*.md]\ntrim_trailing_whitespace = false\n").unwrap();
+        fs::write(project.join("src").join(FILE_NAME), "[*.rs]\nindent_size = 2\n").unwrap();
+
+        let properties = properties_for(&project.join("src").join("main.rs"));
+        assert_eq!(properties.indent_style, Some(IndentStyle::Space));
+        assert_eq!(properties.indent_size(), Some(2));
+        assert_eq!(properties.end_of_line, Some(LineEnding::Crlf));
+        assert_eq!(properties.trim_trailing_whitespace, None);
+        assert_eq!(properties.charset, None);
+
+        assert_eq!(properties_for(&project.join("README.md")).trim_trailing_whitespace, Some(false));
+    }
+
+    #[test]
+    fn test_charsets() {
+        for charset in [Charset::Utf8, Charset::Utf8Bom, Charset::Latin1, Charset::Utf16Be, Charset::Utf16Le] {
+            assert_eq!(charset.decode(&charset.encode("café\n")).unwrap(), "café\n");
+        }
+        assert_eq!(Charset::Latin1.encode("€"), b"?");
+        assert_eq!(Charset::Utf8.decode(b"\xEF\xBB\xBFa").unwrap(), "a");
+    }
+}
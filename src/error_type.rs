@@ -5,6 +5,28 @@ pub enum ErrorType {
     NONE,
     FileNotFound,
     FileExists,
+    InvalidLine,
+    NoBuffer,
+    InvalidIndent,
+    EmptyCommand,
+    NoSelection,
+    InvalidFillColumn,
+    InvalidMacroName,
+    InvalidRepeatCount,
+    InvalidPlayback,
+    MacroNotFound,
+    NoMacroRecorded,
+    NoMacroDir,
+    NoSnippets,
+    NoDictionary,
+    NoMisspelling,
+    InvalidMark,
+    MarkNotSet,
+    NoFileForMark,
+    NoMarks,
+    InvalidWordGoal,
+    // A failed file operation, with the system's message.
+    Io(String),
 }
 
 impl fmt::Display for ErrorType {
@@ -12,6 +34,27 @@ impl fmt::Display for ErrorType {
         let message = match self {
             ErrorType::FileNotFound => "File not found",
             ErrorType::FileExists => "File already exists",
+            ErrorType::InvalidLine => "Invalid line number",
+            ErrorType::NoBuffer => "No buffer open",
+            ErrorType::InvalidIndent => "Invalid indentation",
+            ErrorType::EmptyCommand => "No command given",
+            ErrorType::NoSelection => "No text selected",
+            ErrorType::InvalidFillColumn => "Invalid fill column",
+            ErrorType::InvalidMacroName => "Macro names use letters, digits, - and _",
+            ErrorType::InvalidRepeatCount => "Count must be a positive number or *",
+            ErrorType::InvalidPlayback => "Expected [name] [count or *]",
+            ErrorType::MacroNotFound => "No such macro",
+            ErrorType::NoMacroRecorded => "No macro recorded",
+            ErrorType::NoMacroDir => "No config directory for macros",
+            ErrorType::NoSnippets => "No snippets for this file type",
+            ErrorType::NoDictionary => "No dictionary found",
+            ErrorType::NoMisspelling => "No misspelled words",
+            ErrorType::InvalidMark => "Marks are a-z, or A-Z across files",
+            ErrorType::MarkNotSet => "Mark not set",
+            ErrorType::NoFileForMark => "Global marks need a buffer with a file",
+            ErrorType::NoMarks => "No marks set",
+            ErrorType::InvalidWordGoal => "Invalid word goal",
+            ErrorType::Io(message) => message.as_str(),
             _ => ""
         };
         write!(f, "{}", message)
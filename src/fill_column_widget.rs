@@ -0,0 +1,94 @@
+use crate::action_bar::ActionWidget;
+use crate::error_type::ErrorType;
+use crate::prompt::Prompt;
+use crate::state::State;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+const POPUP_TITLE: &str = "Fill column for justify and hard wrap";
+
+#[derive(Debug)]
+pub struct FillColumnWidget<'a> {
+    pub state: Rc<RefCell<State<'a>>>,
+    pub prompt: Prompt<'a>,
+}
+
+impl<'a> FillColumnWidget<'a> {
+    pub fn new(state: Rc<RefCell<State<'a>>>) -> FillColumnWidget<'a> {
+        FillColumnWidget { state, prompt: Prompt::new(POPUP_TITLE) }
+    }
+
+    pub fn set_fill_column(&mut self) {
+        let mut state = self.state.borrow_mut();
+        let index = state.current_buffer;
+        let Some(buffer) = state.buffer_list.get_mut(index) else {
+            self.prompt.error = ErrorType::NoBuffer;
+            return;
+        };
+
+        match self.prompt.text().trim().parse::<usize>() {
+            Ok(column) if column > 0 => buffer.fill_column = column,
+            _ => {
+                self.prompt.error = ErrorType::InvalidFillColumn;
+                return;
+            }
+        }
+        self.prompt.clear();
+    }
+}
+
+impl ActionWidget for FillColumnWidget<'_> {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.prompt.render(area, 50, buf);
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
+        self.prompt.handle_input(key);
+        Ok(())
+    }
+
+    fn has_error(&self) -> bool {
+        self.prompt.has_error()
+    }
+
+    fn process_action(&mut self) -> Result<bool, io::Error> {
+        self.set_fill_column();
+        Ok(!self.prompt.has_error())
+    }
+
+    fn init_action(&mut self) {}
+
+    fn reset(&mut self) {
+        self.prompt.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::state_with_buffer;
+
+    fn create_widget(input: &str) -> FillColumnWidget<'static> {
+        let mut widget = FillColumnWidget::new(state_with_buffer(&[""]));
+        widget.prompt.input.insert_str(input);
+        widget
+    }
+
+    #[test]
+    fn test_set_fill_column() {
+        let mut widget = create_widget("80");
+        assert!(widget.process_action().unwrap());
+        assert_eq!(widget.state.borrow().buffer_list[0].fill_column, 80);
+    }
+
+    #[test]
+    fn test_invalid_fill_column() {
+        let mut widget = create_widget("0");
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.prompt.error, ErrorType::InvalidFillColumn);
+    }
+}
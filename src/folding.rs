@@ -0,0 +1,279 @@
+use crate::brackets::{closing_bracket, opening_bracket};
+use crate::history::common_ends;
+use crate::indent::leading_whitespace;
+use crate::text_view::display_width;
+use std::fmt;
+use std::iter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FoldMethod {
+    #[default]
+    Indent,
+    Brackets,
+}
+
+impl FoldMethod {
+    pub fn by_name(name: &str) -> Option<FoldMethod> {
+        match name {
+            "indent" => Some(FoldMethod::Indent),
+            "brackets" => Some(FoldMethod::Brackets),
+            _ => None,
+        }
+    }
+
+    pub fn next(self) -> FoldMethod {
+        match self {
+            FoldMethod::Indent => FoldMethod::Brackets,
+            FoldMethod::Brackets => FoldMethod::Indent,
+        }
+    }
+}
+
+impl fmt::Display for FoldMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FoldMethod::Indent => write!(f, "Fold by indent"),
+            FoldMethod::Brackets => write!(f, "Fold by brackets"),
+        }
+    }
+}
+
+// Lines `start + 1..=end` are hidden behind the line `start`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fold {
+    pub start: usize,
+    pub end: usize,
+}
+
+// Every block that can be folded, nested ones included, sorted by start.
+pub fn fold_ranges(lines: &[String], method: FoldMethod, tab_length: usize) -> Vec<Fold> {
+    let mut ranges = match method {
+        FoldMethod::Indent => indent_ranges(lines, tab_length),
+        FoldMethod::Brackets => bracket_ranges(lines),
+    };
+    ranges.sort_by_key(|fold| (fold.start, fold.end));
+    ranges
+}
+
+// A line followed by more deeply indented ones; blank lines do not end a block.
+fn indent_ranges(lines: &[String], tab_length: usize) -> Vec<Fold> {
+    let mut ranges = Vec::new();
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    let mut last = 0;
+    let mut close = |open: &mut Vec<(usize, usize)>, level: Option<usize>, last: usize| {
+        while let Some(&(open_level, start)) = open.last() {
+            if level.is_some_and(|level| level > open_level) {
+                break;
+            }
+            open.pop();
+            if last > start {
+                ranges.push(Fold { start, end: last });
+            }
+        }
+    };
+
+    for (row, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let level = display_width(leading_whitespace(line), tab_length);
+        close(&mut open, Some(level), last);
+        open.push((level, row));
+        last = row;
+    }
+    close(&mut open, None, last);
+    ranges
+}
+
+// The lines between a bracket and its match on a later line; the closing line stays visible.
+fn bracket_ranges(lines: &[String]) -> Vec<Fold> {
+    let mut ranges: Vec<Fold> = Vec::new();
+    let mut open: Vec<(char, usize)> = Vec::new();
+    for (row, line) in lines.iter().enumerate() {
+        for c in line.chars() {
+            if closing_bracket(c).is_some() {
+                open.push((c, row));
+            } else if let Some(opener) = opening_bracket(c) {
+                let Some(index) = open.iter().rposition(|(c, _)| *c == opener) else {
+                    continue;
+                };
+                let start = open[index].1;
+                open.truncate(index);
+                if row > start + 1 {
+                    match ranges.iter_mut().find(|fold| fold.start == start) {
+                        Some(fold) => fold.end = fold.end.max(row - 1),
+                        None => ranges.push(Fold { start, end: row - 1 }),
+                    }
+                }
+            }
+        }
+    }
+    ranges
+}
+
+// The range starting on `row`, or else the innermost one around it.
+pub fn range_at(ranges: &[Fold], row: usize) -> Option<Fold> {
+    let starting = ranges.iter().filter(|fold| fold.start == row).max_by_key(|fold| fold.end);
+    starting
+        .or_else(|| ranges.iter().filter(|fold| fold.start < row && row <= fold.end).max_by_key(|fold| fold.start))
+        .copied()
+}
+
+// The closed folds of a buffer, mapping its lines to the ones on screen.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Folds {
+    closed: Vec<Fold>,
+}
+
+impl Folds {
+    pub fn is_empty(&self) -> bool {
+        self.closed.is_empty()
+    }
+
+    pub fn is_closed(&self, fold: Fold) -> bool {
+        self.closed.contains(&fold)
+    }
+
+    pub fn close(&mut self, fold: Fold) {
+        if !self.is_closed(fold) {
+            self.closed.push(fold);
+        }
+    }
+
+    pub fn close_all(&mut self, ranges: Vec<Fold>) {
+        self.closed = ranges;
+    }
+
+    pub fn open_all(&mut self) {
+        self.closed.clear();
+    }
+
+    // Opens the folds starting on `row`, returning whether there were any.
+    pub fn open(&mut self, row: usize) -> bool {
+        let count = self.closed.len();
+        self.closed.retain(|fold| fold.start != row);
+        self.closed.len() != count
+    }
+
+    // Opens the folds hiding `row`.
+    pub fn reveal(&mut self, row: usize) {
+        self.closed.retain(|fold| !(fold.start < row && row <= fold.end));
+    }
+
+    pub fn is_hidden(&self, row: usize) -> bool {
+        self.closed.iter().any(|fold| fold.start < row && row <= fold.end)
+    }
+
+    // Skips whole folds at a time rather than checking each hidden line.
+    pub fn next_visible(&self, row: usize, line_count: usize) -> Option<usize> {
+        let mut next = row + 1;
+        while let Some(end) = self.closed.iter().filter(|fold| fold.start < next && next <= fold.end).map(|fold| fold.end).max() {
+            next = end + 1;
+        }
+        (next < line_count).then_some(next)
+    }
+
+    pub fn prev_visible(&self, row: usize) -> Option<usize> {
+        let mut prev = row.checked_sub(1)?;
+        while let Some(start) = self.closed.iter().filter(|fold| fold.start < prev && prev <= fold.end).map(|fold| fold.start).min() {
+            prev = start;
+        }
+        Some(prev)
+    }
+
+    // Visible lines from `row` downwards, starting with `row` itself.
+    pub fn visible_rows(&self, row: usize, line_count: usize) -> impl Iterator<Item = usize> + '_ {
+        iter::successors(Some(row), move |&row| self.next_visible(row, line_count))
+    }
+
+    // Visible lines from `row` upwards, starting with `row` itself.
+    pub fn visible_rows_up(&self, row: usize) -> impl Iterator<Item = usize> + '_ {
+        iter::successors(Some(row), move |&row| self.prev_visible(row))
+    }
+
+    // How many lines are hidden behind the visible `row`.
+    pub fn hidden_after(&self, row: usize, line_count: usize) -> usize {
+        self.next_visible(row, line_count).unwrap_or(line_count) - row - 1
+    }
+
+    // The most lines any fold hides, to size the gutter.
+    pub fn max_hidden(&self) -> usize {
+        self.closed.iter().map(|fold| fold.end - fold.start).max().unwrap_or(0)
+    }
+
+    // Keeps folds on their lines after lines `row..row + before.len()` were replaced by
+    // `after`. A fold is opened when its hidden lines change; its first line may be edited.
+    pub fn shift(&mut self, row: usize, before: &[String], after: &[String]) {
+        let (prefix, suffix) = common_ends(before, after);
+        let first = row + prefix;
+        let last = row + before.len() - suffix;
+        let same_length = before.len() == after.len();
+        self.closed.retain_mut(|fold| {
+            if last <= fold.start {
+                fold.start = fold.start + after.len() - before.len();
+                fold.end = fold.end + after.len() - before.len();
+                true
+            } else {
+                first > fold.end || (first == fold.start && last == fold.start + 1 && same_length)
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_indent_ranges() {
+        let text = lines(&["fn a() {", "    if x {", "        y", "", "    }", "}", "", "b"]);
+        assert_eq!(
+            fold_ranges(&text, FoldMethod::Indent, 4),
+            vec![Fold { start: 0, end: 4 }, Fold { start: 1, end: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_bracket_ranges() {
+        let text = lines(&["if (a) {", "  b", "} else {", "  c", "  d", "}", "f(x,", "  y)"]);
+        assert_eq!(
+            fold_ranges(&text, FoldMethod::Brackets, 4),
+            vec![Fold { start: 0, end: 1 }, Fold { start: 2, end: 4 }]
+        );
+        assert_eq!(range_at(&fold_ranges(&text, FoldMethod::Brackets, 4), 3), Some(Fold { start: 2, end: 4 }));
+        assert_eq!(range_at(&fold_ranges(&text, FoldMethod::Brackets, 4), 6), None);
+    }
+
+    #[test]
+    fn test_visible_rows() {
+        let mut folds = Folds::default();
+        folds.close(Fold { start: 1, end: 3 });
+        folds.close(Fold { start: 2, end: 3 });
+        assert_eq!(folds.visible_rows(0, 6).collect::<Vec<_>>(), vec![0, 1, 4, 5]);
+        assert_eq!(folds.visible_rows_up(4).collect::<Vec<_>>(), vec![4, 1, 0]);
+        assert_eq!(folds.hidden_after(1, 6), 2);
+
+        assert!(folds.open(1));
+        assert_eq!(folds.visible_rows(0, 6).collect::<Vec<_>>(), vec![0, 1, 2, 4, 5]);
+        folds.reveal(3);
+        assert!(folds.is_empty());
+    }
+
+    #[test]
+    fn test_shift_folds() {
+        let mut folds = Folds::default();
+        folds.close(Fold { start: 2, end: 4 });
+        folds.shift(0, &lines(&["a", "b"]), &lines(&["a", "new", "b"]));
+        assert_eq!(folds.closed, vec![Fold { start: 3, end: 5 }]);
+        // Typing on the first line keeps the fold.
+        folds.shift(2, &lines(&["b", "c", "d"]), &lines(&["b", "cx", "d"]));
+        assert_eq!(folds.closed, vec![Fold { start: 3, end: 5 }]);
+        // Changing a hidden line opens it.
+        folds.shift(4, &lines(&["e"]), &lines(&["f"]));
+        assert!(folds.is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+use crate::action_bar::ActionWidget;
+use crate::error_type::ErrorType;
+use crate::prompt::Prompt;
+use crate::state::State;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+const POPUP_TITLE: &str = "Go to line (line, line:col, +N, -N, N%)";
+
+#[derive(Debug)]
+pub struct GotoLineWidget<'a> {
+    pub state: Rc<RefCell<State<'a>>>,
+    pub prompt: Prompt<'a>,
+}
+
+// Resolves the user input to a zero-based (row, column) target.
+pub fn parse_position(input: &str, current_row: usize, line_count: usize) -> Option<(usize, Option<usize>)> {
+    let input = input.trim();
+    let (line_part, col_part) = match input.split_once(':') {
+        Some((line, col)) => (line.trim(), Some(col.trim())),
+        None => (input, None),
+    };
+
+    let last_row = line_count.saturating_sub(1);
+    let row = if let Some(offset) = line_part.strip_prefix('+') {
+        current_row.saturating_add(offset.parse::<usize>().ok()?)
+    } else if let Some(offset) = line_part.strip_prefix('-') {
+        current_row.saturating_sub(offset.parse::<usize>().ok()?)
+    } else if let Some(percent) = line_part.strip_suffix('%') {
+        let percent = percent.trim().parse::<usize>().ok()?.min(100);
+        (line_count * percent / 100).saturating_sub(1)
+    } else {
+        line_part.parse::<usize>().ok()?.checked_sub(1)?
+    };
+
+    let col = match col_part {
+        Some(col) => Some(col.parse::<usize>().ok()?.checked_sub(1)?),
+        None => None,
+    };
+
+    Some((row.min(last_row), col))
+}
+
+impl<'a> GotoLineWidget<'a> {
+    pub fn new(state: Rc<RefCell<State<'a>>>) -> GotoLineWidget<'a> {
+        GotoLineWidget { state, prompt: Prompt::new(POPUP_TITLE) }
+    }
+
+    pub fn goto_line(&mut self) {
+        let mut state = self.state.borrow_mut();
+        let index = state.current_buffer;
+        let Some(buffer) = state.buffer_list.get_mut(index) else {
+            self.prompt.error = ErrorType::NoBuffer;
+            return;
+        };
+
+        let (current_row, current_col) = buffer.input.cursor();
+        let line_count = buffer.input.lines().len();
+        let Some((row, col)) = parse_position(self.prompt.text(), current_row, line_count) else {
+            self.prompt.error = ErrorType::InvalidLine;
+            return;
+        };
+
+        let col = col.unwrap_or(if row == current_row { current_col } else { 0 });
+        buffer.input.cancel_selection();
+        buffer.move_cursor_to(row, col);
+        buffer.viewport.center_on(row);
+        self.prompt.clear();
+    }
+}
+
+impl ActionWidget for GotoLineWidget<'_> {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.prompt.render(area, 50, buf);
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
+        self.prompt.handle_input(key);
+        Ok(())
+    }
+
+    fn has_error(&self) -> bool {
+        self.prompt.has_error()
+    }
+
+    fn process_action(&mut self) -> Result<bool, io::Error> {
+        self.goto_line();
+        Ok(!self.prompt.has_error())
+    }
+
+    fn init_action(&mut self) {}
+
+    fn reset(&mut self) {
+        self.prompt.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::state_with_buffer;
+
+    fn create_widget_with_input(input: &str, line_count: usize) -> GotoLineWidget<'static> {
+        let lines: Vec<String> = (1..=line_count).map(|i| format!("line {}", i)).collect();
+        let mut widget = GotoLineWidget::new(state_with_buffer(&lines));
+        widget.prompt.input.insert_str(input);
+        widget
+    }
+
+    #[test]
+    fn test_parse_absolute_line() {
+        assert_eq!(parse_position("5", 0, 10), Some((4, None)));
+        assert_eq!(parse_position("50", 0, 10), Some((9, None)));
+        assert_eq!(parse_position("0", 0, 10), None);
+    }
+
+    #[test]
+    fn test_parse_line_and_column() {
+        assert_eq!(parse_position("3:7", 0, 10), Some((2, Some(6))));
+        assert_eq!(parse_position("3:", 0, 10), None);
+        assert_eq!(parse_position("+2:1", 4, 10), Some((6, Some(0))));
+    }
+
+    #[test]
+    fn test_parse_relative_offsets() {
+        assert_eq!(parse_position("+3", 4, 10), Some((7, None)));
+        assert_eq!(parse_position("-3", 4, 10), Some((1, None)));
+        assert_eq!(parse_position("-30", 4, 10), Some((0, None)));
+    }
+
+    #[test]
+    fn test_parse_percentage() {
+        assert_eq!(parse_position("50%", 0, 200), Some((99, None)));
+        assert_eq!(parse_position("100%", 0, 200), Some((199, None)));
+        assert_eq!(parse_position("0%", 0, 200), Some((0, None)));
+        assert_eq!(parse_position("abc%", 0, 200), None);
+    }
+
+    #[test]
+    fn test_goto_line_moves_cursor_and_centers() {
+        let mut widget = create_widget_with_input("40:3", 100);
+        widget.state.borrow().buffer_list[0].viewport.height.set(20);
+
+        assert!(widget.process_action().unwrap());
+
+        let state = widget.state.borrow();
+        assert_eq!(state.buffer_list[0].input.cursor(), (39, 2));
+        assert_eq!(state.buffer_list[0].viewport.top_row.get(), 29);
+    }
+
+    #[test]
+    fn test_goto_line_invalid_input() {
+        let mut widget = create_widget_with_input("abc", 10);
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.prompt.error, ErrorType::InvalidLine);
+    }
+
+    #[test]
+    fn test_goto_line_without_buffer() {
+        let state = Rc::new(RefCell::new(State::default()));
+        let mut widget = GotoLineWidget::new(state);
+        widget.prompt.input.insert_str("1");
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.prompt.error, ErrorType::NoBuffer);
+    }
+}
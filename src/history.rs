@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+
+const MAX_HISTORY: usize = 100;
+
+// Lines `row..row + before.len()` were replaced by `after`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub row: usize,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+    pub cursor_before: (usize, usize),
+    pub cursor_after: (usize, usize),
+}
+
+// State of the lines around an edit, taken before it runs.
+#[derive(Debug)]
+pub struct Checkpoint {
+    pub first: usize,
+    pub last: usize,
+    pub line_count: usize,
+    pub before: Vec<String>,
+    pub cursor: (usize, usize),
+}
+
+// Lengths of the unchanged lines at the start and end of a change.
+pub fn common_ends(before: &[String], after: &[String]) -> (usize, usize) {
+    let prefix = before.iter().zip(after).take_while(|(a, b)| a == b).count();
+    let suffix = before[prefix..].iter().rev().zip(after[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    (prefix, suffix)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+}
+
+impl History {
+    pub fn push(&mut self, change: Change) {
+        self.redo.clear();
+        if self.undo.len() == MAX_HISTORY {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(change);
+    }
+
+    pub fn undo(&mut self) -> Option<Change> {
+        let change = self.undo.pop_back()?;
+        self.redo.push(change.clone());
+        Some(change)
+    }
+
+    pub fn redo(&mut self) -> Option<Change> {
+        let change = self.redo.pop()?;
+        self.undo.push_back(change.clone());
+        Some(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(row: usize) -> Change {
+        Change {
+            row,
+            before: vec!["a".to_string()],
+            after: vec!["b".to_string()],
+            cursor_before: (row, 0),
+            cursor_after: (row, 1),
+        }
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut history = History::default();
+        history.push(change(0));
+        history.push(change(1));
+
+        assert_eq!(history.undo(), Some(change(1)));
+        assert_eq!(history.undo(), Some(change(0)));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(change(0)));
+    }
+
+    #[test]
+    fn test_push_clears_redo() {
+        let mut history = History::default();
+        history.push(change(0));
+        history.undo();
+        history.push(change(1));
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = History::default();
+        for row in 0..MAX_HISTORY + 5 {
+            history.push(change(row));
+        }
+        let mut count = 0;
+        while history.undo().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, MAX_HISTORY);
+    }
+}
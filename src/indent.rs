@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::fmt;
+
+pub const DEFAULT_TAB_WIDTH: u8 = 4;
+const MAX_TAB_WIDTH: u8 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    Tabs(u8),
+    Spaces(u8),
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Indent::Spaces(DEFAULT_TAB_WIDTH)
+    }
+}
+
+impl fmt::Display for Indent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Indent::Tabs(_) => write!(f, "Tabs"),
+            Indent::Spaces(width) => write!(f, "Spaces: {}", width),
+        }
+    }
+}
+
+impl Indent {
+    pub fn width(&self) -> u8 {
+        match self {
+            Indent::Tabs(width) | Indent::Spaces(width) => *width,
+        }
+    }
+
+    pub fn is_hard_tab(&self) -> bool {
+        matches!(self, Indent::Tabs(_))
+    }
+
+    pub fn unit(&self) -> String {
+        match self {
+            Indent::Tabs(_) => "\t".to_string(),
+            Indent::Spaces(width) => " ".repeat(*width as usize),
+        }
+    }
+}
+
+// Accepts "tab", "tab 8", "4" or "spaces 4".
+pub fn parse_indent(input: &str, current: Indent) -> Option<Indent> {
+    let mut words = input.split_whitespace();
+    let first = words.next()?.to_lowercase();
+    let width = match words.next() {
+        Some(width) => Some(width.parse::<u8>().ok()?),
+        None => None,
+    };
+    if words.next().is_some() {
+        return None;
+    }
+
+    let indent = match (first.as_str(), width) {
+        ("t" | "tab" | "tabs", width) => Indent::Tabs(width.unwrap_or(current.width())),
+        ("s" | "space" | "spaces", Some(width)) => Indent::Spaces(width),
+        (number, None) => Indent::Spaces(number.parse().ok()?),
+        _ => return None,
+    };
+
+    (1..=MAX_TAB_WIDTH).contains(&indent.width()).then_some(indent)
+}
+
+pub fn leading_whitespace(line: &str) -> &str {
+    let end = line
+        .char_indices()
+        .find(|(_, c)| *c != ' ' && *c != '\t')
+        .map(|(i, _)| i)
+        .unwrap_or(line.len());
+    &line[..end]
+}
+
+// Number of characters to remove from the head of `line` to drop one indent level.
+pub fn dedent_len(line: &str, indent: Indent) -> usize {
+    if line.starts_with('\t') {
+        return 1;
+    }
+    let spaces = line.chars().take_while(|c| *c == ' ').count();
+    if spaces == 0 {
+        return 0;
+    }
+    let width = indent.width().max(1) as usize;
+    match spaces % width {
+        0 => width,
+        remainder => remainder,
+    }
+}
+
+// Guesses the dominant indentation of a file from the lines that are indented.
+pub fn detect_indent(lines: &[String], tab_width: u8) -> Option<Indent> {
+    let mut tab_lines = 0;
+    let mut space_lines = 0;
+    let mut deltas: HashMap<usize, usize> = HashMap::new();
+    let mut previous = 0;
+
+    for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+        if line.starts_with('\t') {
+            tab_lines += 1;
+            continue;
+        }
+        let spaces = line.chars().take_while(|c| *c == ' ').count();
+        if spaces > 0 {
+            space_lines += 1;
+        }
+        if spaces > previous {
+            *deltas.entry(spaces - previous).or_default() += 1;
+        }
+        previous = spaces;
+    }
+
+    if tab_lines == 0 && space_lines == 0 {
+        return None;
+    }
+    if tab_lines >= space_lines {
+        return Some(Indent::Tabs(tab_width));
+    }
+
+    let width = deltas
+        .into_iter()
+        .filter(|(delta, _)| (1..=MAX_TAB_WIDTH as usize).contains(delta))
+        .max_by(|(a_delta, a_count), (b_delta, b_count)| a_count.cmp(b_count).then(b_delta.cmp(a_delta)))
+        .map(|(delta, _)| delta as u8)?;
+    Some(Indent::Spaces(width))
+}
+
+pub fn has_mixed_indent(lines: &[String]) -> bool {
+    let mut tabs = false;
+    let mut spaces = false;
+    for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+        match line.chars().next() {
+            Some('\t') => tabs = true,
+            Some(' ') => spaces = true,
+            _ => (),
+        }
+        if tabs && spaces {
+            return true;
+        }
+    }
+    false
+}
+
+// Rewrites the indentation of `line` from one style to another, keeping its level.
+pub fn convert_indent(line: &str, from: Indent, to: Indent) -> String {
+    let whitespace = leading_whitespace(line);
+    let from_width = from.width().max(1) as usize;
+    let columns = whitespace.chars().fold(0, |columns, c| {
+        if c == '\t' {
+            columns + from_width - columns % from_width
+        } else {
+            columns + 1
+        }
+    });
+
+    let levels = columns / from_width;
+    let remainder = columns % from_width;
+    format!("{}{}{}", to.unit().repeat(levels), " ".repeat(remainder), &line[whitespace.len()..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit() {
+        assert_eq!(Indent::Tabs(8).unit(), "\t");
+        assert_eq!(Indent::Spaces(2).unit(), "  ");
+    }
+
+    #[test]
+    fn test_parse_indent() {
+        let current = Indent::Spaces(4);
+        assert_eq!(parse_indent("tab", current), Some(Indent::Tabs(4)));
+        assert_eq!(parse_indent("tabs 8", current), Some(Indent::Tabs(8)));
+        assert_eq!(parse_indent("2", current), Some(Indent::Spaces(2)));
+        assert_eq!(parse_indent("spaces 3", current), Some(Indent::Spaces(3)));
+        assert_eq!(parse_indent("spaces", current), None);
+        assert_eq!(parse_indent("0", current), None);
+        assert_eq!(parse_indent("", current), None);
+    }
+
+    #[test]
+    fn test_leading_whitespace() {
+        assert_eq!(leading_whitespace("  \tfoo "), "  \t");
+        assert_eq!(leading_whitespace("foo"), "");
+        assert_eq!(leading_whitespace("   "), "   ");
+    }
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_detect_indent_spaces() {
+        let two = lines(&["fn main() {", "  if x {", "    y();", "  }", "}"]);
+        assert_eq!(detect_indent(&two, 4), Some(Indent::Spaces(2)));
+
+        let four = lines(&["a:", "    b:", "        c", "    d", "e"]);
+        assert_eq!(detect_indent(&four, 4), Some(Indent::Spaces(4)));
+    }
+
+    #[test]
+    fn test_detect_indent_tabs() {
+        let tabs = lines(&["a {", "\tb", "\t\tc", "  d", "}"]);
+        assert_eq!(detect_indent(&tabs, 8), Some(Indent::Tabs(8)));
+    }
+
+    #[test]
+    fn test_detect_indent_without_indentation() {
+        assert_eq!(detect_indent(&lines(&["a", "b", ""]), 4), None);
+    }
+
+    #[test]
+    fn test_has_mixed_indent() {
+        assert!(has_mixed_indent(&lines(&["\ta", "  b"])));
+        assert!(!has_mixed_indent(&lines(&["\ta", "\t  b", "c"])));
+    }
+
+    #[test]
+    fn test_convert_indent() {
+        assert_eq!(convert_indent("\t\tfoo", Indent::Tabs(4), Indent::Spaces(2)), "    foo");
+        assert_eq!(convert_indent("      foo", Indent::Spaces(4), Indent::Tabs(4)), "\t  foo");
+        assert_eq!(convert_indent("foo", Indent::Spaces(4), Indent::Tabs(4)), "foo");
+    }
+
+    #[test]
+    fn test_dedent_len() {
+        assert_eq!(dedent_len("\tfoo", Indent::Spaces(4)), 1);
+        assert_eq!(dedent_len("        foo", Indent::Spaces(4)), 4);
+        assert_eq!(dedent_len("      foo", Indent::Spaces(4)), 2);
+        assert_eq!(dedent_len("  foo", Indent::Spaces(4)), 2);
+        assert_eq!(dedent_len("foo", Indent::Spaces(4)), 0);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::action_bar::ActionWidget;
+use crate::error_type::ErrorType;
+use crate::indent::{parse_indent, Indent};
+use crate::prompt::Prompt;
+use crate::state::State;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use std::cell::RefCell;
+use std::fmt;
+use std::io;
+use std::rc::Rc;
+
+pub type ApplyIndentFn<'a> = Box<dyn Fn(&mut crate::buffer::Buffer<'a>, Indent) + 'a>;
+
+pub struct IndentWidget<'a> {
+    pub state: Rc<RefCell<State<'a>>>,
+    pub prompt: Prompt<'a>,
+    pub apply_fn: ApplyIndentFn<'a>,
+}
+
+impl fmt::Debug for IndentWidget<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndentWidget")
+            .field("state", &self.state)
+            .field("prompt", &self.prompt)
+            .field("apply_fn", &"<function>")
+            .finish()
+    }
+}
+
+impl<'a> IndentWidget<'a> {
+    pub fn new(state: Rc<RefCell<State<'a>>>, title: &'a str, apply_fn: ApplyIndentFn<'a>) -> IndentWidget<'a> {
+        IndentWidget { state, prompt: Prompt::new(title), apply_fn }
+    }
+
+    pub fn for_setting(state: Rc<RefCell<State<'a>>>) -> IndentWidget<'a> {
+        let set_fn: ApplyIndentFn<'a> = Box::new(|buffer, indent| buffer.set_indent(indent));
+        IndentWidget::new(state, "Indentation (tab, tab N or N spaces)", set_fn)
+    }
+
+    pub fn for_conversion(state: Rc<RefCell<State<'a>>>) -> IndentWidget<'a> {
+        let convert_fn: ApplyIndentFn<'a> = Box::new(|buffer, indent| buffer.convert_indent(indent));
+        IndentWidget::new(state, "Convert indentation to (tab, tab N or N spaces)", convert_fn)
+    }
+
+    pub fn apply_indent(&mut self) {
+        let mut state = self.state.borrow_mut();
+        let index = state.current_buffer;
+        let Some(buffer) = state.buffer_list.get_mut(index) else {
+            self.prompt.error = ErrorType::NoBuffer;
+            return;
+        };
+
+        let Some(indent) = parse_indent(self.prompt.text(), buffer.indent) else {
+            self.prompt.error = ErrorType::InvalidIndent;
+            return;
+        };
+
+        (self.apply_fn)(buffer, indent);
+        self.prompt.clear();
+    }
+}
+
+impl ActionWidget for IndentWidget<'_> {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.prompt.render(area, 50, buf);
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
+        self.prompt.handle_input(key);
+        Ok(())
+    }
+
+    fn has_error(&self) -> bool {
+        self.prompt.has_error()
+    }
+
+    fn process_action(&mut self) -> Result<bool, io::Error> {
+        self.apply_indent();
+        Ok(!self.prompt.has_error())
+    }
+
+    fn init_action(&mut self) {}
+
+    fn reset(&mut self) {
+        self.prompt.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::state_with_buffer;
+
+    fn create_widget_with_input(input: &str) -> IndentWidget<'static> {
+        let state = state_with_buffer(&[""]);
+        let mut widget = IndentWidget::for_setting(state);
+        widget.prompt.input.insert_str(input);
+        widget
+    }
+
+    #[test]
+    fn test_set_indent() {
+        let mut widget = create_widget_with_input("tab 8");
+        assert!(widget.process_action().unwrap());
+
+        let state = widget.state.borrow();
+        assert_eq!(state.buffer_list[0].indent, Indent::Tabs(8));
+        assert!(state.buffer_list[0].input.hard_tab_indent());
+        assert_eq!(state.buffer_list[0].input.tab_length(), 8);
+    }
+
+    #[test]
+    fn test_convert_indent() {
+        let state = state_with_buffer(&["a", "    b"]);
+        let mut widget = IndentWidget::for_conversion(state);
+        widget.prompt.input.insert_str("tab");
+
+        assert!(widget.process_action().unwrap());
+
+        let state = widget.state.borrow();
+        assert_eq!(state.buffer_list[0].input.lines(), vec!["a", "\tb"]);
+        assert_eq!(state.buffer_list[0].indent, Indent::Tabs(4));
+    }
+
+    #[test]
+    fn test_set_indent_invalid_input() {
+        let mut widget = create_widget_with_input("wide");
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.prompt.error, ErrorType::InvalidIndent);
+    }
+}
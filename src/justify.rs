@@ -0,0 +1,184 @@
+use crate::text_view::display_width;
+
+pub const DEFAULT_FILL_COLUMN: usize = 72;
+const COMMENT_MARKERS: [&str; 7] = ["///", "//!", "//", "#", ";", "--", ">"];
+
+#[derive(Debug, PartialEq)]
+pub struct LineParts<'l> {
+    pub prefix: &'l str,
+    pub bullet: &'l str,
+    pub text: &'l str,
+}
+
+impl LineParts<'_> {
+    // Prefix of the lines following this one in the same paragraph.
+    pub fn continuation(&self) -> String {
+        format!("{}{}", self.prefix, " ".repeat(self.bullet.chars().count()))
+    }
+}
+
+fn skip_whitespace(line: &str, start: usize) -> usize {
+    line[start..].find(|c: char| !c.is_whitespace()).map_or(line.len(), |i| start + i)
+}
+
+fn bullet_len(text: &str) -> usize {
+    let marker = if text.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+        if digits > 0 && text[digits..].starts_with(['.', ')']) { digits + 1 } else { 0 }
+    };
+    if marker > 0 && text[marker..].starts_with(' ') {
+        skip_whitespace(text, marker)
+    } else {
+        0
+    }
+}
+
+// "  // - some text" -> prefix "  // ", bullet "- ", text "some text".
+pub fn split_line(line: &str) -> LineParts<'_> {
+    let mut end = skip_whitespace(line, 0);
+    while let Some(marker) = COMMENT_MARKERS.iter().find(|marker| line[end..].starts_with(*marker)) {
+        end = skip_whitespace(line, end + marker.len());
+    }
+    let bullet = bullet_len(&line[end..]);
+    LineParts { prefix: &line[..end], bullet: &line[end..end + bullet], text: &line[end + bullet..] }
+}
+
+fn continues(previous: &LineParts, line: &str) -> bool {
+    let parts = split_line(line);
+    !parts.text.trim().is_empty() && parts.bullet.is_empty() && parts.prefix == previous.continuation()
+}
+
+// First and last row of the paragraph containing `row`, if the row is not blank.
+pub fn paragraph_bounds(lines: &[String], row: usize) -> Option<(usize, usize)> {
+    if split_line(&lines[row]).text.trim().is_empty() {
+        return None;
+    }
+
+    let starts_paragraph = |row: usize| {
+        row == 0 || split_line(&lines[row - 1]).text.trim().is_empty() || !continues(&split_line(&lines[row - 1]), &lines[row])
+    };
+    let mut first = row;
+    while !starts_paragraph(first) {
+        first -= 1;
+    }
+
+    let mut last = row;
+    while last + 1 < lines.len() && continues(&split_line(&lines[last]), &lines[last + 1]) {
+        last += 1;
+    }
+    Some((first, last))
+}
+
+fn fill_paragraph(lines: &[String], fill_column: usize, tab_length: usize) -> Vec<String> {
+    let first = split_line(&lines[0]);
+    let continuation = first.continuation();
+    let words = lines.iter().flat_map(|line| split_line(line).text.split_whitespace());
+
+    let mut filled = Vec::new();
+    let mut current = format!("{}{}", first.prefix, first.bullet);
+    let mut has_word = false;
+    for word in words {
+        let width = display_width(&current, tab_length) + 1 + display_width(word, tab_length);
+        if has_word && width > fill_column {
+            filled.push(std::mem::replace(&mut current, continuation.clone()));
+            has_word = false;
+        }
+        if has_word {
+            current.push(' ');
+        }
+        current.push_str(word);
+        has_word = true;
+    }
+    filled.push(current);
+    filled
+}
+
+// Reflows every paragraph in `lines` to `fill_column`, keeping blank lines in between.
+pub fn justify_lines(lines: &[String], fill_column: usize, tab_length: usize) -> Vec<String> {
+    let mut justified = Vec::new();
+    let mut row = 0;
+    while row < lines.len() {
+        match paragraph_bounds(lines, row) {
+            Some((_, last)) => {
+                justified.extend(fill_paragraph(&lines[row..=last], fill_column, tab_length));
+                row = last + 1;
+            }
+            None => {
+                justified.push(lines[row].clone());
+                row += 1;
+            }
+        }
+    }
+    justified
+}
+
+// Byte offsets where `line` should be split to fit `fill_column`: end of the kept text and start of the moved text.
+pub fn wrap_point(line: &str, fill_column: usize, tab_length: usize) -> Option<(usize, usize)> {
+    if display_width(line.trim_end(), tab_length) <= fill_column {
+        return None;
+    }
+
+    let parts = split_line(line);
+    let text_start = line.len() - parts.text.len();
+    let mut best = None;
+    for (i, c) in line[text_start..].char_indices() {
+        let i = text_start + i;
+        if c.is_whitespace() && !line[text_start..i].trim().is_empty() {
+            if display_width(&line[..i], tab_length) > fill_column && best.is_some() {
+                break;
+            }
+            best = Some(i);
+        }
+    }
+
+    let end = best?;
+    let kept = line[..end].trim_end().len();
+    let moved = skip_whitespace(line, end);
+    (moved < line.len()).then_some((kept, moved))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_split_line() {
+        assert_eq!(split_line("  // - item"), LineParts { prefix: "  // ", bullet: "- ", text: "item" });
+        assert_eq!(split_line("> > quoted"), LineParts { prefix: "> > ", bullet: "", text: "quoted" });
+        assert_eq!(split_line("12. twelve"), LineParts { prefix: "", bullet: "12. ", text: "twelve" });
+        assert_eq!(split_line("-1 is not a bullet").bullet, "");
+    }
+
+    #[test]
+    fn test_paragraph_bounds() {
+        let text = lines(&["# one", "# two", "#", "# three", "- item", "  more", "after"]);
+        assert_eq!(paragraph_bounds(&text, 1), Some((0, 1)));
+        assert_eq!(paragraph_bounds(&text, 2), None);
+        assert_eq!(paragraph_bounds(&text, 3), Some((3, 3)));
+        assert_eq!(paragraph_bounds(&text, 5), Some((4, 5)));
+        assert_eq!(paragraph_bounds(&text, 6), Some((6, 6)));
+    }
+
+    #[test]
+    fn test_justify_keeps_prefixes() {
+        let text = lines(&["// aaa bbb", "// ccc ddd eee", "", "- one two three four"]);
+        assert_eq!(
+            justify_lines(&text, 14, 4),
+            lines(&["// aaa bbb ccc", "// ddd eee", "", "- one two", "  three four"])
+        );
+    }
+
+    #[test]
+    fn test_wrap_point() {
+        assert_eq!(wrap_point("> aaa bbb ccc", 10, 4), Some((9, 10)));
+        assert_eq!(wrap_point("aaa bbb ", 7, 4), None);
+        assert_eq!(wrap_point("aaaaaaaaaaaa", 5, 4), None);
+        assert_eq!(wrap_point("aaaaaaaa bb", 5, 4), Some((8, 9)));
+    }
+}
@@ -0,0 +1,415 @@
+use crate::action_bar::ActionType;
+use crate::keys::{key_to_string, parse_key};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use toml::{Table, Value};
+
+// Where a binding applies. Global bindings are checked first, on every screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    Global,
+    Editor,
+    ActionBar,
+}
+
+impl Context {
+    fn name(self) -> &'static str {
+        match self {
+            Context::Global => "global",
+            Context::Editor => "editor",
+            Context::ActionBar => "action_bar",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Context> {
+        [Context::Global, Context::Editor, Context::ActionBar].into_iter().find(|context| context.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Quit,
+    ToggleActionBar,
+    ToggleMacroRecording,
+    PlayLastMacro,
+    Action(ActionType),
+    Save,
+    Cut,
+    Paste,
+    Undo,
+    Redo,
+    Indent,
+    Dedent,
+    PageUp,
+    PageDown,
+    CycleSoftWrap,
+    Justify,
+    ToggleHardWrap,
+    MatchingBracket,
+    MoveLinesUp,
+    MoveLinesDown,
+    DuplicateLines,
+    DeleteLines,
+    JoinLines,
+    InsertLineBelow,
+    InsertLineAbove,
+    ToggleAutoPair,
+    ToggleAutoComplete,
+    Complete,
+    ToggleSpellCheck,
+    ToggleViMode,
+    SetMark,
+    JumpToMark,
+    Fold,
+    Unfold,
+    ToggleFold,
+    FoldAll,
+    UnfoldAll,
+    CycleFoldMethod,
+    ToggleWordCount,
+    ToggleOverwrite,
+}
+
+const COMMANDS: [(&str, Command); 58] = [
+    ("quit", Command::Quit),
+    ("action_bar", Command::ToggleActionBar),
+    ("toggle_macro_recording", Command::ToggleMacroRecording),
+    ("play_last_macro", Command::PlayLastMacro),
+    ("new_file", Command::Action(ActionType::NewFile)),
+    ("open_file", Command::Action(ActionType::OpenFile)),
+    ("change_buffer", Command::Action(ActionType::ChangeBuffer)),
+    ("close_buffer", Command::Action(ActionType::DeleteBuffer)),
+    ("goto_line", Command::Action(ActionType::GotoLine)),
+    ("indentation", Command::Action(ActionType::Indentation)),
+    ("reindent", Command::Action(ActionType::ConvertIndentation)),
+    ("transform", Command::Action(ActionType::Transform)),
+    ("pipe", Command::Action(ActionType::Pipe)),
+    ("insert_file", Command::Action(ActionType::InsertFile)),
+    ("write_selection", Command::Action(ActionType::WriteSelection)),
+    ("fill_column", Command::Action(ActionType::FillColumn)),
+    ("play_macro", Command::Action(ActionType::PlayMacro)),
+    ("save_macro", Command::Action(ActionType::SaveMacro)),
+    ("insert_snippet", Command::Action(ActionType::InsertSnippet)),
+    ("spelling", Command::Action(ActionType::Spelling)),
+    ("whitespace", Command::Action(ActionType::Whitespace)),
+    ("marks", Command::Action(ActionType::Marks)),
+    ("statistics", Command::Action(ActionType::Statistics)),
+    ("save", Command::Save),
+    ("cut", Command::Cut),
+    ("paste", Command::Paste),
+    ("undo", Command::Undo),
+    ("redo", Command::Redo),
+    ("indent", Command::Indent),
+    ("dedent", Command::Dedent),
+    ("page_up", Command::PageUp),
+    ("page_down", Command::PageDown),
+    ("soft_wrap", Command::CycleSoftWrap),
+    ("justify", Command::Justify),
+    ("hard_wrap", Command::ToggleHardWrap),
+    ("matching_bracket", Command::MatchingBracket),
+    ("move_lines_up", Command::MoveLinesUp),
+    ("move_lines_down", Command::MoveLinesDown),
+    ("duplicate_lines", Command::DuplicateLines),
+    ("delete_lines", Command::DeleteLines),
+    ("join_lines", Command::JoinLines),
+    ("insert_line_below", Command::InsertLineBelow),
+    ("insert_line_above", Command::InsertLineAbove),
+    ("auto_pair", Command::ToggleAutoPair),
+    ("auto_complete", Command::ToggleAutoComplete),
+    ("complete", Command::Complete),
+    ("spell_check", Command::ToggleSpellCheck),
+    ("vi_mode", Command::ToggleViMode),
+    ("set_mark", Command::SetMark),
+    ("jump_to_mark", Command::JumpToMark),
+    ("fold", Command::Fold),
+    ("unfold", Command::Unfold),
+    ("toggle_fold", Command::ToggleFold),
+    ("fold_all", Command::FoldAll),
+    ("unfold_all", Command::UnfoldAll),
+    ("fold_method", Command::CycleFoldMethod),
+    ("word_count", Command::ToggleWordCount),
+    ("overwrite", Command::ToggleOverwrite),
+];
+
+const DEFAULT_BINDINGS: [(Context, &str, &str); 63] = [
+    (Context::Global, "C-q", "quit"),
+    (Context::Global, "C-Space", "action_bar"),
+    (Context::Global, "M-:", "toggle_macro_recording"),
+    (Context::Global, "M-;", "play_last_macro"),
+    (Context::Editor, "C-_", "goto_line"),
+    // Ctrl+_ arrives as Ctrl+7 on most terminals
+    (Context::Editor, "C-7", "goto_line"),
+    (Context::Editor, "C-r", "insert_file"),
+    (Context::Editor, "C-s", "save"),
+    (Context::Editor, "C-k", "cut"),
+    (Context::Editor, "C-u", "paste"),
+    // Ctrl+U is taken by paste, so undo and redo follow nano's Alt+U / Alt+E
+    (Context::Editor, "M-u", "undo"),
+    (Context::Editor, "M-e", "redo"),
+    (Context::Editor, "M-}", "indent"),
+    (Context::Editor, "M-{", "dedent"),
+    (Context::Editor, "PageUp", "page_up"),
+    (Context::Editor, "M-v", "page_up"),
+    (Context::Editor, "PageDown", "page_down"),
+    (Context::Editor, "C-v", "page_down"),
+    (Context::Editor, "M-s", "soft_wrap"),
+    (Context::Editor, "C-j", "justify"),
+    (Context::Editor, "M-l", "hard_wrap"),
+    (Context::Editor, "M-]", "matching_bracket"),
+    (Context::Editor, "M-Up", "move_lines_up"),
+    (Context::Editor, "M-Down", "move_lines_down"),
+    (Context::Editor, "M-S-Down", "duplicate_lines"),
+    (Context::Editor, "M-k", "delete_lines"),
+    (Context::Editor, "M-j", "join_lines"),
+    (Context::Editor, "M-o", "insert_line_below"),
+    (Context::Editor, "M-O", "insert_line_above"),
+    (Context::Editor, "M-(", "auto_pair"),
+    (Context::Editor, "M-c", "auto_complete"),
+    (Context::Editor, "M-/", "complete"),
+    (Context::Editor, "F7", "spell_check"),
+    (Context::Editor, "M-m", "vi_mode"),
+    (Context::Editor, "M-a", "set_mark"),
+    (Context::Editor, "M-g", "jump_to_mark"),
+    // Folding follows vi's z commands, after Alt+Z
+    (Context::Editor, "M-z c", "fold"),
+    (Context::Editor, "M-z o", "unfold"),
+    (Context::Editor, "M-z a", "toggle_fold"),
+    (Context::Editor, "M-z m", "fold_all"),
+    (Context::Editor, "M-z r", "unfold_all"),
+    (Context::Editor, "M-z t", "fold_method"),
+    (Context::Editor, "M-#", "word_count"),
+    (Context::Editor, "Insert", "overwrite"),
+    (Context::ActionBar, "n", "new_file"),
+    (Context::ActionBar, "o", "open_file"),
+    (Context::ActionBar, "b", "change_buffer"),
+    (Context::ActionBar, "d", "close_buffer"),
+    (Context::ActionBar, "g", "goto_line"),
+    (Context::ActionBar, "i", "indentation"),
+    (Context::ActionBar, "r", "reindent"),
+    (Context::ActionBar, "t", "transform"),
+    (Context::ActionBar, "p", "pipe"),
+    (Context::ActionBar, "f", "insert_file"),
+    (Context::ActionBar, "w", "write_selection"),
+    (Context::ActionBar, "l", "fill_column"),
+    (Context::ActionBar, "m", "play_macro"),
+    (Context::ActionBar, "s", "save_macro"),
+    (Context::ActionBar, "e", "insert_snippet"),
+    (Context::ActionBar, "c", "spelling"),
+    (Context::ActionBar, "x", "whitespace"),
+    (Context::ActionBar, "k", "marks"),
+    (Context::ActionBar, "a", "statistics"),
+];
+
+pub fn command_by_name(name: &str) -> Option<Command> {
+    COMMANDS.iter().find(|(command_name, _)| *command_name == name).map(|(_, command)| *command)
+}
+
+pub fn command_name(command: Command) -> &'static str {
+    COMMANDS.iter().find(|(_, named)| *named == command).map_or("", |(name, _)| name)
+}
+
+// Characters already carry Shift in their case or symbol, so only other modifiers count.
+fn normalize(key: &KeyEvent) -> (KeyCode, KeyModifiers) {
+    match key.code {
+        KeyCode::Char(c) => (KeyCode::Char(c), key.modifiers - KeyModifiers::SHIFT),
+        code => (code, key.modifiers),
+    }
+}
+
+fn same_keys(a: &[KeyEvent], b: &[KeyEvent]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| normalize(a) == normalize(b))
+}
+
+// Space-separated chords, as in "C-x C-s".
+pub fn parse_sequence(text: &str) -> Option<Vec<KeyEvent>> {
+    let keys: Option<Vec<KeyEvent>> = text.split_whitespace().map(parse_key).collect();
+    keys.filter(|keys| !keys.is_empty())
+}
+
+pub fn sequence_to_string(keys: &[KeyEvent]) -> String {
+    keys.iter().filter_map(key_to_string).collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub context: Context,
+    pub keys: Vec<KeyEvent>,
+    pub command: Command,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lookup {
+    Command(Context, Command),
+    // The keys start a longer sequence.
+    Prefix,
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    pub bindings: Vec<Binding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .filter_map(|&(context, keys, name)| Some(Binding { context, keys: parse_sequence(keys)?, command: command_by_name(name)? }))
+            .collect();
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    // Global bindings win over the ones of `context`.
+    pub fn lookup(&self, context: Option<Context>, keys: &[KeyEvent]) -> Lookup {
+        let contexts = [Some(Context::Global), context];
+        for context in contexts.into_iter().flatten() {
+            let bindings = || self.bindings.iter().filter(move |binding| binding.context == context);
+            if let Some(binding) = bindings().find(|binding| same_keys(&binding.keys, keys)) {
+                return Lookup::Command(context, binding.command);
+            }
+            if bindings().any(|binding| binding.keys.len() > keys.len() && same_keys(&binding.keys[..keys.len()], keys)) {
+                return Lookup::Prefix;
+            }
+        }
+        Lookup::None
+    }
+
+    // The command a single key runs in `context` itself, ignoring global bindings.
+    pub fn command_for(&self, context: Context, key: &KeyEvent) -> Option<Command> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.context == context && same_keys(&binding.keys, std::slice::from_ref(key)))
+            .map(|binding| binding.command)
+    }
+
+    // The sequences that run `command` in `context`, as written in the config.
+    pub fn keys_for(&self, context: Context, command: Command) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.context == context && binding.command == command)
+            .map(|binding| sequence_to_string(&binding.keys))
+            .collect()
+    }
+
+    // Replaces the bindings of `command` in `context`; no sequences unbinds it.
+    pub fn bind(&mut self, context: Context, command: Command, sequences: Vec<Vec<KeyEvent>>) {
+        self.bindings.retain(|binding| binding.context != context || binding.command != command);
+        self.bindings.extend(sequences.into_iter().map(|keys| Binding { context, keys, command }));
+    }
+
+    // Bindings that can never run because another one in the same or the global context
+    // uses the same keys or a prefix of them.
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        for (i, a) in self.bindings.iter().enumerate() {
+            for b in &self.bindings[i + 1..] {
+                let overlapping = a.context == b.context || a.context == Context::Global || b.context == Context::Global;
+                let len = a.keys.len().min(b.keys.len());
+                if overlapping && a.command != b.command && same_keys(&a.keys[..len], &b.keys[..len]) {
+                    conflicts.push(format!(
+                        "{} ({}) conflicts with {} ({})",
+                        sequence_to_string(&a.keys),
+                        command_name(a.command),
+                        sequence_to_string(&b.keys),
+                        command_name(b.command)
+                    ));
+                }
+            }
+        }
+        conflicts
+    }
+
+    // Applies `[keymap.<context>]` tables of `command = "keys"` or `command = ["keys", ...]`.
+    pub fn apply_overrides(&mut self, sections: &Table) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (section, bindings) in sections {
+            let Some(context) = Context::parse(section) else {
+                errors.push(format!("unknown keymap section {}", section));
+                continue;
+            };
+            let Value::Table(bindings) = bindings else {
+                errors.push(format!("keymap.{} must be a table", section));
+                continue;
+            };
+            for (name, keys) in bindings {
+                let Some(command) = command_by_name(name) else {
+                    errors.push(format!("unknown command {}", name));
+                    continue;
+                };
+                let values = match keys {
+                    Value::Array(values) => values.as_slice(),
+                    value => std::slice::from_ref(value),
+                };
+                let sequences: Option<Vec<Vec<KeyEvent>>> = values
+                    .iter()
+                    .filter(|value| **value != Value::String(String::new()))
+                    .map(|value| match value {
+                        Value::String(text) => parse_sequence(text),
+                        _ => None,
+                    })
+                    .collect();
+                match sequences {
+                    Some(sequences) => self.bind(context, command, sequences),
+                    None => errors.push(format!("invalid keys for {}", name)),
+                }
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(text: &str) -> KeyEvent {
+        parse_key(text).unwrap()
+    }
+
+    fn overrides(keymap: &mut Keymap, content: &str) -> Vec<String> {
+        keymap.apply_overrides(&toml::from_str(content).unwrap())
+    }
+
+    #[test]
+    fn test_default_keymap_has_no_conflicts() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.bindings.len(), DEFAULT_BINDINGS.len());
+        assert!(keymap.conflicts().is_empty(), "{:?}", keymap.conflicts());
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut keymap = Keymap::default();
+        overrides(&mut keymap, "[editor]\nquit = \"C-x C-c\"");
+        assert_eq!(keymap.lookup(Some(Context::Editor), &[key("C-s")]), Lookup::Command(Context::Editor, Command::Save));
+        assert_eq!(keymap.lookup(Some(Context::ActionBar), &[key("C-q")]), Lookup::Command(Context::Global, Command::Quit));
+        assert_eq!(keymap.lookup(None, &[key("C-s")]), Lookup::None);
+        assert_eq!(keymap.lookup(Some(Context::Editor), &[key("C-x")]), Lookup::Prefix);
+        assert_eq!(keymap.lookup(Some(Context::Editor), &[key("C-x"), key("C-c")]), Lookup::Command(Context::Editor, Command::Quit));
+        // Shift is part of the character
+        let shifted = KeyEvent::new(KeyCode::Char('}'), KeyModifiers::ALT | KeyModifiers::SHIFT);
+        assert_eq!(keymap.command_for(Context::Editor, &shifted), Some(Command::Indent));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let mut keymap = Keymap::default();
+        let errors = overrides(&mut keymap, "[editor]\nsave = [\"C-x C-s\", \"F2\"]\nundo = \"\"\nbogus = \"C-b\"\nredo = \"C-Nope\"\n[nowhere]\n");
+        assert_eq!(errors, vec!["unknown command bogus", "invalid keys for redo", "unknown keymap section nowhere"]);
+        assert_eq!(keymap.command_for(Context::Editor, &key("C-s")), None);
+        assert_eq!(keymap.command_for(Context::Editor, &key("F2")), Some(Command::Save));
+        assert_eq!(keymap.command_for(Context::Editor, &key("M-u")), None);
+        assert_eq!(keymap.lookup(Some(Context::Editor), &[key("C-x"), key("C-s")]), Lookup::Command(Context::Editor, Command::Save));
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut keymap = Keymap::default();
+        overrides(&mut keymap, "[editor]\nsave = \"C-k\"\nquit = \"C-x C-c\"\nredo = \"C-x\"\n[global]\naction_bar = \"C-j\"");
+        assert_eq!(
+            keymap.conflicts(),
+            vec!["C-k (cut) conflicts with C-k (save)", "C-j (justify) conflicts with C-j (action_bar)", "C-x C-c (quit) conflicts with C-x (redo)"]
+        );
+    }
+}
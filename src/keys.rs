@@ -0,0 +1,119 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+const NAMED_KEYS: [(&str, KeyCode); 15] = [
+    ("Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("BackTab", KeyCode::BackTab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Esc", KeyCode::Esc),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+];
+
+const MODIFIERS: [(&str, KeyModifiers); 3] = [
+    ("C-", KeyModifiers::CONTROL),
+    ("M-", KeyModifiers::ALT),
+    ("S-", KeyModifiers::SHIFT),
+];
+
+fn code_name(code: KeyCode) -> Option<String> {
+    match code {
+        KeyCode::Char(' ') => Some("Space".to_string()),
+        KeyCode::Char(c) => Some(c.to_string()),
+        KeyCode::F(n) => Some(format!("F{}", n)),
+        code => NAMED_KEYS.iter().find(|(_, named)| *named == code).map(|(name, _)| name.to_string()),
+    }
+}
+
+fn parse_code(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => return Some(KeyCode::Char(c)),
+        (None, _) => return None,
+        _ => (),
+    }
+    match name {
+        "Space" => Some(KeyCode::Char(' ')),
+        _ => {
+            if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse().ok()) {
+                return Some(KeyCode::F(n));
+            }
+            NAMED_KEYS.iter().find(|(named, _)| *named == name).map(|(_, code)| *code)
+        }
+    }
+}
+
+// Emacs-style notation: "C-s", "M-S-Down", "Enter", "Space".
+pub fn key_to_string(key: &KeyEvent) -> Option<String> {
+    let mut text = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        text.push_str("C-");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        text.push_str("M-");
+    }
+    if key.modifiers.contains(KeyModifiers::SHIFT) {
+        text.push_str("S-");
+    }
+    text.push_str(&code_name(key.code)?);
+    Some(text)
+}
+
+pub fn parse_key(text: &str) -> Option<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = text.trim();
+    // A prefix with nothing after it is the key itself, as in "C-".
+    while let Some((next, modifier)) = MODIFIERS
+        .iter()
+        .find_map(|&(prefix, modifier)| rest.strip_prefix(prefix).filter(|next| !next.is_empty()).map(|next| (next, modifier)))
+    {
+        modifiers |= modifier;
+        rest = next;
+    }
+    Some(KeyEvent::new(parse_code(rest)?, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_round_trip() {
+        let keys = [
+            KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Down, KeyModifiers::ALT | KeyModifiers::SHIFT),
+            KeyEvent::new(KeyCode::Char('-'), KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE),
+        ];
+        for key in keys {
+            let text = key_to_string(&key).unwrap();
+            assert_eq!(parse_key(&text), Some(key), "{}", text);
+        }
+        assert_eq!(key_to_string(&keys[1]).unwrap(), "M-S-Down");
+    }
+
+    #[test]
+    fn test_parse_invalid_key() {
+        assert_eq!(parse_key("C-"), None);
+        assert_eq!(parse_key("Hyper"), None);
+        assert_eq!(parse_key(""), None);
+    }
+
+    #[test]
+    fn test_parse_multibyte_key() {
+        assert_eq!(parse_key("C-€"), Some(KeyEvent::new(KeyCode::Char('€'), KeyModifiers::CONTROL)));
+        assert_eq!(parse_key("€"), Some(KeyEvent::new(KeyCode::Char('€'), KeyModifiers::NONE)));
+        assert_eq!(parse_key("€x"), None);
+        assert_eq!(parse_key("M-é-"), None);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::action_bar::ActionWidget;
+use crate::error_type::ErrorType;
+use crate::macros::{is_valid_name, load_macro, save_macro, Playback, Repeat};
+use crate::prompt::Prompt;
+use crate::state::State;
+use crossterm::event::KeyEvent;
+use log::error;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use std::cell::RefCell;
+use std::io;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MacroPrompt {
+    Play,
+    Save,
+}
+
+fn macro_name(name: &str) -> Result<&str, ErrorType> {
+    if is_valid_name(name) {
+        Ok(name)
+    } else {
+        Err(ErrorType::InvalidMacroName)
+    }
+}
+
+fn repeat(word: &str) -> Result<Repeat, ErrorType> {
+    match word {
+        "*" => Ok(Repeat::UntilEnd),
+        _ => word.parse().ok().filter(|&n| n > 0).map(Repeat::Times).ok_or(ErrorType::InvalidRepeatCount),
+    }
+}
+
+// "[name] [count | *]", where an empty input plays the last macro once.
+// A lone number or * is a count for the last macro.
+pub fn parse_playback(input: &str) -> Result<(Option<&str>, Repeat), ErrorType> {
+    let is_count = |word: &str| word == "*" || word.chars().all(|c| c.is_ascii_digit());
+
+    let words: Vec<&str> = input.split_whitespace().collect();
+    match words.as_slice() {
+        [] => Ok((None, Repeat::Times(1))),
+        [word] if is_count(word) => Ok((None, repeat(word)?)),
+        [name] => Ok((Some(macro_name(name)?), Repeat::Times(1))),
+        [name, count] => Ok((Some(macro_name(name)?), repeat(count)?)),
+        _ => Err(ErrorType::InvalidPlayback),
+    }
+}
+
+#[derive(Debug)]
+pub struct MacroWidget<'a> {
+    pub state: Rc<RefCell<State<'a>>>,
+    pub prompt: Prompt<'a>,
+    pub kind: MacroPrompt,
+    // Where named macros are kept, none without a config directory.
+    pub dir: Option<PathBuf>,
+}
+
+impl<'a> MacroWidget<'a> {
+    pub fn new(state: Rc<RefCell<State<'a>>>, kind: MacroPrompt, dir: Option<PathBuf>) -> MacroWidget<'a> {
+        let title = match kind {
+            MacroPrompt::Play => "Play macro ([name] [count or *])",
+            MacroPrompt::Save => "Save last macro as",
+        };
+        MacroWidget { state, prompt: Prompt::new(title), kind, dir }
+    }
+
+    fn play(&self, state: &mut State<'a>, input: &str) -> Result<(), ErrorType> {
+        let (name, repeat) = parse_playback(input)?;
+        let keys = match (name, &self.dir) {
+            (None, _) => state.macros.last.clone(),
+            (Some(name), Some(dir)) if dir.join(name).is_file() => load_macro(dir, name).map_err(|error| {
+                error!("{}: {}", name, error);
+                ErrorType::Io(error.to_string())
+            })?,
+            (Some(_), _) => return Err(ErrorType::MacroNotFound),
+        };
+        if keys.is_empty() {
+            return Err(if name.is_none() { ErrorType::NoMacroRecorded } else { ErrorType::MacroNotFound });
+        }
+
+        state.macros.pending = Some(Playback { keys, repeat });
+        Ok(())
+    }
+
+    fn save(&self, state: &mut State<'a>, input: &str) -> Result<(), ErrorType> {
+        let name = macro_name(input.trim())?;
+        if state.macros.last.is_empty() {
+            return Err(ErrorType::NoMacroRecorded);
+        }
+        let Some(dir) = &self.dir else {
+            return Err(ErrorType::NoMacroDir);
+        };
+
+        save_macro(dir, name, &state.macros.last).map_err(|error| {
+            error!("{}: {}", name, error);
+            ErrorType::Io(error.to_string())
+        })
+    }
+
+    pub fn run_macro_action(&mut self) {
+        let mut state = self.state.borrow_mut();
+        let input = self.prompt.text().to_string();
+        let result = match self.kind {
+            MacroPrompt::Play => self.play(&mut state, &input),
+            MacroPrompt::Save => self.save(&mut state, &input),
+        };
+
+        match result {
+            Ok(()) => {
+                self.prompt.clear();
+            }
+            Err(error) => self.prompt.error = error,
+        }
+    }
+}
+
+impl ActionWidget for MacroWidget<'_> {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.prompt.render(area, 50, buf);
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
+        self.prompt.handle_input(key);
+        Ok(())
+    }
+
+    fn has_error(&self) -> bool {
+        self.prompt.has_error()
+    }
+
+    fn process_action(&mut self) -> Result<bool, io::Error> {
+        self.run_macro_action();
+        Ok(!self.prompt.has_error())
+    }
+
+    fn init_action(&mut self) {}
+
+    fn reset(&mut self) {
+        self.prompt.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use tempfile::tempdir;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn run(widget: &mut MacroWidget, input: &str) -> ErrorType {
+        widget.reset();
+        widget.prompt.input.insert_str(input);
+        widget.process_action().unwrap();
+        widget.prompt.error.clone()
+    }
+
+    #[test]
+    fn test_parse_playback() {
+        assert_eq!(parse_playback(""), Ok((None, Repeat::Times(1))));
+        assert_eq!(parse_playback(" 3 "), Ok((None, Repeat::Times(3))));
+        assert_eq!(parse_playback("*"), Ok((None, Repeat::UntilEnd)));
+        assert_eq!(parse_playback("fix *"), Ok((Some("fix"), Repeat::UntilEnd)));
+        assert_eq!(parse_playback("fix"), Ok((Some("fix"), Repeat::Times(1))));
+        assert_eq!(parse_playback("0"), Err(ErrorType::InvalidRepeatCount));
+        assert_eq!(parse_playback("fix 0"), Err(ErrorType::InvalidRepeatCount));
+        assert_eq!(parse_playback("fix twice"), Err(ErrorType::InvalidRepeatCount));
+        assert_eq!(parse_playback("fix! 2"), Err(ErrorType::InvalidMacroName));
+        assert_eq!(parse_playback("a b c"), Err(ErrorType::InvalidPlayback));
+    }
+
+    #[test]
+    fn test_save_and_play_named_macro() {
+        let dir = tempdir().unwrap();
+        let state = Rc::new(RefCell::new(State::default()));
+        let mut save = MacroWidget::new(state.clone(), MacroPrompt::Save, Some(dir.path().to_path_buf()));
+        let mut play = MacroWidget::new(state.clone(), MacroPrompt::Play, Some(dir.path().to_path_buf()));
+
+        assert_eq!(run(&mut save, "fix"), ErrorType::NoMacroRecorded);
+        assert_eq!(run(&mut play, ""), ErrorType::NoMacroRecorded);
+
+        state.borrow_mut().macros.last = vec![key('x'), KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)];
+        assert_eq!(run(&mut save, "bad name"), ErrorType::InvalidMacroName);
+        assert_eq!(run(&mut save, "fix"), ErrorType::NONE);
+
+        state.borrow_mut().macros.last.clear();
+        assert_eq!(run(&mut play, "missing"), ErrorType::MacroNotFound);
+        assert_eq!(run(&mut play, "fix 2"), ErrorType::NONE);
+
+        let pending = state.borrow_mut().macros.pending.take().unwrap();
+        assert_eq!(pending.keys, vec![key('x'), KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)]);
+        assert_eq!(pending.repeat, Repeat::Times(2));
+    }
+
+    #[test]
+    fn test_save_without_config_dir() {
+        let state = Rc::new(RefCell::new(State::default()));
+        state.borrow_mut().macros.last = vec![key('x')];
+        let mut save = MacroWidget::new(state, MacroPrompt::Save, None);
+        assert_eq!(run(&mut save, "fix"), ErrorType::NoMacroDir);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::keys::{key_to_string, parse_key};
+use crossterm::event::KeyEvent;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Upper bound for "until end of buffer" playback, in case the macro never reaches the end.
+pub const MAX_PLAYBACK: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Repeat {
+    Times(usize),
+    UntilEnd,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playback {
+    pub keys: Vec<KeyEvent>,
+    pub repeat: Repeat,
+}
+
+#[derive(Debug, Default)]
+pub struct Macros {
+    pub recording: Option<Vec<KeyEvent>>,
+    pub last: Vec<KeyEvent>,
+    pub pending: Option<Playback>,
+}
+
+impl Macros {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn toggle_recording(&mut self) {
+        match self.recording.take() {
+            Some(keys) => self.last = keys,
+            None => self.recording = Some(Vec::new()),
+        }
+    }
+
+    pub fn record(&mut self, key: KeyEvent) {
+        if let Some(keys) = &mut self.recording {
+            keys.push(key);
+        }
+    }
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn macros_dir() -> Option<PathBuf> {
+    crate::paths::config_dir().map(|dir| dir.join("macros"))
+}
+
+// One key per line in the notation of `keys::key_to_string`.
+pub fn save_macro(dir: &Path, name: &str, keys: &[KeyEvent]) -> io::Result<()> {
+    let lines: Vec<String> = keys.iter().filter_map(key_to_string).collect();
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(name), lines.join("\n") + "\n")
+}
+
+pub fn load_macro(dir: &Path, name: &str) -> io::Result<Vec<KeyEvent>> {
+    let content = fs::read_to_string(dir.join(name))?;
+    Ok(content.lines().filter_map(parse_key).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use tempfile::tempdir;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_recording() {
+        let mut macros = Macros::default();
+        macros.record(key('a'));
+        macros.toggle_recording();
+        assert!(macros.is_recording());
+        macros.record(key('b'));
+        macros.toggle_recording();
+
+        assert!(!macros.is_recording());
+        assert_eq!(macros.last, vec![key('b')]);
+    }
+
+    #[test]
+    fn test_save_and_load_macro() {
+        let dir = tempdir().unwrap();
+        let keys = vec![key('x'), KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)];
+        save_macro(dir.path(), "fix", &keys).unwrap();
+        assert_eq!(load_macro(dir.path(), "fix").unwrap(), keys);
+        assert!(is_valid_name("fix-2_b"));
+        assert!(!is_valid_name("bad name"));
+    }
+}
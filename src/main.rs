@@ -1,10 +1,24 @@
+// Lints that the original code and its tests trip, left as written.
+#![allow(
+    clippy::upper_case_acronyms,
+    clippy::borrowed_box,
+    clippy::explicit_auto_deref,
+    clippy::bool_assert_comparison,
+    clippy::field_reassign_with_default,
+    mismatched_lifetime_syntaxes
+)]
+// Unused test helpers of the original tests.
+#![cfg_attr(test, allow(dead_code))]
+
 use std::error::Error;
 use std::fs::{OpenOptions};
+use std::path::{Path, PathBuf};
 use chrono::Local;
-use clap::{arg, command};
+use clap::{arg, command, value_parser, ArgMatches};
 use fern::Dispatch;
 use log::{log, Level};
 use crate::app::App;
+use toml::{Table, Value};
 
 pub mod editor;
 pub mod buffer;
@@ -18,13 +32,49 @@ mod error_type;
 mod open_file_widget;
 mod popup;
 mod buffer_list_widget;
+mod cut_buffer;
+mod text_view;
+mod goto_line_widget;
+mod indent;
+mod indent_widget;
+mod brackets;
+mod history;
+mod transform;
+mod transform_widget;
+mod pipe;
+mod pipe_widget;
+mod justify;
+mod fill_column_widget;
+mod keys;
+mod keymap;
+mod config;
+mod editorconfig;
+mod theme;
+mod paths;
+mod macros;
+mod macro_widget;
+mod completion;
+mod snippets;
+mod snippet_widget;
+mod spell;
+mod spell_widget;
+mod whitespace;
+mod whitespace_widget;
+mod vi;
+mod marks;
+mod marks_widget;
+mod folding;
+mod stats;
+mod stats_widget;
+mod prompt;
+#[cfg(test)]
+mod test_support;
 
-fn init_logger() {
+fn init_logger(path: &Path) -> Result<(), Box<dyn Error>> {
     let log_file = OpenOptions::new()
         .create(true)
         .append(true)
-        .open("divino_editor_log")
-        .expect("Unable to open log file");
+        .open(path)?;
 
     Dispatch::new()
         .format(|out, message, record| {
@@ -36,25 +86,63 @@ fn init_logger() {
             ));
         })
         .chain(log_file)
-        .apply()
-        .expect("Initialization failed");
+        .apply()?;
+    Ok(())
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    init_logger();
-    log!(Level::Info,"Welcome to Divitext!");
+// Settings given on the command line, as the last config layer.
+fn flag_settings(matches: &ArgMatches) -> Table {
+    let mut flags = Table::new();
+    if let Some(width) = matches.get_one::<i64>("tab-width") {
+        flags.insert("tab_width".to_string(), Value::Integer(*width));
+    }
+    if let Some(on) = matches.get_one::<bool>("line-numbers") {
+        flags.insert("line_numbers".to_string(), Value::Boolean(*on));
+    }
+    if let Some(theme) = matches.get_one::<String>("theme") {
+        flags.insert("theme".to_string(), Value::String(theme.clone()));
+    }
+    if let Some(seconds) = matches.get_one::<i64>("autosave") {
+        flags.insert("autosave".to_string(), Value::Integer(*seconds));
+    }
+    if let Some(path) = matches.get_one::<String>("log-file") {
+        flags.insert("log_file".to_string(), Value::String(path.clone()));
+    }
+    flags
+}
 
+fn main() -> Result<(), Box<dyn Error>> {
     let matches = command!()
         .author("Schaeffer Divino, divino.schaeffer@gmail.com")
         .arg(arg!([FILE] "Open a file").required(false))
+        .arg(arg!(-c --config <FILE> "Read settings from FILE instead of the user config file").value_parser(value_parser!(PathBuf)))
+        .arg(arg!(--"no-config" "Ignore the user and project config files").conflicts_with("config"))
+        .arg(arg!(--"tab-width" <WIDTH> "Indentation width for new buffers").value_parser(value_parser!(i64)))
+        .arg(arg!(--"line-numbers" <BOOL> "Show line numbers").value_parser(value_parser!(bool)))
+        .arg(arg!(--theme <NAME> "Color theme: dark, light or monochrome"))
+        .arg(arg!(--autosave <SECONDS> "Save modified files every SECONDS, 0 to turn off").value_parser(value_parser!(i64)))
+        .arg(arg!(--"log-file" <PATH> "Where to write the log"))
         .get_matches();
 
     let file = matches.get_one::<String>("FILE");
+    let explicit = matches.get_one::<PathBuf>("config");
+    let (files, project) = if matches.get_flag("no-config") {
+        (Vec::new(), None)
+    } else {
+        (explicit.cloned().or_else(config::user_config_file).into_iter().collect(), config::project_config_file())
+    };
+    let (config, mut errors) = config::load(&files, project.as_deref(), explicit.map(PathBuf::as_path), &flag_settings(&matches));
+
+    if let Err(error) = init_logger(&config.log_file) {
+        errors.push(format!("cannot open log file {}: {}", config.log_file.display(), error));
+    }
+    log!(Level::Info,"Welcome to Divitext!");
 
     let mut terminal = ratatui::init();
 
     App::init(&mut terminal)?;
     let mut app: App = App::default();
+    app.configure(config, errors);
     app.run(&mut terminal, file)?;
     App::drop(&mut terminal)?;
     ratatui::restore();
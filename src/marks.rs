@@ -0,0 +1,310 @@
+use crate::buffer::Buffer;
+use crate::error_type::ErrorType;
+use crate::history::common_ends;
+use crate::paths::config_dir;
+use crate::state::State;
+use log::{log, Level};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+const SESSION_FILE: &str = "session.toml";
+
+// Marks a-z belong to one buffer; A-Z are global and jump to their file.
+pub fn is_mark(mark: char) -> bool {
+    mark.is_ascii_alphabetic()
+}
+
+pub fn is_global(mark: char) -> bool {
+    mark.is_ascii_uppercase()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkCommand {
+    Set,
+    Jump,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlobalMark {
+    pub path: PathBuf,
+    pub row: usize,
+    pub col: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkEntry {
+    pub mark: char,
+    pub row: usize,
+    pub col: usize,
+    // The marked line, or the file of a global mark in another buffer.
+    pub text: String,
+}
+
+// Where a mark on `mark_row` ends up after lines `row..row + before.len()` were replaced
+// by `after`. A marked line that moved within the change follows its text; None when
+// the line was removed.
+pub fn shift_row(mark_row: usize, row: usize, before: &[String], after: &[String]) -> Option<usize> {
+    if mark_row < row {
+        return Some(mark_row);
+    }
+    if mark_row >= row + before.len() {
+        return Some(mark_row + after.len() - before.len());
+    }
+
+    let (prefix, suffix) = common_ends(before, after);
+    let offset = mark_row - row;
+    if offset < prefix {
+        return Some(mark_row);
+    }
+    if offset >= before.len() - suffix {
+        return Some(mark_row + after.len() - before.len());
+    }
+
+    let changed = &after[prefix..after.len() - suffix];
+    if let Some(moved) = changed.iter().position(|line| *line == before[offset]) {
+        return Some(row + prefix + moved);
+    }
+    (offset - prefix < changed.len()).then_some(mark_row)
+}
+
+fn canonical_path(path: &str) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+fn buffer_path(buffer: &Buffer) -> Option<PathBuf> {
+    buffer.path.as_deref().map(canonical_path)
+}
+
+pub fn session_file() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(SESSION_FILE))
+}
+
+// Global marks saved by `save_session`; entries that do not parse are skipped.
+pub fn load_session(path: &Path) -> BTreeMap<char, GlobalMark> {
+    let Some(Value::Table(marks)) = fs::read_to_string(path).ok().and_then(|content| toml::from_str::<Table>(&content).ok()).and_then(|mut table| table.remove("marks")) else {
+        return BTreeMap::new();
+    };
+
+    let mut global_marks = BTreeMap::new();
+    for (name, value) in marks {
+        let mut chars = name.chars();
+        let (Some(mark), None, Ok(global)) = (chars.next(), chars.next(), value.try_into::<GlobalMark>()) else {
+            continue;
+        };
+        if is_global(mark) {
+            global_marks.insert(mark, global);
+        }
+    }
+    global_marks
+}
+
+#[derive(Serialize)]
+struct Session<'m> {
+    marks: BTreeMap<String, &'m GlobalMark>,
+}
+
+pub fn write_session(path: &Path, marks: &BTreeMap<char, GlobalMark>) -> io::Result<()> {
+    let session = toml::to_string(&Session { marks: marks.iter().map(|(mark, global)| (mark.to_string(), global)).collect() }).map_err(io::Error::other)?;
+    let content = format!("# Global marks, rewritten by divitext on exit.\n\n{}", session);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, content)
+}
+
+// Copies the positions of global marks from the open buffers, which keep them up to date.
+pub fn sync_global_marks(state: &mut State) {
+    for buffer in &state.buffer_list {
+        let Some(path) = buffer_path(buffer) else {
+            continue;
+        };
+        state.global_marks.retain(|mark, global| global.path != path || buffer.marks.contains_key(mark));
+        for (&mark, &(row, col)) in buffer.marks.iter().filter(|(mark, _)| is_global(**mark)) {
+            state.global_marks.insert(mark, GlobalMark { path: path.clone(), row, col });
+        }
+    }
+}
+
+// Global marks that belong to the file of `buffer`.
+pub fn restore_global_marks(state: &State, buffer: &mut Buffer) {
+    if let Some(path) = buffer_path(buffer) {
+        for (&mark, global) in state.global_marks.iter().filter(|(_, global)| global.path == path) {
+            buffer.marks.insert(mark, (global.row, global.col));
+        }
+    }
+}
+
+pub fn save_session(state: &mut State) {
+    sync_global_marks(state);
+    if let Some(path) = &state.session_file {
+        if let Err(error) = write_session(path, &state.global_marks) {
+            log!(Level::Warn, "Could not save the session to {}: {}", path.display(), error);
+        }
+    }
+}
+
+pub fn set_mark(state: &mut State, mark: char) -> Result<(), ErrorType> {
+    if !is_mark(mark) {
+        return Err(ErrorType::InvalidMark);
+    }
+    let current = state.current_buffer;
+    let buffer = state.buffer_list.get(current).ok_or(ErrorType::NoBuffer)?;
+    let position = buffer.input.cursor();
+    if !is_global(mark) {
+        state.buffer_list[current].marks.insert(mark, position);
+        return Ok(());
+    }
+
+    let path = buffer_path(buffer).ok_or(ErrorType::NoFileForMark)?;
+    for buffer in &mut state.buffer_list {
+        buffer.marks.remove(&mark);
+    }
+    state.buffer_list[current].marks.insert(mark, position);
+    state.global_marks.insert(mark, GlobalMark { path, row: position.0, col: position.1 });
+    save_session(state);
+    Ok(())
+}
+
+pub fn jump_to_mark(state: &mut State, mark: char) -> Result<(), ErrorType> {
+    if !is_mark(mark) {
+        return Err(ErrorType::InvalidMark);
+    }
+    if !is_global(mark) {
+        let current = state.current_buffer;
+        let buffer = state.buffer_list.get_mut(current).ok_or(ErrorType::NoBuffer)?;
+        let &(row, col) = buffer.marks.get(&mark).ok_or(ErrorType::MarkNotSet)?;
+        move_to(buffer, row, col);
+        return Ok(());
+    }
+
+    sync_global_marks(state);
+    let global = state.global_marks.get(&mark).ok_or(ErrorType::MarkNotSet)?.clone();
+    match state.buffer_list.iter().position(|buffer| buffer_path(buffer).as_ref() == Some(&global.path)) {
+        Some(index) => state.current_buffer = index,
+        None => {
+            if !global.path.is_file() {
+                return Err(ErrorType::FileNotFound);
+            }
+            let mut buffer = Buffer::with_config(&state.config);
+            buffer.init(&global.path.to_string_lossy()).map_err(|_| ErrorType::FileNotFound)?;
+            state.push_buffer(buffer);
+        }
+    }
+    let buffer = &mut state.buffer_list[state.current_buffer];
+    buffer.marks.insert(mark, (global.row, global.col));
+    move_to(buffer, global.row, global.col);
+    Ok(())
+}
+
+fn move_to(buffer: &mut Buffer, row: usize, col: usize) {
+    buffer.input.cancel_selection();
+    buffer.move_cursor_to(row, col);
+    buffer.viewport.center_on(row);
+}
+
+pub fn delete_mark(state: &mut State, mark: char) {
+    if is_global(mark) {
+        for buffer in &mut state.buffer_list {
+            buffer.marks.remove(&mark);
+        }
+        state.global_marks.remove(&mark);
+        save_session(state);
+    } else if let Some(buffer) = state.buffer_list.get_mut(state.current_buffer) {
+        buffer.marks.remove(&mark);
+    }
+}
+
+// The marks of the current buffer followed by the global marks.
+pub fn mark_list(state: &mut State) -> Vec<MarkEntry> {
+    sync_global_marks(state);
+    let Some(buffer) = state.buffer_list.get(state.current_buffer) else {
+        return Vec::new();
+    };
+    let path = buffer_path(buffer);
+    let line = |row: usize| buffer.input.lines().get(row).map(|line| line.trim().to_string()).unwrap_or_default();
+
+    let local = buffer.marks.iter().filter(|(mark, _)| !is_global(**mark)).map(|(&mark, &(row, col))| MarkEntry { mark, row, col, text: line(row) });
+    let global = state.global_marks.iter().map(|(&mark, global)| {
+        let text = if path.as_ref() == Some(&global.path) { line(global.row) } else { global.path.display().to_string() };
+        MarkEntry { mark, row: global.row, col: global.col, text }
+    });
+    local.chain(global).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use tui_textarea::TextArea;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_shift_row() {
+        // A line inserted above the mark.
+        assert_eq!(shift_row(5, 2, &lines(&["a", "b"]), &lines(&["a", "new", "b"])), Some(6));
+        assert_eq!(shift_row(1, 2, &lines(&["a"]), &lines(&[])), Some(1));
+        // Splitting the marked line keeps the mark on its first half.
+        assert_eq!(shift_row(3, 2, &lines(&["a", "bc", "d"]), &lines(&["a", "b", "c", "d"])), Some(3));
+        // Deleting it removes the mark.
+        assert_eq!(shift_row(3, 2, &lines(&["a", "b", "c"]), &lines(&["a", "c"])), None);
+        // Moving it down takes the mark along.
+        assert_eq!(shift_row(3, 2, &lines(&["a", "b", "c", "d"]), &lines(&["a", "c", "b", "d"])), Some(4));
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("session.toml");
+        let marks = BTreeMap::from([('A', GlobalMark { path: PathBuf::from("/tmp/\"a\".txt"), row: 3, col: 1 })]);
+        write_session(&path, &marks).unwrap();
+        assert_eq!(load_session(&path), marks);
+
+        fs::write(&path, "[marks.a]\npath = \"x\"\nrow = 1\ncol = 0\n[marks.B]\nrow = -1\n").unwrap();
+        assert!(load_session(&path).is_empty());
+        assert!(load_session(&dir.path().join("missing.toml")).is_empty());
+    }
+
+    #[test]
+    fn test_set_and_jump_to_local_mark() {
+        let mut state = State::default();
+        state.push_buffer(Buffer::new(TextArea::new(lines(&["one", "two", "three"])), None));
+        state.buffer_list[0].move_cursor_to(1, 2);
+        set_mark(&mut state, 'a').unwrap();
+        assert_eq!(set_mark(&mut state, 'A'), Err(ErrorType::NoFileForMark));
+        assert_eq!(jump_to_mark(&mut state, 'b'), Err(ErrorType::MarkNotSet));
+        assert_eq!(set_mark(&mut state, '1'), Err(ErrorType::InvalidMark));
+
+        state.buffer_list[0].move_cursor_to(0, 0);
+        jump_to_mark(&mut state, 'a').unwrap();
+        assert_eq!(state.buffer_list[0].input.cursor(), (1, 2));
+        assert_eq!(mark_list(&mut state), vec![MarkEntry { mark: 'a', row: 1, col: 2, text: "two".to_string() }]);
+    }
+
+    #[test]
+    fn test_global_mark_opens_its_file() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "one\ntwo\n").unwrap();
+        let mut state = State { session_file: Some(dir.path().join("session.toml")), ..State::default() };
+        let mut buffer = Buffer::default();
+        buffer.init(file.to_str().unwrap()).unwrap();
+        state.push_buffer(buffer);
+        state.buffer_list[0].move_cursor_to(1, 0);
+        set_mark(&mut state, 'F').unwrap();
+        assert_eq!(load_session(&dir.path().join("session.toml"))[&'F'].row, 1);
+
+        state.buffer_list.clear();
+        state.push_buffer(Buffer::new(TextArea::default(), None));
+        jump_to_mark(&mut state, 'F').unwrap();
+        assert_eq!(state.current_buffer, 1);
+        assert_eq!(state.buffer_list[1].input.cursor(), (1, 0));
+        assert_eq!(state.buffer_list[1].marks[&'F'], (1, 0));
+    }
+}
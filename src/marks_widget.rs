@@ -0,0 +1,149 @@
+use crate::action_bar::ActionWidget;
+use crate::error_type::ErrorType;
+use crate::marks::{delete_mark, jump_to_mark, mark_list, MarkEntry};
+use crate::popup::popup_area;
+use crate::prompt::render_message;
+use crate::state::State;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::widgets::{Block, Borders, Clear, HighlightSpacing, List, ListItem, ListState, StatefulWidget, Widget};
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+const SELECT_STYLE: Style = Style::new().bg(Color::White).fg(Color::Black);
+const MAX_VISIBLE: u16 = 12;
+
+// Lists the marks of the current buffer and the global marks. Enter jumps, Delete removes.
+#[derive(Debug)]
+pub struct MarksWidget<'a> {
+    pub state: Rc<RefCell<State<'a>>>,
+    pub list_state: ListState,
+    pub items: Vec<MarkEntry>,
+    pub error: ErrorType,
+}
+
+impl<'a> MarksWidget<'a> {
+    pub fn new(state: Rc<RefCell<State<'a>>>) -> MarksWidget<'a> {
+        MarksWidget { state, list_state: ListState::default(), items: Vec::new(), error: ErrorType::NONE }
+    }
+
+    pub fn refresh_list(&mut self) {
+        self.items = mark_list(&mut self.state.borrow_mut());
+        if self.list_state.selected().is_some_and(|index| index >= self.items.len()) {
+            self.list_state.select(self.items.len().checked_sub(1));
+        }
+    }
+
+    fn render_content(&self, area: Rect, buf: &mut Buffer) {
+        let height = (self.items.len() as u16).clamp(1, MAX_VISIBLE) + 2;
+        let area = popup_area(area, 60, height);
+        Clear.render(area, buf);
+
+        if self.error != ErrorType::NONE {
+            render_message(&self.error.to_string(), popup_area(area, 60, 3), buf);
+            return;
+        }
+
+        let block = Block::default()
+            .bold()
+            .title("Marks (Del: remove)")
+            .borders(Borders::ALL);
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .map(|entry| ListItem::new(format!("{} {:>9} {}", entry.mark, format!("{}:{}", entry.row + 1, entry.col + 1), entry.text)))
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECT_STYLE)
+            .highlight_symbol(">> ")
+            .highlight_spacing(HighlightSpacing::Always);
+
+        StatefulWidget::render(list, area, buf, &mut self.list_state.clone());
+    }
+}
+
+impl ActionWidget for MarksWidget<'_> {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.render_content(area, buf);
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
+        if self.error != ErrorType::NONE {
+            self.error = ErrorType::NONE;
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Up => self.list_state.select_previous(),
+            KeyCode::Down => self.list_state.select_next(),
+            KeyCode::Home => self.list_state.select_first(),
+            KeyCode::End => self.list_state.select(self.items.len().checked_sub(1)),
+            KeyCode::Delete => {
+                if let Some(entry) = self.list_state.selected().and_then(|index| self.items.get(index)) {
+                    delete_mark(&mut self.state.borrow_mut(), entry.mark);
+                    self.refresh_list();
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn has_error(&self) -> bool {
+        self.error != ErrorType::NONE
+    }
+
+    fn process_action(&mut self) -> Result<bool, io::Error> {
+        if self.items.is_empty() {
+            self.error = ErrorType::NoMarks;
+            return Ok(false);
+        }
+        let Some(entry) = self.list_state.selected().and_then(|index| self.items.get(index)) else {
+            return Ok(false);
+        };
+        if let Err(error) = jump_to_mark(&mut self.state.borrow_mut(), entry.mark) {
+            self.error = error;
+        }
+        Ok(self.error == ErrorType::NONE)
+    }
+
+    fn init_action(&mut self) {
+        self.refresh_list();
+    }
+
+    fn reset(&mut self) {
+        self.error = ErrorType::NONE;
+        self.list_state.select(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{buffer, state_with};
+
+    #[test]
+    fn test_jump_to_and_delete_marks() {
+        let mut buffer = buffer(&["one", "two"]);
+        buffer.marks.insert('a', (1, 0));
+        buffer.marks.insert('b', (0, 2));
+
+        let mut widget = MarksWidget::new(state_with(buffer));
+        widget.init_action();
+        widget.handle_input(KeyEvent::from(KeyCode::Down)).unwrap();
+        assert!(widget.process_action().unwrap());
+        assert_eq!(widget.state.borrow().buffer_list[0].input.cursor(), (1, 0));
+
+        widget.handle_input(KeyEvent::from(KeyCode::Delete)).unwrap();
+        widget.handle_input(KeyEvent::from(KeyCode::Delete)).unwrap();
+        assert!(widget.state.borrow().buffer_list[0].marks.is_empty());
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.error, ErrorType::NoMarks);
+    }
+}
@@ -1,104 +1,77 @@
 use crate::action_bar::ActionWidget;
 use crate::app::CurrentScreen;
 use crate::error_type::ErrorType;
-use crate::popup::popup_area;
+use crate::prompt::Prompt;
 use crate::state::State;
-use crate::text_area_popup_widget::text_area_popup;
 use crossterm::event::KeyEvent;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::Stylize;
-use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+use ratatui::widgets::Widget;
 use std::cell::RefCell;
 use std::io;
-use std::ops::Deref;
 use std::path::PathBuf;
 use std::rc::Rc;
-use tui_textarea::{CursorMove, TextArea};
 
 const POPUP_TITLE: &str = "Filename";
 
 #[derive(Debug)]
 pub struct NewFileWidget<'a> {
     pub state: Rc<RefCell<State<'a>>>,
-    pub input: TextArea<'a>,
-    pub error: ErrorType,
+    pub prompt: Prompt<'a>,
 }
 
 impl<'a> NewFileWidget<'a> {
     pub fn new(state: Rc<RefCell<State<'a>>>) -> NewFileWidget<'a> {
-        NewFileWidget { state,  input: text_area_popup(POPUP_TITLE), error: ErrorType::NONE }
+        NewFileWidget { state,  prompt: Prompt::new(POPUP_TITLE) }
     }
 
     pub fn create_new_file(&mut self) -> io::Result<()> {
         let mut state = self.state.borrow_mut();
-        let mut buffer = crate::buffer::Buffer::default();
-        let path = self.input.lines().first().unwrap();
+        let mut buffer = crate::buffer::Buffer::with_config(&state.config);
+        let path = self.prompt.text();
 
         if PathBuf::from(path).is_file() {
-            self.error = ErrorType::FileExists;
+            self.prompt.error = ErrorType::FileExists;
             return Ok(());
         }
 
-        buffer.init(path.deref())?;
+        buffer.init(path)?;
         state.push_buffer(buffer);
         state.current_screen = CurrentScreen::Editor;
-        self.input.move_cursor(CursorMove::Head);
-        self.input.delete_line_by_end();
+        self.prompt.clear();
         Ok(())
     }
-
-    fn render_content(&self, area: Rect, buf: &mut Buffer) {
-        let pop_up_area = popup_area(area, 50, 3);
-        Clear.render(pop_up_area, buf);
-        if self.error == ErrorType::NONE {
-            self.input.render(pop_up_area, buf);
-        } else {
-            let block = Block::default().borders(Borders::ALL);
-            let text = Paragraph::new(self.error.to_string())
-                .block(block)
-                .centered()
-                .bold();
-            text.render(pop_up_area, buf);
-        }
-    }
 }
 
 impl ActionWidget for NewFileWidget<'_> {
     fn render(&self, area: Rect, buf: &mut Buffer) {
-        self.render_content(area, buf);
+        self.prompt.render(area, 50, buf);
     }
 
     fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
-        if self.error != ErrorType::NONE {
-            self.error = ErrorType::NONE;
-        } else {
-            self.input.input(key);
-        }
+        self.prompt.handle_input(key);
         Ok(())
     }
 
     fn has_error(&self) -> bool {
-        self.error != ErrorType::NONE
+        self.prompt.has_error()
     }
 
     fn process_action(&mut self) -> Result<bool, io::Error> {
         self.create_new_file()?;
-        Ok(self.error == ErrorType::NONE)
+        Ok(!self.prompt.has_error())
     }
 
     fn init_action(&mut self) {}
 
     fn reset(&mut self) {
-        self.error = ErrorType::NONE;
-        self.input.move_cursor(CursorMove::Head);
-        self.input.delete_line_by_end();
+        self.prompt.reset();
     }
 }
 
 impl Widget for &NewFileWidget<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        self.render_content(area, buf);
+        self.prompt.render(area, 50, buf);
     }
 }
 
@@ -110,11 +83,12 @@ mod tests {
     use std::fs;
     use std::path::Path;
     use std::rc::Rc;
+    use crate::popup::popup_area;
 
     fn create_widget_with_input(input: &str) -> NewFileWidget<'static> {
         let state = Rc::new(RefCell::new(State::default()));
         let mut widget = NewFileWidget::new(Rc::clone(&state));
-        widget.input.insert_str(input);
+        widget.prompt.input.insert_str(input);
         widget
     }
 
@@ -129,7 +103,7 @@ mod tests {
         let result = widget.create_new_file();
 
         assert!(result.is_ok());
-        assert_eq!(widget.error, ErrorType::NONE);
+        assert_eq!(widget.prompt.error, ErrorType::NONE);
         assert!(Path::new(file_path).exists());
 
         fs::remove_file(file_path).unwrap();
@@ -144,7 +118,7 @@ mod tests {
         let result = widget.create_new_file();
 
         assert!(result.is_ok()); // because error is handled gracefully
-        assert_eq!(widget.error, ErrorType::FileExists);
+        assert_eq!(widget.prompt.error, ErrorType::FileExists);
 
         fs::remove_file(file_path).unwrap();
     }
@@ -152,7 +126,7 @@ mod tests {
     #[test]
     fn test_popup_area_is_centered() {
         let area = Rect::new(0, 0, 100, 30);
-        let popup = super::popup_area(area, 50, 3);
+        let popup = popup_area(area, 50, 3);
         assert_eq!(popup.width, 50);
         assert_eq!(popup.height, 3);
         assert!(popup.x > 0);
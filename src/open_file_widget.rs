@@ -1,107 +1,147 @@
 use crate::app::CurrentScreen;
 use crate::error_type::ErrorType;
+use crate::prompt::Prompt;
 use crate::state::State;
-use crate::text_area_popup_widget::text_area_popup;
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Rect};
-use ratatui::style::Stylize;
-use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+use ratatui::widgets::Widget;
 use std::cell::RefCell;
+use std::fmt;
+use std::fs;
 use std::io;
-use std::ops::Deref;
 use std::path::PathBuf;
 use std::rc::Rc;
 use crossterm::event::KeyEvent;
 use log::error;
-use tui_textarea::{CursorMove, TextArea};
 use crate::action_bar::ActionWidget;
-use crate::popup::{popup_area};
 
 const POPUP_TITLE: &str = "Filename";
 
-#[derive(Debug)]
+pub type PathActionFn<'a> = Box<dyn Fn(&mut State<'a>, &str) -> Result<ErrorType, io::Error> + 'a>;
+
 pub struct OpenFileWidget<'a> {
     pub state: Rc<RefCell<State<'a>>>,
-    pub input: TextArea<'a>,
-    pub error: ErrorType,
+    pub prompt: Prompt<'a>,
+    pub path_fn: PathActionFn<'a>,
+}
+
+impl fmt::Debug for OpenFileWidget<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpenFileWidget")
+            .field("state", &self.state)
+            .field("prompt", &self.prompt)
+            .field("path_fn", &"<function>")
+            .finish()
+    }
 }
 
 impl<'a> OpenFileWidget<'a> {
     pub fn new(state: Rc<RefCell<State<'a>>>) -> OpenFileWidget<'a> {
-        OpenFileWidget { state,  input: text_area_popup(POPUP_TITLE), error: ErrorType::NONE }
+        let open_fn: PathActionFn<'a> = Box::new(|state, path| {
+            if !PathBuf::from(path).is_file() {
+                error!("File not found: {}", path);
+                return Ok(ErrorType::FileNotFound);
+            }
+
+            let mut buffer = crate::buffer::Buffer::with_config(&state.config);
+            buffer.init(path)?;
+            state.push_buffer(buffer);
+            state.current_screen = CurrentScreen::Editor;
+            Ok(ErrorType::NONE)
+        });
+
+        OpenFileWidget::with_action(state, POPUP_TITLE, open_fn)
     }
 
-    pub fn open_file(&mut self) -> io::Result<()> {
-        let mut state = self.state.borrow_mut();
-        let mut buffer = crate::buffer::Buffer::default();
-        let path = self.input.lines().first().unwrap();
+    pub fn with_action(state: Rc<RefCell<State<'a>>>, title: &'a str, path_fn: PathActionFn<'a>) -> OpenFileWidget<'a> {
+        OpenFileWidget { state, prompt: Prompt::new(title), path_fn }
+    }
 
-        if !PathBuf::from(path).is_file() {
-            self.error = ErrorType::FileNotFound;
-            error!("File not found: {}", path);
-            return Ok(());
-        }
+    pub fn for_insertion(state: Rc<RefCell<State<'a>>>) -> OpenFileWidget<'a> {
+        let insert_fn: PathActionFn<'a> = Box::new(|state, path| {
+            if !PathBuf::from(path).is_file() {
+                return Ok(ErrorType::FileNotFound);
+            }
+            let content = fs::read_to_string(path)?;
+
+            let index = state.current_buffer;
+            let Some(buffer) = state.buffer_list.get_mut(index) else {
+                return Ok(ErrorType::NoBuffer);
+            };
+            buffer.paste(&content);
+            state.current_screen = CurrentScreen::Editor;
+            Ok(ErrorType::NONE)
+        });
+
+        OpenFileWidget::with_action(state, "Insert file", insert_fn)
+    }
 
-        buffer.init(path.deref())?;
-        state.push_buffer(buffer);
-        state.current_screen = CurrentScreen::Editor;
-        self.input.move_cursor(CursorMove::Head);
-        self.input.delete_line_by_end();
-        Ok(())
+    pub fn for_writing_selection(state: Rc<RefCell<State<'a>>>) -> OpenFileWidget<'a> {
+        let write_fn: PathActionFn<'a> = Box::new(|state, path| {
+            let Some(buffer) = state.buffer_list.get(state.current_buffer) else {
+                return Ok(ErrorType::NoBuffer);
+            };
+            let Some(text) = buffer.selected_text() else {
+                return Ok(ErrorType::NoSelection);
+            };
+            if PathBuf::from(path).exists() {
+                return Ok(ErrorType::FileExists);
+            }
+
+            fs::write(path, text)?;
+            Ok(ErrorType::NONE)
+        });
+
+        OpenFileWidget::with_action(state, "Write selection to file", write_fn)
     }
 
-    fn render_content(&self, area: Rect, buf: &mut Buffer) {
-        let pop_up_area = popup_area(area, 50, 3);
-        Clear.render(pop_up_area, buf);
-        if self.error == ErrorType::NONE {
-            self.input.render(pop_up_area, buf);
-        } else {
-            let block = Block::default().borders(Borders::ALL);
-            let text = Paragraph::new(self.error.to_string())
-                .block(block)
-                .centered()
-                .bold();
-            text.render(pop_up_area, buf);
+    // Errors from the file system are shown in the popup, so a mistyped or unreadable
+    // path never closes the editor.
+    pub fn open_file(&mut self) -> io::Result<()> {
+        let mut state = self.state.borrow_mut();
+        let path = self.prompt.text().to_string();
+
+        self.prompt.error = (self.path_fn)(&mut state, &path).unwrap_or_else(|error| {
+            error!("{}: {}", path, error);
+            ErrorType::Io(error.to_string())
+        });
+        if !self.prompt.has_error() {
+            self.prompt.clear();
         }
+        Ok(())
     }
 }
 
 impl ActionWidget for OpenFileWidget<'_> {
     fn render(&self, area: Rect, buf: &mut Buffer) {
-        self.render_content(area, buf);
+        self.prompt.render(area, 50, buf);
     }
 
     fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
-        if self.error != ErrorType::NONE {
-            self.error = ErrorType::NONE;
-        } else {
-            self.input.input(key);
-        }
+        self.prompt.handle_input(key);
         Ok(())
     }
 
     fn has_error(&self) -> bool {
-        self.error != ErrorType::NONE
+        self.prompt.has_error()
     }
 
     fn process_action(&mut self) -> Result<bool, io::Error> {
         self.open_file()?;
-        Ok(self.error == ErrorType::NONE)
+        Ok(!self.prompt.has_error())
     }
 
     fn init_action(&mut self) {}
 
     fn reset(&mut self) {
-        self.error = ErrorType::NONE;
-        self.input.move_cursor(CursorMove::Head);
-        self.input.delete_line_by_end();
+        self.prompt.reset();
     }
 }
 
 impl Widget for &OpenFileWidget<'_> {
     fn render(self, area: Rect, buf: &mut Buffer)
     {
-        self.render_content(area, buf);
+        self.prompt.render(area, 50, buf);
     }
 }
 
@@ -113,11 +153,13 @@ mod tests {
     use std::fs;
     use std::path::Path;
     use std::rc::Rc;
+    use crate::popup::popup_area;
+    use crate::test_support::state_with_buffer;
 
     fn create_widget_with_input(input: &str) -> OpenFileWidget<'static> {
         let state = Rc::new(RefCell::new(State::default()));
         let mut widget = OpenFileWidget::new(Rc::clone(&state));
-        widget.input.insert_str(input);
+        widget.prompt.input.insert_str(input);
         widget
     }
 
@@ -130,7 +172,7 @@ mod tests {
         let result = widget.open_file();
 
         assert!(result.is_ok());
-        assert_eq!(widget.error, ErrorType::NONE);
+        assert_eq!(widget.prompt.error, ErrorType::NONE);
         assert!(Path::new(file_path).exists());
         fs::remove_file(file_path).unwrap();
     }
@@ -146,7 +188,59 @@ mod tests {
         let result = widget.open_file();
 
         assert!(result.is_ok());
-        assert_eq!(widget.error, ErrorType::FileNotFound);
+        assert_eq!(widget.prompt.error, ErrorType::FileNotFound);
+    }
+
+    #[test]
+    fn test_insert_file_at_cursor() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"one\ntwo\n").unwrap();
+
+        let state = state_with_buffer(&["ab"]);
+        state.borrow_mut().buffer_list[0].move_cursor_to(0, 1);
+        let mut widget = OpenFileWidget::for_insertion(Rc::clone(&state));
+        widget.prompt.input.insert_str(file.path().to_str().unwrap());
+
+        assert!(widget.process_action().unwrap());
+        assert_eq!(state.borrow().buffer_list.len(), 1);
+        assert_eq!(state.borrow().buffer_list[0].input.lines(), vec!["aone", "two", "b"]);
+    }
+
+    #[test]
+    fn test_insert_missing_file() {
+        let state = state_with_buffer(&["ab"]);
+        let mut widget = OpenFileWidget::for_insertion(state);
+        widget.prompt.input.insert_str("non_existing_file.txt");
+
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.prompt.error, ErrorType::FileNotFound);
+    }
+
+    #[test]
+    fn test_write_selection_to_new_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("selection.txt");
+        let state = state_with_buffer(&["one", "two"]);
+        let mut widget = OpenFileWidget::for_writing_selection(Rc::clone(&state));
+        widget.prompt.input.insert_str(path.to_str().unwrap());
+
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.prompt.error, ErrorType::NoSelection);
+        widget.reset();
+
+        {
+            let mut state = state.borrow_mut();
+            let buffer = &mut state.buffer_list[0];
+            buffer.input.start_selection();
+            buffer.move_cursor_to(1, 1);
+        }
+        widget.prompt.input.insert_str(path.to_str().unwrap());
+        assert!(widget.process_action().unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\nt");
+
+        widget.prompt.input.insert_str(path.to_str().unwrap());
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.prompt.error, ErrorType::FileExists);
     }
 
     #[test]
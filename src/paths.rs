@@ -0,0 +1,13 @@
+use std::env;
+use std::path::PathBuf;
+
+const APP_DIR: &str = "divitext";
+
+// $XDG_CONFIG_HOME/divitext, falling back to ~/.config/divitext.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join(APP_DIR))
+}
@@ -0,0 +1,157 @@
+use std::io;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Commands still running after this are killed, so one waiting forever cannot hang the editor.
+pub const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+// Output past this many bytes is dropped and the command counts as failed.
+const MAX_OUTPUT: usize = 16 * 1024 * 1024;
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, PartialEq)]
+pub struct CommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub code: Option<i32>,
+    pub timed_out: bool,
+    pub truncated: bool,
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        self.code == Some(0) && !self.timed_out && !self.truncated
+    }
+
+    // Message for the status bar, if the command failed or wrote to stderr.
+    pub fn error_message(&self) -> Option<String> {
+        if self.timed_out {
+            return Some("Command timed out and was killed".to_string());
+        }
+        if self.truncated {
+            return Some(format!("Command output is larger than {} MB", MAX_OUTPUT / (1024 * 1024)));
+        }
+        let stderr = self.stderr.lines().find(|line| !line.trim().is_empty());
+        match (self.success(), stderr) {
+            (true, None) => None,
+            (true, Some(stderr)) => Some(stderr.to_string()),
+            (false, stderr) => {
+                let status = match self.code {
+                    Some(code) => format!("Command exited with status {}", code),
+                    None => "Command was terminated".to_string(),
+                };
+                Some(match stderr {
+                    Some(stderr) => format!("{}: {}", status, stderr),
+                    None => status,
+                })
+            }
+        }
+    }
+}
+
+// Reads at most MAX_OUTPUT bytes and drains the rest, so the command never blocks on a full pipe.
+fn read_capped(mut reader: impl Read) -> (Vec<u8>, bool) {
+    let mut bytes = Vec::new();
+    let _ = reader.by_ref().take(MAX_OUTPUT as u64).read_to_end(&mut bytes);
+    let dropped = io::copy(&mut reader, &mut io::sink()).unwrap_or(0);
+    (bytes, dropped > 0)
+}
+
+// Runs `command` through the shell with `input` on stdin, killing it after `timeout`.
+pub fn run_command(command: &str, input: &str, timeout: Duration) -> io::Result<CommandOutput> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Written and read from other threads so a command producing a lot of output cannot block on us.
+    let mut stdin = child.stdin.take().ok_or(io::ErrorKind::BrokenPipe)?;
+    let input = input.to_string();
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let stdout = child.stdout.take().ok_or(io::ErrorKind::BrokenPipe)?;
+    let stdout = thread::spawn(move || read_capped(stdout));
+    let stderr = child.stderr.take().ok_or(io::ErrorKind::BrokenPipe)?;
+    let stderr = thread::spawn(move || read_capped(stderr));
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            // The threads are left behind: processes started by the command may still hold the pipes.
+            let _ = child.kill();
+            child.wait()?;
+            return Ok(CommandOutput { stdout: String::new(), stderr: String::new(), code: None, timed_out: true, truncated: false });
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    // Commands like `head` may exit before reading all of their input.
+    let _ = writer.join();
+    let (stdout, stdout_truncated) = stdout.join().unwrap_or_default();
+    let (stderr, stderr_truncated) = stderr.join().unwrap_or_default();
+
+    Ok(CommandOutput {
+        stdout: String::from_utf8_lossy(&stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        code: status.code(),
+        timed_out: false,
+        truncated: stdout_truncated || stderr_truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_command_with_input() {
+        let output = run_command("tr a-z A-Z", "hello\n", COMMAND_TIMEOUT).unwrap();
+        assert_eq!(output.stdout, "HELLO\n");
+        assert!(output.success());
+        assert_eq!(output.error_message(), None);
+    }
+
+    #[test]
+    fn test_run_command_failure() {
+        let output = run_command("echo oops >&2; exit 3", "", COMMAND_TIMEOUT).unwrap();
+        assert!(!output.success());
+        assert_eq!(output.error_message(), Some("Command exited with status 3: oops".to_string()));
+    }
+
+    #[test]
+    fn test_stderr_on_success_is_reported() {
+        let output = run_command("cat; echo warning >&2", "a", COMMAND_TIMEOUT).unwrap();
+        assert_eq!(output.stdout, "a");
+        assert_eq!(output.error_message(), Some("warning".to_string()));
+    }
+
+    #[test]
+    fn test_command_not_reading_input() {
+        let input = "line\n".repeat(100_000);
+        let output = run_command("head -n 1", &input, COMMAND_TIMEOUT).unwrap();
+        assert_eq!(output.stdout, "line\n");
+    }
+
+    #[test]
+    fn test_command_is_killed_after_timeout() {
+        let start = Instant::now();
+        let output = run_command("sleep 5", "", Duration::from_millis(100)).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(output.timed_out);
+        assert!(!output.success());
+        assert_eq!(output.error_message(), Some("Command timed out and was killed".to_string()));
+    }
+
+    #[test]
+    fn test_large_output_is_capped() {
+        let output = run_command(&format!("head -c {} /dev/zero", MAX_OUTPUT + 1), "", COMMAND_TIMEOUT).unwrap();
+        assert_eq!(output.stdout.len(), MAX_OUTPUT);
+        assert!(output.truncated);
+        assert_eq!(output.error_message(), Some("Command output is larger than 16 MB".to_string()));
+    }
+}
@@ -0,0 +1,220 @@
+use crate::action_bar::ActionWidget;
+use crate::error_type::ErrorType;
+use crate::pipe::{run_command, COMMAND_TIMEOUT};
+use crate::prompt::Prompt;
+use crate::state::State;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::{Block, Borders};
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PipeTarget {
+    Replace,
+    Insert,
+    NewBuffer,
+}
+
+impl PipeTarget {
+    fn next(self) -> PipeTarget {
+        match self {
+            PipeTarget::Replace => PipeTarget::Insert,
+            PipeTarget::Insert => PipeTarget::NewBuffer,
+            PipeTarget::NewBuffer => PipeTarget::Replace,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            PipeTarget::Replace => "Pipe through command, replace text (Tab: change)",
+            PipeTarget::Insert => "Pipe through command, insert at cursor (Tab: change)",
+            PipeTarget::NewBuffer => "Pipe through command, open in new buffer (Tab: change)",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PipeWidget<'a> {
+    pub state: Rc<RefCell<State<'a>>>,
+    pub prompt: Prompt<'a>,
+    pub target: PipeTarget,
+}
+
+impl<'a> PipeWidget<'a> {
+    pub fn new(state: Rc<RefCell<State<'a>>>) -> PipeWidget<'a> {
+        let target = PipeTarget::Replace;
+        PipeWidget { state, prompt: Prompt::new(target.title()), target }
+    }
+
+    fn set_target(&mut self, target: PipeTarget) {
+        self.target = target;
+        self.prompt.input.set_block(Block::default().borders(Borders::ALL).title(target.title()));
+    }
+
+    pub fn pipe(&mut self) {
+        let command = self.prompt.text().trim().to_string();
+        if command.is_empty() {
+            self.prompt.error = ErrorType::EmptyCommand;
+            return;
+        }
+
+        let mut state = self.state.borrow_mut();
+        let index = state.current_buffer;
+        let Some(buffer) = state.buffer_list.get_mut(index) else {
+            self.prompt.error = ErrorType::NoBuffer;
+            return;
+        };
+
+        let mut input = buffer.selected_text().unwrap_or_else(|| buffer.input.lines().join("\n"));
+        let added_newline = !input.ends_with('\n');
+        if added_newline {
+            input.push('\n');
+        }
+
+        let output = match run_command(&command, &input, COMMAND_TIMEOUT) {
+            Ok(output) => output,
+            Err(error) => {
+                state.status_error = Some(format!("Cannot run command: {}", error));
+                return;
+            }
+        };
+        state.status_error = output.error_message();
+        if !output.success() {
+            return;
+        }
+
+        let mut text = output.stdout;
+        if added_newline && text.ends_with('\n') {
+            text.pop();
+        }
+
+        let buffer = &mut state.buffer_list[index];
+        match self.target {
+            PipeTarget::Replace => buffer.replace_selection_or_all(&text),
+            PipeTarget::Insert => buffer.paste(&text),
+            PipeTarget::NewBuffer => {
+                let lines = text.split('\n').map(String::from).collect();
+                let scratch = crate::buffer::Buffer::new(buffer.custom_text_area(lines), None);
+                state.push_buffer(scratch);
+            }
+        }
+        self.prompt.clear();
+    }
+}
+
+impl ActionWidget for PipeWidget<'_> {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.prompt.render(area, 60, buf);
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
+        if key.code == KeyCode::Tab && !self.prompt.has_error() {
+            self.set_target(self.target.next());
+        } else {
+            self.prompt.handle_input(key);
+        }
+        Ok(())
+    }
+
+    fn has_error(&self) -> bool {
+        self.prompt.has_error()
+    }
+
+    fn process_action(&mut self) -> Result<bool, io::Error> {
+        self.pipe();
+        Ok(!self.prompt.has_error())
+    }
+
+    fn init_action(&mut self) {}
+
+    fn reset(&mut self) {
+        self.prompt.error = ErrorType::NONE;
+        self.set_target(PipeTarget::Replace);
+        self.prompt.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{buffer, state_with};
+    use crossterm::event::KeyModifiers;
+    use tui_textarea::CursorMove;
+
+    fn create_widget(lines: &[&str], command: &str) -> PipeWidget<'static> {
+        let mut buffer = buffer(lines);
+        buffer.filename = Some("file.txt".to_string());
+        let state = state_with(buffer);
+        let mut widget = PipeWidget::new(state);
+        widget.prompt.input.insert_str(command);
+        widget
+    }
+
+    #[test]
+    fn test_replace_whole_buffer() {
+        let mut widget = create_widget(&["b", "a"], "sort");
+        assert!(widget.process_action().unwrap());
+
+        let state = widget.state.borrow();
+        assert_eq!(state.buffer_list[0].input.lines(), vec!["a", "b"]);
+        assert_eq!(state.status_error, None);
+    }
+
+    #[test]
+    fn test_replace_selection() {
+        let mut widget = create_widget(&["keep", "shout"], "tr a-z A-Z");
+        {
+            let mut state = widget.state.borrow_mut();
+            let buffer = &mut state.buffer_list[0];
+            buffer.move_cursor_to(1, 0);
+            buffer.input.start_selection();
+            buffer.input.move_cursor(CursorMove::End);
+        }
+        assert!(widget.process_action().unwrap());
+        assert_eq!(widget.state.borrow().buffer_list[0].input.lines(), vec!["keep", "SHOUT"]);
+    }
+
+    #[test]
+    fn test_insert_at_cursor() {
+        let mut widget = create_widget(&["ab"], "echo x");
+        widget.handle_input(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE)).unwrap();
+        assert_eq!(widget.target, PipeTarget::Insert);
+        widget.state.borrow_mut().buffer_list[0].move_cursor_to(0, 1);
+
+        assert!(widget.process_action().unwrap());
+        assert_eq!(widget.state.borrow().buffer_list[0].input.lines(), vec!["axb"]);
+    }
+
+    #[test]
+    fn test_output_to_new_buffer() {
+        let mut widget = create_widget(&["one"], "cat; echo two");
+        widget.set_target(PipeTarget::NewBuffer);
+
+        assert!(widget.process_action().unwrap());
+        let state = widget.state.borrow();
+        assert_eq!(state.buffer_list.len(), 2);
+        assert_eq!(state.current_buffer, 1);
+        assert_eq!(state.buffer_list[1].input.lines(), vec!["one", "two"]);
+        assert_eq!(state.buffer_list[1].path, None);
+    }
+
+    #[test]
+    fn test_failing_command_reports_error_and_keeps_text() {
+        let mut widget = create_widget(&["text"], "echo bad >&2; exit 1");
+        assert!(widget.process_action().unwrap());
+
+        let state = widget.state.borrow();
+        assert_eq!(state.buffer_list[0].input.lines(), vec!["text"]);
+        assert_eq!(state.status_error, Some("Command exited with status 1: bad".to_string()));
+    }
+
+    #[test]
+    fn test_empty_command() {
+        let mut widget = create_widget(&["text"], "  ");
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.prompt.error, ErrorType::EmptyCommand);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::error_type::ErrorType;
+use crate::popup::popup_area;
+use crate::text_area_popup_widget::text_area_popup;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+use tui_textarea::{CursorMove, TextArea};
+
+// A bordered message filling `area`, used for the errors of every action widget.
+pub fn render_message(message: &str, area: Rect, buf: &mut Buffer) {
+    Clear.render(area, buf);
+    let block = Block::default().borders(Borders::ALL);
+    Paragraph::new(message).block(block).centered().bold().render(area, buf);
+}
+
+// The one-line input of the action widgets that ask for text. An error replaces
+// the input until the next key.
+#[derive(Debug)]
+pub struct Prompt<'a> {
+    pub input: TextArea<'a>,
+    pub error: ErrorType,
+}
+
+impl<'a> Prompt<'a> {
+    pub fn new(title: &'a str) -> Prompt<'a> {
+        Prompt { input: text_area_popup(title), error: ErrorType::NONE }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.input.lines()[0]
+    }
+
+    pub fn has_error(&self) -> bool {
+        self.error != ErrorType::NONE
+    }
+
+    // The key that dismisses an error is not typed.
+    pub fn handle_input(&mut self, key: KeyEvent) {
+        if self.has_error() {
+            self.error = ErrorType::NONE;
+        } else {
+            self.input.input(key);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.input.move_cursor(CursorMove::Head);
+        self.input.delete_line_by_end();
+    }
+
+    pub fn reset(&mut self) {
+        self.error = ErrorType::NONE;
+        self.clear();
+    }
+
+    // The input, or its error, centered in `area` and at most `width` wide.
+    pub fn render(&self, area: Rect, width: u16, buf: &mut Buffer) {
+        let area = popup_area(area, width, 3);
+        if self.has_error() {
+            render_message(&self.error.to_string(), area, buf);
+        } else {
+            Clear.render(area, buf);
+            self.input.render(area, buf);
+        }
+    }
+}
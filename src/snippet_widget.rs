@@ -0,0 +1,152 @@
+use crate::action_bar::ActionWidget;
+use crate::error_type::ErrorType;
+use crate::popup::popup_area;
+use crate::snippets::Snippet;
+use crate::prompt::render_message;
+use crate::state::State;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::widgets::{Block, Borders, Clear, HighlightSpacing, List, ListItem, ListState, StatefulWidget, Widget};
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+const SELECT_STYLE: Style = Style::new().bg(Color::White).fg(Color::Black);
+const MAX_VISIBLE: u16 = 12;
+
+#[derive(Debug)]
+pub struct SnippetWidget<'a> {
+    pub state: Rc<RefCell<State<'a>>>,
+    pub list_state: ListState,
+    pub items: Vec<Snippet>,
+    pub error: ErrorType,
+}
+
+impl<'a> SnippetWidget<'a> {
+    pub fn new(state: Rc<RefCell<State<'a>>>) -> SnippetWidget<'a> {
+        SnippetWidget { state, list_state: ListState::default(), items: Vec::new(), error: ErrorType::NONE }
+    }
+
+    pub fn refresh_list(&mut self) {
+        let state = self.state.borrow();
+        self.items = state.buffer_list.get(state.current_buffer).map(|buffer| buffer.snippets.clone()).unwrap_or_default();
+    }
+
+    // Inserts the snippet at the cursor, wrapping the selection if there is one.
+    pub fn insert(&mut self, index: usize) {
+        let mut state = self.state.borrow_mut();
+        let current = state.current_buffer;
+        let Some(buffer) = state.buffer_list.get_mut(current) else {
+            self.error = ErrorType::NoBuffer;
+            return;
+        };
+        buffer.insert_snippet(&self.items[index].body, buffer.input.cursor());
+    }
+
+    fn render_content(&self, area: Rect, buf: &mut Buffer) {
+        let height = (self.items.len() as u16).clamp(1, MAX_VISIBLE) + 2;
+        let area = popup_area(area, 60, height);
+        Clear.render(area, buf);
+
+        if self.error != ErrorType::NONE {
+            render_message(&self.error.to_string(), popup_area(area, 60, 3), buf);
+            return;
+        }
+
+        let block = Block::default()
+            .bold()
+            .title("Insert snippet")
+            .borders(Borders::ALL);
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .map(|snippet| ListItem::new(format!("{:<12} {}", snippet.trigger, snippet.body.lines().next().unwrap_or(""))))
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECT_STYLE)
+            .highlight_symbol(">> ")
+            .highlight_spacing(HighlightSpacing::Always);
+
+        StatefulWidget::render(list, area, buf, &mut self.list_state.clone());
+    }
+}
+
+impl ActionWidget for SnippetWidget<'_> {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.render_content(area, buf);
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
+        if self.error != ErrorType::NONE {
+            self.error = ErrorType::NONE;
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Up => self.list_state.select_previous(),
+            KeyCode::Down => self.list_state.select_next(),
+            KeyCode::Home => self.list_state.select_first(),
+            KeyCode::End => self.list_state.select(self.items.len().checked_sub(1)),
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn has_error(&self) -> bool {
+        self.error != ErrorType::NONE
+    }
+
+    fn process_action(&mut self) -> Result<bool, io::Error> {
+        if self.items.is_empty() {
+            self.error = ErrorType::NoSnippets;
+            return Ok(false);
+        }
+        let Some(index) = self.list_state.selected().filter(|&index| index < self.items.len()) else {
+            return Ok(false);
+        };
+        self.insert(index);
+        Ok(self.error == ErrorType::NONE)
+    }
+
+    fn init_action(&mut self) {
+        self.refresh_list();
+    }
+
+    fn reset(&mut self) {
+        self.error = ErrorType::NONE;
+        self.list_state.select(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{buffer, state_with, state_with_buffer};
+
+    #[test]
+    fn test_insert_selected_snippet() {
+        let mut buffer = buffer(&["x"]);
+        buffer.snippets = vec![Snippet { trigger: "b".to_string(), body: "[$SELECTION]".to_string() }];
+        buffer.input.select_all();
+
+        let mut widget = SnippetWidget::new(state_with(buffer));
+        widget.init_action();
+        widget.handle_input(KeyEvent::from(KeyCode::Down)).unwrap();
+        assert!(widget.process_action().unwrap());
+        assert_eq!(widget.state.borrow().buffer_list[0].input.lines(), vec!["[x]"]);
+    }
+
+    #[test]
+    fn test_no_snippets() {
+        let state = state_with_buffer(&[""]);
+        let mut widget = SnippetWidget::new(state);
+        widget.init_action();
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.error, ErrorType::NoSnippets);
+    }
+}
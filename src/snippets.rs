@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Snippets in `all.snippets` are available in every buffer.
+const ALL_FILE_TYPES: &str = "all";
+
+pub type Pos = (usize, usize);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snippet {
+    pub trigger: String,
+    pub body: String,
+}
+
+// Placeholder positions of one tab stop; the first range is edited, the others mirror it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabStop {
+    pub number: usize,
+    pub ranges: Vec<(Pos, Pos)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expansion {
+    pub lines: Vec<String>,
+    pub stops: Vec<TabStop>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnippetSession {
+    pub stops: Vec<TabStop>,
+    pub current: usize,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Stop(usize, Option<String>),
+    Variable(String),
+}
+
+fn finish_snippet(current: Option<(String, Vec<&str>)>, snippets: &mut Vec<Snippet>) {
+    if let Some((trigger, lines)) = current {
+        let body = lines.join("\n").trim_end_matches('\n').to_string();
+        snippets.push(Snippet { trigger, body });
+    }
+}
+
+// SnipMate format: a "snippet <trigger>" line followed by the body, each body line indented by one tab.
+pub fn parse_snippets(content: &str) -> Vec<Snippet> {
+    let mut snippets = Vec::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+    for line in content.lines() {
+        if let Some(trigger) = line.strip_prefix("snippet ") {
+            finish_snippet(current.take(), &mut snippets);
+            current = Some((trigger.trim().to_string(), Vec::new()));
+        } else if let Some((_, lines)) = &mut current {
+            if let Some(text) = line.strip_prefix('\t') {
+                lines.push(text);
+            } else if line.trim().is_empty() {
+                lines.push("");
+            }
+        }
+    }
+    finish_snippet(current, &mut snippets);
+    snippets
+}
+
+pub fn snippets_dir() -> Option<PathBuf> {
+    crate::paths::config_dir().map(|dir| dir.join("snippets"))
+}
+
+// Snippets for the file type first, so they win over the ones for all files.
+pub fn load_snippets(dir: &Path, file_type: Option<&str>) -> Vec<Snippet> {
+    file_type
+        .into_iter()
+        .chain([ALL_FILE_TYPES])
+        .filter_map(|file_type| fs::read_to_string(dir.join(format!("{}.snippets", file_type))).ok())
+        .flat_map(|content| parse_snippets(&content))
+        .collect()
+}
+
+fn read_until(chars: &mut std::iter::Peekable<std::str::Chars>, end: char) -> String {
+    let mut text = String::new();
+    for c in chars.by_ref() {
+        if c == end {
+            break;
+        }
+        text.push(c);
+    }
+    text
+}
+
+fn tokenize(body: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.peek() == Some(&'$') {
+            text.push(chars.next().unwrap());
+            continue;
+        }
+        if c != '$' {
+            text.push(c);
+            continue;
+        }
+
+        let field = match chars.peek() {
+            Some('{') => {
+                chars.next();
+                read_until(&mut chars, '}')
+            }
+            Some(c) if c.is_ascii_alphanumeric() || *c == '_' => {
+                let mut name = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                name
+            }
+            _ => {
+                text.push('$');
+                continue;
+            }
+        };
+
+        tokens.push(Token::Text(std::mem::take(&mut text)));
+        let (name, default) = match field.split_once(':') {
+            Some((name, default)) => (name, Some(default.to_string())),
+            None => (field.as_str(), None),
+        };
+        match name.parse::<usize>() {
+            Ok(number) => tokens.push(Token::Stop(number, default)),
+            Err(_) => tokens.push(Token::Variable(name.to_string())),
+        }
+    }
+    tokens.push(Token::Text(text));
+    tokens
+}
+
+// Expands `body` as inserted on a line indented by `line_indent`, with body tabs replaced by `indent_unit`.
+pub fn expand(body: &str, variable: impl Fn(&str) -> Option<String>, indent_unit: &str, line_indent: &str) -> Expansion {
+    let tokens = tokenize(body);
+    let mut defaults: HashMap<usize, String> = HashMap::new();
+    for token in &tokens {
+        if let Token::Stop(number, Some(default)) = token {
+            defaults.entry(*number).or_insert_with(|| default.clone());
+        }
+    }
+
+    let mut lines = vec![String::new()];
+    let mut stops: Vec<TabStop> = Vec::new();
+    let push_text = |lines: &mut Vec<String>, text: &str| {
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(line_indent.to_string());
+            }
+            lines.last_mut().unwrap().push_str(&part.replace('\t', indent_unit));
+        }
+    };
+    let position = |lines: &Vec<String>| (lines.len() - 1, lines.last().unwrap().chars().count());
+
+    for token in tokens {
+        match token {
+            Token::Text(text) => push_text(&mut lines, &text),
+            Token::Variable(name) => match variable(&name) {
+                Some(value) => push_text(&mut lines, &value),
+                None => push_text(&mut lines, &format!("${}", name)),
+            },
+            Token::Stop(number, _) => {
+                let start = position(&lines);
+                push_text(&mut lines, defaults.get(&number).map_or("", String::as_str));
+                let range = (start, position(&lines));
+                match stops.iter_mut().find(|stop| stop.number == number) {
+                    Some(stop) => stop.ranges.push(range),
+                    None => stops.push(TabStop { number, ranges: vec![range] }),
+                }
+            }
+        }
+    }
+
+    // Stops are visited in increasing order and $0, the final cursor position, comes last.
+    if !stops.iter().any(|stop| stop.number == 0) {
+        let end = position(&lines);
+        stops.push(TabStop { number: 0, ranges: vec![(end, end)] });
+    }
+    stops.sort_by_key(|stop| if stop.number == 0 { usize::MAX } else { stop.number });
+    Expansion { lines, stops }
+}
+
+// Where `pos` ends up after the text ending at `old_end` now ends at `new_end`.
+pub fn shift(pos: Pos, old_end: Pos, new_end: Pos) -> Pos {
+    if pos < old_end {
+        pos
+    } else if pos.0 == old_end.0 {
+        (new_end.0, pos.1 - old_end.1 + new_end.1)
+    } else {
+        (pos.0 + new_end.0 - old_end.0, pos.1)
+    }
+}
+
+// End of `text` inserted at `start`.
+pub fn end_of(start: Pos, text: &str) -> Pos {
+    match text.rsplit_once('\n') {
+        Some((before, last)) => (start.0 + before.matches('\n').count() + 1, last.chars().count()),
+        None => (start.0, start.1 + text.chars().count()),
+    }
+}
+
+impl SnippetSession {
+    // Moves every position after `old_end`, except the range `skip` of the current stop.
+    pub fn shift_after(&mut self, old_end: Pos, new_end: Pos, skip: Option<(usize, usize)>) {
+        for (i, stop) in self.stops.iter_mut().enumerate() {
+            for (j, range) in stop.ranges.iter_mut().enumerate() {
+                if skip != Some((i, j)) {
+                    *range = (shift(range.0, old_end, new_end), shift(range.1, old_end, new_end));
+                }
+            }
+        }
+    }
+
+    pub fn rows(&self) -> (usize, usize) {
+        let positions = self.stops.iter().flat_map(|stop| stop.ranges.iter().flat_map(|&(start, end)| [start, end]));
+        let (first, last) = positions.fold((usize::MAX, 0), |(first, last), pos| (first.min(pos.0), last.max(pos.0)));
+        (first, last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_snippets() {
+        let content = "# comment\nsnippet fn\n\tfn $1() {\n\t\t$0\n\t}\n\nsnippet date\n\t$DATE\n";
+        let snippets = parse_snippets(content);
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0], Snippet { trigger: "fn".to_string(), body: "fn $1() {\n\t$0\n}".to_string() });
+        assert_eq!(snippets[1].body, "$DATE");
+    }
+
+    #[test]
+    fn test_expand_placeholders_and_mirrors() {
+        let expansion = expand("let ${1:x} = $2;\n\t$1", |_| None, "  ", "    ");
+        assert_eq!(expansion.lines, vec!["let x = ;", "      x"]);
+        assert_eq!(expansion.stops[0], TabStop { number: 1, ranges: vec![((0, 4), (0, 5)), ((1, 6), (1, 7))] });
+        assert_eq!(expansion.stops[1], TabStop { number: 2, ranges: vec![((0, 8), (0, 8))] });
+        assert_eq!(expansion.stops[2], TabStop { number: 0, ranges: vec![((1, 7), (1, 7))] });
+    }
+
+    #[test]
+    fn test_expand_variables() {
+        let variable = |name: &str| (name == "FILENAME").then(|| "main.rs".to_string());
+        let expansion = expand("// ${FILENAME} $UNKNOWN \\$1", variable, "\t", "");
+        assert_eq!(expansion.lines, vec!["// main.rs $UNKNOWN $1"]);
+        assert_eq!(expansion.stops.len(), 1);
+    }
+
+    #[test]
+    fn test_shift() {
+        assert_eq!(shift((0, 2), (0, 5), (0, 8)), (0, 2));
+        assert_eq!(shift((0, 7), (0, 5), (0, 8)), (0, 10));
+        assert_eq!(shift((0, 7), (0, 5), (1, 2)), (1, 4));
+        assert_eq!(shift((2, 1), (0, 5), (1, 2)), (3, 1));
+        assert_eq!(end_of((3, 4), "ab\ncde"), (4, 3));
+    }
+}
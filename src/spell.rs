@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const MAX_SUGGESTIONS: usize = 8;
+const MAX_DISTANCE: usize = 2;
+const SYSTEM_DICTIONARIES: [&str; 3] = ["/usr/share/hunspell/en_US.dic", "/usr/share/myspell/en_US.dic", "/usr/share/dict/words"];
+const PROSE_FILE_TYPES: [&str; 8] = ["md", "markdown", "txt", "text", "rst", "adoc", "org", "tex"];
+// Tried when a word is not in the dictionary, since affix files are not read.
+const SUFFIXES: [(&str, &str); 10] = [
+    ("'s", ""),
+    ("ies", "y"),
+    ("ied", "y"),
+    ("es", ""),
+    ("s", ""),
+    ("ed", ""),
+    ("ed", "e"),
+    ("ing", ""),
+    ("ing", "e"),
+    ("ly", ""),
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+    personal_path: Option<PathBuf>,
+}
+
+fn parse_words(content: &str) -> impl Iterator<Item = String> + '_ {
+    content
+        .lines()
+        .enumerate()
+        // Hunspell .dic files start with the word count and add affix flags after a slash.
+        .filter(|(i, line)| !(*i == 0 && line.trim().parse::<usize>().is_ok()))
+        .filter_map(|(_, line)| line.split('/').next())
+        .map(|word| word.trim().to_lowercase())
+        .filter(|word| !word.is_empty())
+}
+
+impl Dictionary {
+    pub fn from_words(content: &str) -> Dictionary {
+        Dictionary { words: parse_words(content).collect(), personal_path: None }
+    }
+
+    // The user's dictionary or the first system one, plus the personal word list.
+    pub fn load(config_dir: Option<&Path>) -> Option<Dictionary> {
+        let user = config_dir.into_iter().flat_map(|dir| [dir.join("dictionary.dic"), dir.join("dictionary.txt")]);
+        let content = user
+            .chain(SYSTEM_DICTIONARIES.iter().map(PathBuf::from))
+            .find_map(|path| fs::read_to_string(path).ok())?;
+
+        let mut dictionary = Dictionary::from_words(&content);
+        if let Some(dir) = config_dir {
+            let personal_path = dir.join("personal.dic");
+            if let Ok(personal) = fs::read_to_string(&personal_path) {
+                dictionary.words.extend(parse_words(&personal));
+            }
+            dictionary.personal_path = Some(personal_path);
+        }
+        Some(dictionary)
+    }
+
+    pub fn is_correct(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.words.contains(&word)
+            || SUFFIXES.iter().any(|(suffix, replacement)| {
+                word.strip_suffix(suffix)
+                    .is_some_and(|stem| stem.chars().count() > 1 && self.words.contains(&format!("{}{}", stem, replacement)))
+            })
+    }
+
+    pub fn add_personal(&mut self, word: &str) -> io::Result<()> {
+        self.words.insert(word.to_lowercase());
+        let Some(path) = &self.personal_path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", word)
+    }
+
+    // Closest words by edit distance, keeping the capitalisation of `word`.
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        let lower: Vec<char> = word.to_lowercase().chars().collect();
+        let mut scored: Vec<(usize, &String)> = self
+            .words
+            .iter()
+            .filter(|candidate| candidate.chars().count().abs_diff(lower.len()) <= MAX_DISTANCE)
+            .filter_map(|candidate| {
+                let distance = edit_distance(&lower, &candidate.chars().collect::<Vec<_>>());
+                (distance <= MAX_DISTANCE).then_some((distance, candidate))
+            })
+            .collect();
+        scored.sort();
+
+        let capitalized = word.chars().next().is_some_and(char::is_uppercase);
+        scored
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, candidate)| if capitalized { capitalize(candidate) } else { candidate.clone() })
+            .collect()
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+// Levenshtein distance counting a swap of two neighbours as one edit.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1).min(row[j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+pub fn is_prose(file_type: Option<&str>) -> bool {
+    file_type.is_none_or(|file_type| PROSE_FILE_TYPES.contains(&file_type.to_lowercase().as_str()))
+}
+
+fn line_comment(file_type: &str) -> &'static str {
+    match file_type {
+        "py" | "sh" | "bash" | "zsh" | "rb" | "pl" | "toml" | "yaml" | "yml" | "conf" | "cfg" | "r" | "cmake" => "#",
+        "sql" | "lua" | "hs" => "--",
+        "lisp" | "clj" | "el" | "scm" | "asm" | "ini" => ";",
+        _ => "//",
+    }
+}
+
+// Character ranges of `line` to spell check: everything in prose, comments and strings in code.
+pub fn checked_ranges(line: &str, file_type: Option<&str>) -> Vec<(usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let Some(file_type) = file_type.filter(|_| !is_prose(file_type)) else {
+        return vec![(0, chars.len())];
+    };
+
+    let marker: Vec<char> = line_comment(file_type).chars().collect();
+    let c_style = marker == ['/', '/'];
+    let starts_with = |i: usize, text: &[char]| chars[i..].starts_with(text);
+    // Lines inside a /* ... */ block usually start with "*".
+    if c_style && line.trim_start().starts_with('*') {
+        return vec![(0, chars.len())];
+    }
+
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if starts_with(i, &marker) {
+            ranges.push((i, chars.len()));
+            break;
+        } else if c_style && starts_with(i, &['/', '*']) {
+            let end = (i + 2..chars.len()).find(|&j| starts_with(j, &['*', '/'])).unwrap_or(chars.len());
+            ranges.push((i, end));
+            i = end + 2;
+        } else if chars[i] == '"' {
+            let mut end = i + 1;
+            while end < chars.len() && chars[end] != '"' {
+                end += if chars[end] == '\\' { 2 } else { 1 };
+            }
+            ranges.push((i + 1, end.min(chars.len())));
+            i = end + 1;
+        } else {
+            i += 1;
+        }
+    }
+    ranges
+}
+
+// Words worth checking: letters only, not all capitals and not camelCase.
+fn is_checkable(word: &[char]) -> bool {
+    word.len() > 1
+        && word.iter().all(|c| c.is_alphabetic() || *c == '\'')
+        && !word[1..].iter().any(|c| c.is_uppercase())
+}
+
+// Character ranges of the misspelled words in `line`.
+pub fn misspelled(line: &str, file_type: Option<&str>, dictionary: &Dictionary) -> Vec<(usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut result = Vec::new();
+    for (start, end) in checked_ranges(line, file_type) {
+        let mut i = start;
+        while i < end {
+            if !chars[i].is_alphanumeric() {
+                i += 1;
+                continue;
+            }
+            let word_start = i;
+            while i < end && (chars[i].is_alphanumeric() || chars[i] == '_' || (chars[i] == '\'' && i + 1 < end && chars[i + 1].is_alphabetic())) {
+                i += 1;
+            }
+            let word = &chars[word_start..i];
+            if is_checkable(word) && !dictionary.is_correct(&word.iter().collect::<String>()) {
+                result.push((word_start, i));
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn dictionary() -> Dictionary {
+        Dictionary::from_words("5\nhello/S\nworld\ncheck/DGS\nthe\nspell\n")
+    }
+
+    #[test]
+    fn test_is_correct() {
+        let dictionary = dictionary();
+        assert!(dictionary.is_correct("Hello"));
+        assert!(dictionary.is_correct("checked"));
+        assert!(dictionary.is_correct("worlds"));
+        assert!(!dictionary.is_correct("wrold"));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let dictionary = dictionary();
+        assert_eq!(dictionary.suggestions("wrold"), vec!["world"]);
+        assert_eq!(dictionary.suggestions("Teh"), vec!["The"]);
+    }
+
+    #[test]
+    fn test_checked_ranges_in_code() {
+        assert_eq!(checked_ranges("let x = \"a b\"; // note", Some("rs")), vec![(9, 12), (15, 22)]);
+        assert_eq!(checked_ranges("x = 1 # note", Some("py")), vec![(6, 12)]);
+        assert_eq!(checked_ranges("any text", Some("md")), vec![(0, 8)]);
+        assert_eq!(checked_ranges(" * doc line", Some("c")), vec![(0, 11)]);
+    }
+
+    #[test]
+    fn test_misspelled() {
+        let dictionary = dictionary();
+        assert_eq!(misspelled("helo world, NASA fooBar x2y", None, &dictionary), vec![(0, 4)]);
+        assert_eq!(misspelled("let wrold = \"wrold\";", Some("rs"), &dictionary), vec![(13, 18)]);
+    }
+
+    #[test]
+    fn test_personal_dictionary() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("dictionary.txt"), "word\n").unwrap();
+
+        let mut dictionary = Dictionary::load(Some(dir.path())).unwrap();
+        assert!(!dictionary.is_correct("divitext"));
+        dictionary.add_personal("divitext").unwrap();
+        assert!(dictionary.is_correct("divitext"));
+
+        let reloaded = Dictionary::load(Some(dir.path())).unwrap();
+        assert!(reloaded.is_correct("divitext"));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::action_bar::ActionWidget;
+use crate::error_type::ErrorType;
+use crate::popup::popup_area;
+use crate::spell::misspelled;
+use crate::prompt::render_message;
+use crate::state::State;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::widgets::{Block, Borders, Clear, HighlightSpacing, List, ListItem, ListState, StatefulWidget, Widget};
+use std::cell::{Cell, RefCell};
+use std::io;
+use std::rc::Rc;
+
+const SELECT_STYLE: Style = Style::new().bg(Color::White).fg(Color::Black);
+const ADD_TO_DICTIONARY: &str = "Add to personal dictionary";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Misspelling {
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
+    pub word: String,
+    pub suggestions: Vec<String>,
+}
+
+// Lists suggestions for the misspelled word at or after the cursor.
+#[derive(Debug)]
+pub struct SpellWidget<'a> {
+    pub state: Rc<RefCell<State<'a>>>,
+    pub list_state: ListState,
+    // Searched on first use, since suggestions are slow to compute for every action bar key.
+    pub misspelling: RefCell<Option<Misspelling>>,
+    pub searched: Cell<bool>,
+    pub error: ErrorType,
+}
+
+impl<'a> SpellWidget<'a> {
+    pub fn new(state: Rc<RefCell<State<'a>>>) -> SpellWidget<'a> {
+        SpellWidget {
+            state,
+            list_state: ListState::default(),
+            misspelling: RefCell::new(None),
+            searched: Cell::new(false),
+            error: ErrorType::NONE,
+        }
+    }
+
+    fn find_misspelling(&self) -> Result<Option<Misspelling>, ErrorType> {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        if state.load_dictionary().is_none() {
+            return Err(ErrorType::NoDictionary);
+        }
+        let (Some(buffer), Some(dictionary)) = (state.buffer_list.get(state.current_buffer), &state.dictionary) else {
+            return Err(ErrorType::NoBuffer);
+        };
+
+        // The word under the cursor, else the next one, wrapping around to the top.
+        let lines = buffer.input.lines();
+        let (cursor_row, cursor_col) = buffer.input.cursor();
+        let rows = (cursor_row..lines.len()).chain(0..=cursor_row);
+        let found = rows.enumerate().find_map(|(i, row)| {
+            misspelled(&lines[row], buffer.file_type(), dictionary)
+                .into_iter()
+                .find(|&(_, end)| i > 0 || row != cursor_row || end >= cursor_col)
+                .map(|(start, end)| (row, start, end))
+        });
+
+        Ok(found.map(|(row, start, end)| {
+            let word: String = lines[row].chars().skip(start).take(end - start).collect();
+            let suggestions = dictionary.suggestions(&word);
+            Misspelling { row, start, end, word, suggestions }
+        }))
+    }
+
+    fn ensure_searched(&self) -> Result<(), ErrorType> {
+        if !self.searched.get() {
+            self.searched.set(true);
+            *self.misspelling.borrow_mut() = self.find_misspelling()?;
+        }
+        Ok(())
+    }
+
+    // Replaces the word with a suggestion, or adds it to the personal dictionary.
+    fn apply(&mut self, misspelling: &Misspelling, index: usize) -> Result<(), io::Error> {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        if let Some(suggestion) = misspelling.suggestions.get(index) {
+            let buffer = &mut state.buffer_list[state.current_buffer];
+            buffer.input.cancel_selection();
+            buffer.move_cursor_to(misspelling.row, misspelling.end);
+            buffer.complete_word(misspelling.start, suggestion);
+        } else if let Some(dictionary) = &mut state.dictionary {
+            dictionary.add_personal(&misspelling.word)?;
+        }
+        Ok(())
+    }
+
+    fn render_content(&self, area: Rect, buf: &mut Buffer) {
+        if self.error != ErrorType::NONE {
+            render_message(&self.error.to_string(), popup_area(area, 60, 3), buf);
+            return;
+        }
+        if let Err(error) = self.ensure_searched() {
+            render_message(&error.to_string(), popup_area(area, 60, 3), buf);
+            return;
+        }
+        let misspelling = self.misspelling.borrow();
+        let Some(misspelling) = misspelling.as_ref() else {
+            render_message(&ErrorType::NoMisspelling.to_string(), popup_area(area, 60, 3), buf);
+            return;
+        };
+
+        let items: Vec<ListItem> = misspelling
+            .suggestions
+            .iter()
+            .map(|suggestion| ListItem::new(suggestion.as_str()))
+            .chain([ListItem::new(ADD_TO_DICTIONARY).italic()])
+            .collect();
+        let area = popup_area(area, 60, items.len() as u16 + 2);
+        Clear.render(area, buf);
+
+        let block = Block::default()
+            .bold()
+            .title(format!("Spelling: {} (line {})", misspelling.word, misspelling.row + 1))
+            .borders(Borders::ALL);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECT_STYLE)
+            .highlight_symbol(">> ")
+            .highlight_spacing(HighlightSpacing::Always);
+
+        StatefulWidget::render(list, area, buf, &mut self.list_state.clone());
+    }
+}
+
+impl ActionWidget for SpellWidget<'_> {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.render_content(area, buf);
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
+        if self.error != ErrorType::NONE {
+            self.error = ErrorType::NONE;
+            return Ok(());
+        }
+        if let Err(error) = self.ensure_searched() {
+            self.error = error;
+            return Ok(());
+        }
+
+        let count = self.misspelling.borrow().as_ref().map_or(0, |misspelling| misspelling.suggestions.len() + 1);
+        match key.code {
+            KeyCode::Up => self.list_state.select_previous(),
+            KeyCode::Down => self.list_state.select_next(),
+            KeyCode::Home => self.list_state.select_first(),
+            KeyCode::End => self.list_state.select(count.checked_sub(1)),
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn has_error(&self) -> bool {
+        self.error != ErrorType::NONE
+    }
+
+    fn process_action(&mut self) -> Result<bool, io::Error> {
+        if let Err(error) = self.ensure_searched() {
+            self.error = error;
+            return Ok(false);
+        }
+        let Some(misspelling) = self.misspelling.borrow().clone() else {
+            self.error = ErrorType::NoMisspelling;
+            return Ok(false);
+        };
+        let Some(index) = self.list_state.selected().filter(|&index| index <= misspelling.suggestions.len()) else {
+            return Ok(false);
+        };
+        self.apply(&misspelling, index)?;
+        Ok(true)
+    }
+
+    fn init_action(&mut self) {
+        self.searched.set(false);
+        *self.misspelling.get_mut() = None;
+    }
+
+    fn reset(&mut self) {
+        self.error = ErrorType::NONE;
+        self.list_state.select(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spell::Dictionary;
+    use crate::test_support::state_with_buffer;
+
+    fn widget(lines: &[&str]) -> SpellWidget<'static> {
+        let state = state_with_buffer(lines);
+        state.borrow_mut().dictionary = Some(Dictionary::from_words("hello\nworld\nthe\n"));
+        let mut widget = SpellWidget::new(state);
+        widget.init_action();
+        widget
+    }
+
+    #[test]
+    fn test_replace_with_suggestion() {
+        let mut widget = widget(&["hello wrold", "teh end"]);
+        widget.handle_input(KeyEvent::from(KeyCode::Down)).unwrap();
+        assert!(widget.process_action().unwrap());
+        assert_eq!(widget.state.borrow().buffer_list[0].input.lines(), vec!["hello world", "teh end"]);
+    }
+
+    #[test]
+    fn test_add_to_dictionary() {
+        let mut widget = widget(&["wrold"]);
+        widget.handle_input(KeyEvent::from(KeyCode::End)).unwrap();
+        assert!(widget.process_action().unwrap());
+        assert!(widget.state.borrow().dictionary.as_ref().unwrap().is_correct("wrold"));
+    }
+
+    #[test]
+    fn test_no_misspelling() {
+        let mut widget = widget(&["hello world"]);
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.error, ErrorType::NoMisspelling);
+    }
+}
@@ -1,11 +1,26 @@
 use crate::app::CurrentScreen;
 use crate::buffer::Buffer;
+use crate::config::Config;
+use crate::cut_buffer::CutBuffer;
+use crate::macros::Macros;
+use crate::marks::{restore_global_marks, GlobalMark};
+use crate::spell::Dictionary;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 #[derive(Debug)]
 pub struct State<'a> {
     pub current_screen: CurrentScreen,
     pub current_buffer: usize,
     pub buffer_list: Vec<Buffer<'a>>,
+    pub cut_buffer: CutBuffer,
+    pub status_error: Option<String>,
+    pub macros: Macros,
+    pub config: Config,
+    pub dictionary: Option<Dictionary>,
+    pub global_marks: BTreeMap<char, GlobalMark>,
+    // Where global marks are saved, not saved when None.
+    pub session_file: Option<PathBuf>,
     pub exit: bool,
 }
 
@@ -15,6 +30,13 @@ impl<'a> Default for State<'a> {
             current_screen: CurrentScreen::Home,
             current_buffer: 0,
             buffer_list: Vec::new(),
+            cut_buffer: CutBuffer::default(),
+            status_error: None,
+            macros: Macros::default(),
+            config: Config::default(),
+            dictionary: None,
+            global_marks: BTreeMap::new(),
+            session_file: None,
             exit: false,
         }
     }
@@ -26,19 +48,35 @@ impl<'a> State<'a> {
             current_screen,
             current_buffer: 0,
             buffer_list: Vec::new(),
+            cut_buffer: CutBuffer::default(),
+            status_error: None,
+            macros: Macros::default(),
+            config: Config::default(),
+            dictionary: None,
+            global_marks: BTreeMap::new(),
+            session_file: None,
             exit: false,
         }
     }
 
-    pub fn push_buffer(&mut self, buffer: Buffer<'a>) {
+    pub fn push_buffer(&mut self, mut buffer: Buffer<'a>) {
+        restore_global_marks(self, &mut buffer);
         self.buffer_list.push(buffer);
         self.current_buffer = self.buffer_list.len() - 1;
     }
 
+    // Loads the dictionary the first time spelling is needed.
+    pub fn load_dictionary(&mut self) -> Option<&mut Dictionary> {
+        if self.dictionary.is_none() {
+            self.dictionary = Dictionary::load(crate::paths::config_dir().as_deref());
+        }
+        self.dictionary.as_mut()
+    }
+
     pub fn find_buffer_index(&self, path: &str) -> Option<usize> {
         self.buffer_list
             .iter()
-            .position(|x| x.clone().path.unwrap() == path)
+            .position(|x| x.path.as_deref() == Some(path))
     }
 }
 
@@ -0,0 +1,62 @@
+use crate::buffer::Buffer;
+
+const WORDS_PER_MINUTE: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Stats {
+    pub lines: usize,
+    pub words: usize,
+    pub chars: usize,
+    pub bytes: usize,
+}
+
+impl Stats {
+    // Line breaks count as one character and one byte, as when the text is saved.
+    pub fn of_text(text: &str) -> Stats {
+        Stats {
+            lines: text.split('\n').count(),
+            words: text.split_whitespace().count(),
+            chars: text.chars().count(),
+            bytes: text.len(),
+        }
+    }
+
+    // The selection, or else the whole buffer.
+    pub fn of_buffer(buffer: &Buffer) -> Stats {
+        Stats::of_text(&buffer.selected_text().unwrap_or_else(|| buffer.input.lines().join("\n")))
+    }
+
+    pub fn reading_time(&self) -> String {
+        let minutes = self.words.div_ceil(WORDS_PER_MINUTE);
+        match minutes {
+            0 => "none".to_string(),
+            1..60 => format!("{} min", minutes),
+            _ => format!("{} h {} min", minutes / 60, minutes % 60),
+        }
+    }
+}
+
+pub fn word_count(lines: &[String]) -> usize {
+    lines.iter().map(|line| line.split_whitespace().count()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_of_text() {
+        let stats = Stats::of_text("Déjà vu,  twice\n\nend");
+        assert_eq!(stats, Stats { lines: 3, words: 4, chars: 20, bytes: 22 });
+        assert_eq!(Stats::of_text(""), Stats { lines: 1, words: 0, chars: 0, bytes: 0 });
+    }
+
+    #[test]
+    fn test_reading_time() {
+        let time = |words| Stats { words, ..Stats::default() }.reading_time();
+        assert_eq!(time(0), "none");
+        assert_eq!(time(1), "1 min");
+        assert_eq!(time(401), "3 min");
+        assert_eq!(time(12_500), "1 h 3 min");
+    }
+}
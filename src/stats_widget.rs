@@ -0,0 +1,130 @@
+use crate::action_bar::ActionWidget;
+use crate::error_type::ErrorType;
+use crate::popup::popup_area;
+use crate::prompt::{render_message, Prompt};
+use crate::state::State;
+use crate::stats::Stats;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Stylize;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+const GOAL_TITLE: &str = "Word goal for this session (0: none)";
+
+// Shows the statistics of the selection or the buffer, and sets the word goal.
+#[derive(Debug)]
+pub struct StatsWidget<'a> {
+    pub state: Rc<RefCell<State<'a>>>,
+    pub prompt: Prompt<'a>,
+    pub stats: Stats,
+    pub selection: bool,
+}
+
+impl<'a> StatsWidget<'a> {
+    pub fn new(state: Rc<RefCell<State<'a>>>) -> StatsWidget<'a> {
+        StatsWidget { state, prompt: Prompt::new(GOAL_TITLE), stats: Stats::default(), selection: false }
+    }
+
+    // An empty input keeps the current goal.
+    pub fn set_word_goal(&mut self) {
+        let input = self.prompt.text().trim();
+        if input.is_empty() {
+            return;
+        }
+        match input.parse::<usize>() {
+            Ok(goal) => self.state.borrow_mut().config.word_goal = (goal > 0).then_some(goal),
+            Err(_) => {
+                self.prompt.error = ErrorType::InvalidWordGoal;
+                return;
+            }
+        }
+        self.prompt.clear();
+    }
+
+    fn render_content(&self, area: Rect, buf: &mut Buffer) {
+        let pop_up_area = popup_area(area, 50, 10);
+        Clear.render(pop_up_area, buf);
+        if self.prompt.has_error() {
+            render_message(&self.prompt.error.to_string(), popup_area(pop_up_area, 50, 3), buf);
+            return;
+        }
+
+        let [stats_area, goal_area] = Layout::vertical([Constraint::Length(7), Constraint::Length(3)]).areas(pop_up_area);
+        let title = if self.selection { "Statistics (selection)" } else { "Statistics (buffer)" };
+        let text = format!(
+            "Lines: {}\nWords: {}\nCharacters: {}\nBytes: {}\nReading time: {}",
+            self.stats.lines,
+            self.stats.words,
+            self.stats.chars,
+            self.stats.bytes,
+            self.stats.reading_time()
+        );
+        Paragraph::new(text)
+            .block(Block::default().bold().title(title).borders(Borders::ALL))
+            .render(stats_area, buf);
+        self.prompt.input.render(goal_area, buf);
+    }
+}
+
+impl ActionWidget for StatsWidget<'_> {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.render_content(area, buf);
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
+        self.prompt.handle_input(key);
+        Ok(())
+    }
+
+    fn has_error(&self) -> bool {
+        self.prompt.has_error()
+    }
+
+    fn process_action(&mut self) -> Result<bool, io::Error> {
+        self.set_word_goal();
+        Ok(!self.prompt.has_error())
+    }
+
+    fn init_action(&mut self) {
+        let state = self.state.borrow();
+        let Some(buffer) = state.buffer_list.get(state.current_buffer) else {
+            return;
+        };
+        self.selection = buffer.input.selection_range().is_some();
+        self.stats = Stats::of_buffer(buffer);
+    }
+
+    fn reset(&mut self) {
+        self.prompt.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{buffer, state_with};
+    use tui_textarea::CursorMove;
+
+    #[test]
+    fn test_stats_and_word_goal() {
+        let mut buffer = buffer(&["one two", "three"]);
+        buffer.input.start_selection();
+        buffer.input.move_cursor(CursorMove::WordForward);
+
+        let mut widget = StatsWidget::new(state_with(buffer));
+        widget.init_action();
+        assert!(widget.selection);
+        assert_eq!(widget.stats, Stats { lines: 1, words: 1, chars: 4, bytes: 4 });
+
+        widget.prompt.input.insert_str("500");
+        assert!(widget.process_action().unwrap());
+        assert_eq!(widget.state.borrow().config.word_goal, Some(500));
+        widget.prompt.input.insert_str("many");
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.prompt.error, ErrorType::InvalidWordGoal);
+    }
+}
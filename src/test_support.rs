@@ -0,0 +1,21 @@
+use crate::buffer::Buffer;
+use crate::state::State;
+use std::cell::RefCell;
+use std::rc::Rc;
+use tui_textarea::TextArea;
+
+// An unnamed buffer holding `lines`.
+pub fn buffer(lines: &[impl AsRef<str>]) -> Buffer<'static> {
+    Buffer::new(TextArea::new(lines.iter().map(|line| line.as_ref().to_string()).collect()), None)
+}
+
+// A shared state with `buffer` open, as the action widgets get it.
+pub fn state_with(buffer: Buffer<'static>) -> Rc<RefCell<State<'static>>> {
+    let state = Rc::new(RefCell::new(State::default()));
+    state.borrow_mut().push_buffer(buffer);
+    state
+}
+
+pub fn state_with_buffer(lines: &[impl AsRef<str>]) -> Rc<RefCell<State<'static>>> {
+    state_with(buffer(lines))
+}
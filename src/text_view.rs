@@ -0,0 +1,625 @@
+use crate::brackets::find_matching;
+use crate::buffer::Buffer;
+use crate::config::Config;
+use crate::spell::{misspelled, Dictionary};
+use crate::theme::Theme;
+use ratatui::buffer::Buffer as RatBuffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::widgets::Widget;
+use std::cell::Cell;
+use std::fmt;
+use std::ops::Range;
+use unicode_width::UnicodeWidthChar;
+
+const WRAP_MARKER: &str = "↪";
+const FOLD_MARKER: &str = "▸";
+const GUIDE_MARKER: &str = "│";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SoftWrap {
+    #[default]
+    Off,
+    Chars,
+    Words,
+}
+
+impl SoftWrap {
+    pub fn next(self) -> SoftWrap {
+        match self {
+            SoftWrap::Off => SoftWrap::Chars,
+            SoftWrap::Chars => SoftWrap::Words,
+            SoftWrap::Words => SoftWrap::Off,
+        }
+    }
+}
+
+impl fmt::Display for SoftWrap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SoftWrap::Off => write!(f, "No wrap"),
+            SoftWrap::Chars => write!(f, "Wrap"),
+            SoftWrap::Words => write!(f, "Word wrap"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Viewport {
+    pub top_row: Cell<usize>,
+    pub top_col: Cell<usize>,
+    pub width: Cell<usize>,
+    pub height: Cell<usize>,
+    // Screen position of the cursor at the last render, if it was visible.
+    pub cursor_position: Cell<Option<(u16, u16)>>,
+}
+
+impl Viewport {
+    pub fn center_on(&self, row: usize) {
+        self.top_row.set(row.saturating_sub(self.height.get() / 2));
+    }
+
+    pub fn scroll_to_row(&self, row: usize) {
+        let top = self.top_row.get();
+        let height = self.height.get().max(1);
+        if row < top {
+            self.top_row.set(row);
+        } else if row >= top + height {
+            self.top_row.set(row + 1 - height);
+        }
+    }
+
+    pub fn scroll_to_col(&self, col: usize) {
+        let left = self.top_col.get();
+        let width = self.width.get().max(1);
+        if col < left {
+            self.top_col.set(col);
+        } else if col >= left + width {
+            self.top_col.set(col + 1 - width);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyledCell {
+    pub symbol: String,
+    pub width: usize,
+    pub col: usize,
+    pub style: Style,
+}
+
+pub fn display_width(line: &str, tab_length: usize) -> usize {
+    line.chars().fold(0, |width, c| width + char_width(c, width, tab_length))
+}
+
+fn char_width(c: char, current_width: usize, tab_length: usize) -> usize {
+    if c == '\t' {
+        if tab_length == 0 {
+            0
+        } else {
+            tab_length - current_width % tab_length
+        }
+    } else {
+        c.width().unwrap_or(0)
+    }
+}
+
+pub fn line_cells(line: &str, tab_length: usize, style: Style) -> Vec<StyledCell> {
+    let mut cells = Vec::with_capacity(line.len());
+    let mut width = 0;
+    for (col, c) in line.chars().enumerate() {
+        let char_width = char_width(c, width, tab_length);
+        let symbol = if c == '\t' { " ".repeat(char_width) } else { c.to_string() };
+        cells.push(StyledCell { symbol, width: char_width, col, style });
+        width += char_width;
+    }
+    cells
+}
+
+// Cells of `line` plus one blank cell for the cursor past its end.
+pub fn line_cells_with_end(line: &str, tab_length: usize, style: Style) -> Vec<StyledCell> {
+    let mut cells = line_cells(line, tab_length, style);
+    cells.push(StyledCell { symbol: " ".to_string(), width: 1, col: cells.len(), style });
+    cells
+}
+
+// Splits cells into visual rows of at most `width` columns, at word boundaries when possible.
+pub fn wrap_cells(cells: &[StyledCell], width: usize, mode: SoftWrap) -> Vec<Range<usize>> {
+    let width = width.max(1);
+    let is_blank = |cell: &StyledCell| cell.symbol.chars().all(char::is_whitespace);
+    let mut segments = Vec::new();
+    let mut start = 0;
+    while start < cells.len() {
+        let mut end = start;
+        let mut used = 0;
+        while end < cells.len() && (end == start || used + cells[end].width <= width) {
+            used += cells[end].width;
+            end += 1;
+        }
+        if mode == SoftWrap::Words && end < cells.len() && !is_blank(&cells[end]) {
+            if let Some(boundary) = (start + 1..=end).rev().find(|&i| is_blank(&cells[i - 1])) {
+                end = boundary;
+            }
+        }
+        segments.push(start..end);
+        start = end;
+    }
+    if segments.is_empty() {
+        segments.push(0..0);
+    }
+    segments
+}
+
+fn num_digits(n: usize) -> usize {
+    n.to_string().len()
+}
+
+pub struct TextView<'b, 'a> {
+    buffer: &'b Buffer<'a>,
+    brackets: Option<((usize, usize), (usize, usize))>,
+    dictionary: Option<&'b Dictionary>,
+    theme: Theme,
+    line_numbers: bool,
+    overwrite: bool,
+    guides: Vec<usize>,
+}
+
+impl<'b, 'a> TextView<'b, 'a> {
+    pub fn new(buffer: &'b Buffer<'a>) -> TextView<'b, 'a> {
+        let brackets = find_matching(buffer.input.lines(), buffer.input.cursor());
+        TextView { buffer, brackets, dictionary: None, theme: Theme::default(), line_numbers: true, overwrite: false, guides: Vec::new() }
+    }
+
+    pub fn with_config(mut self, config: &Config) -> TextView<'b, 'a> {
+        self.theme = config.theme;
+        self.line_numbers = config.line_numbers;
+        self.guides = config.guides.clone();
+        self
+    }
+
+    // Underlines misspelled words when the buffer has spell checking on.
+    pub fn with_spelling(mut self, dictionary: Option<&'b Dictionary>) -> TextView<'b, 'a> {
+        self.dictionary = dictionary.filter(|_| self.buffer.spell_check);
+        self
+    }
+
+    // Draws the cursor in the theme's overwrite style.
+    pub fn with_overwrite(mut self, overwrite: bool) -> TextView<'b, 'a> {
+        self.overwrite = overwrite;
+        self
+    }
+
+    fn shows_line_numbers(&self) -> bool {
+        self.line_numbers && self.buffer.input.line_number_style().is_some()
+    }
+
+    // Without line numbers the gutter is only there for marks.
+    fn number_width(&self) -> usize {
+        if self.shows_line_numbers() {
+            num_digits(self.buffer.input.lines().len()) + 2
+        } else if !self.buffer.marks.is_empty() {
+            2
+        } else {
+            0
+        }
+    }
+
+    // Room for the marker and hidden line count of folds, like "▸12 ".
+    fn fold_width(&self) -> usize {
+        if self.buffer.folds.is_empty() {
+            0
+        } else {
+            num_digits(self.buffer.folds.max_hidden()) + 2
+        }
+    }
+
+    fn gutter_width(&self) -> usize {
+        self.number_width() + self.fold_width()
+    }
+
+    // Lines on screen from `top` down, skipping folded ones.
+    fn visible_rows(&self, top: usize) -> impl Iterator<Item = usize> + '_ {
+        self.buffer.folds.visible_rows(top, self.buffer.input.lines().len())
+    }
+
+    fn row_cells(&self, row: usize) -> Vec<StyledCell> {
+        let input = &self.buffer.input;
+        let line = &input.lines()[row];
+        let (cursor_row, cursor_col) = input.cursor();
+        let base_style = if row == cursor_row { input.cursor_line_style() } else { input.style() };
+        let mut cells = line_cells(line, input.tab_length() as usize, base_style);
+
+        if let Some(dictionary) = self.dictionary {
+            for (start, end) in misspelled(line, self.buffer.file_type(), dictionary) {
+                for cell in &mut cells[start..end] {
+                    cell.style = cell.style.patch(self.theme.misspelled);
+                }
+            }
+        }
+
+        if let Some(&limit) = self.guides.iter().min() {
+            let mut column = 0;
+            for cell in &mut cells {
+                if column >= limit {
+                    cell.style = cell.style.patch(self.theme.over_limit);
+                }
+                column += cell.width;
+            }
+        }
+
+        let mut select_at_end = false;
+        if let Some(((start_row, start_col), (end_row, end_col))) = input.selection_range() {
+            if (start_row..=end_row).contains(&row) {
+                let from = if row == start_row { start_col } else { 0 };
+                let to = if row == end_row { end_col } else { usize::MAX };
+                for cell in cells.iter_mut().filter(|cell| cell.col >= from && cell.col < to) {
+                    cell.style = cell.style.patch(self.theme.selection);
+                }
+                select_at_end = row < end_row;
+            }
+        }
+
+        if let Some((bracket, target)) = self.brackets {
+            for (bracket_row, bracket_col) in [bracket, target] {
+                if let Some(cell) = cells.get_mut(bracket_col).filter(|_| bracket_row == row) {
+                    cell.style = cell.style.patch(self.theme.matching_bracket);
+                }
+            }
+        }
+
+        if row == cursor_row {
+            let cursor_style = if self.overwrite { self.theme.overwrite_cursor } else { input.cursor_style() };
+            if cursor_col >= cells.len() {
+                cells.push(StyledCell {
+                    symbol: " ".to_string(),
+                    width: 1,
+                    col: cursor_col,
+                    style: cursor_style,
+                });
+            } else {
+                cells[cursor_col].style = cells[cursor_col].style.patch(cursor_style);
+            }
+        } else if select_at_end {
+            cells.push(StyledCell { symbol: " ".to_string(), width: 1, col: cells.len(), style: self.theme.selection });
+        }
+        cells
+    }
+
+    fn render_gutter(&self, row: usize, continuation: bool, x: u16, y: u16, buf: &mut RatBuffer) {
+        let width = self.number_width();
+        if width > 0 {
+            if self.shows_line_numbers() {
+                let label = if continuation { WRAP_MARKER.to_string() } else { (row + 1).to_string() };
+                let number = format!(" {:>width$} ", label, width = width - 2);
+                buf.set_stringn(x, y, number, width, self.theme.line_number);
+            }
+            // The mark replaces the space before the line number.
+            let mark = self.buffer.marks.iter().find(|(_, (mark_row, _))| *mark_row == row).map(|(mark, _)| *mark);
+            if let Some(mark) = mark.filter(|_| !continuation) {
+                buf.set_string(x, y, mark.to_string(), self.theme.mark);
+            }
+        }
+
+        let fold_width = self.fold_width();
+        let hidden = self.buffer.folds.hidden_after(row, self.buffer.input.lines().len());
+        if fold_width > 0 && hidden > 0 && !continuation {
+            let marker = format!("{}{:<count$} ", FOLD_MARKER, hidden, count = fold_width - 2);
+            buf.set_stringn(x + width as u16, y, marker, fold_width, self.theme.line_number);
+        }
+    }
+
+    // Draws the guides between the end of the text and the right edge, `left` being
+    // the display column at the left edge of the screen row.
+    fn render_guides(&self, left: usize, end: usize, area: Rect, y: u16, buf: &mut RatBuffer) {
+        let right = left + area.width as usize;
+        for &guide in self.guides.iter().filter(|&&guide| guide >= left.max(end) && guide < right) {
+            buf.set_string(area.x + (guide - left) as u16, y, GUIDE_MARKER, self.theme.guide);
+        }
+    }
+
+    fn render_cells(&self, cells: &[StyledCell], left: usize, area: Rect, y: u16, cursor_col: Option<usize>, buf: &mut RatBuffer) {
+        let right = left + area.width as usize;
+        let mut column = 0;
+        for cell in cells {
+            let start = column;
+            column += cell.width;
+            if start < left || column > right {
+                continue;
+            }
+            let x = area.x + (start - left) as u16;
+            if cursor_col == Some(cell.col) {
+                self.buffer.viewport.cursor_position.set(Some((x, y)));
+            }
+            buf.set_stringn(x, y, &cell.symbol, cell.width, cell.style);
+        }
+    }
+
+    fn wrap_row(&self, row: usize, width: usize) -> Vec<Range<usize>> {
+        let input = &self.buffer.input;
+        let cells = line_cells_with_end(&input.lines()[row], input.tab_length() as usize, Style::default());
+        wrap_cells(&cells, width, self.buffer.soft_wrap)
+    }
+
+    // Like `Viewport::scroll_to_row`, counting only the lines that are not folded.
+    fn scroll_to_row(&self, row: usize, height: usize) {
+        let viewport = &self.buffer.viewport;
+        let folds = &self.buffer.folds;
+        if folds.is_empty() {
+            viewport.scroll_to_row(row);
+            return;
+        }
+        let top = viewport.top_row.get();
+        let top = if folds.is_hidden(top) { folds.prev_visible(top).unwrap_or(0) } else { top };
+        if row < top {
+            viewport.top_row.set(row);
+            return;
+        }
+        let rows: Vec<usize> = self.visible_rows(top).take_while(|&visible| visible <= row).collect();
+        let top = if rows.len() > height { rows[rows.len() - height.max(1)] } else { top };
+        viewport.top_row.set(top);
+    }
+
+    // Moves the first visible line down until the cursor's visual row fits on screen.
+    fn scroll_wrapped(&self, width: usize, height: usize) {
+        let viewport = &self.buffer.viewport;
+        let (cursor_row, cursor_col) = self.buffer.input.cursor();
+        let folds = &self.buffer.folds;
+        if folds.is_hidden(viewport.top_row.get()) {
+            viewport.top_row.set(folds.prev_visible(viewport.top_row.get()).unwrap_or(0));
+        }
+        if cursor_row < viewport.top_row.get() {
+            viewport.top_row.set(cursor_row);
+        }
+
+        let segments = self.wrap_row(cursor_row, width);
+        let cursor_segment = segments.iter().position(|segment| segment.contains(&cursor_col)).unwrap_or(0);
+        let rows: Vec<usize> = self.visible_rows(viewport.top_row.get()).take_while(|&row| row < cursor_row).collect();
+        let mut heights: Vec<usize> = rows.iter().map(|&row| self.wrap_row(row, width).len()).collect();
+        let mut visible = heights.iter().sum::<usize>() + cursor_segment + 1;
+        let mut skipped = 0;
+        while visible > height && skipped < rows.len() {
+            visible -= heights.remove(0);
+            skipped += 1;
+        }
+        viewport.top_row.set(rows.get(skipped).copied().unwrap_or(cursor_row));
+    }
+
+    fn render_wrapped(&self, area: Rect, text_area: Rect, buf: &mut RatBuffer) {
+        let input = &self.buffer.input;
+        let width = text_area.width as usize;
+        let height = text_area.height as usize;
+        self.buffer.viewport.top_col.set(0);
+        self.scroll_wrapped(width, height);
+
+        let mut offset = 0;
+        for row in self.visible_rows(self.buffer.viewport.top_row.get()) {
+            let end = display_width(&input.lines()[row], input.tab_length() as usize);
+            let mut cells = self.row_cells(row);
+            if cells.len() == input.lines()[row].chars().count() {
+                let style = if row == input.cursor().0 { input.cursor_line_style() } else { input.style() };
+                cells.push(StyledCell { symbol: " ".to_string(), width: 1, col: cells.len(), style });
+            }
+
+            for (i, segment) in wrap_cells(&cells, width, self.buffer.soft_wrap).into_iter().enumerate() {
+                if offset >= height {
+                    return;
+                }
+                let y = area.y + offset as u16;
+                self.render_gutter(row, i > 0, area.x, y, buf);
+                let cursor_col = (row == input.cursor().0).then_some(input.cursor().1);
+                let left = cells[..segment.start].iter().map(|cell| cell.width).sum();
+                self.render_guides(left, end, text_area, y, buf);
+                self.render_cells(&cells[segment], 0, text_area, y, cursor_col, buf);
+                offset += 1;
+            }
+        }
+    }
+}
+
+impl Widget for TextView<'_, '_> {
+    fn render(self, area: Rect, buf: &mut RatBuffer) {
+        let input = &self.buffer.input;
+        let viewport = &self.buffer.viewport;
+        let gutter_width = (self.gutter_width() as u16).min(area.width);
+        let text_area = Rect {
+            x: area.x + gutter_width,
+            width: area.width - gutter_width,
+            ..area
+        };
+
+        viewport.width.set(text_area.width as usize);
+        viewport.height.set(text_area.height as usize);
+        viewport.cursor_position.set(None);
+        if self.buffer.soft_wrap != SoftWrap::Off {
+            self.render_wrapped(area, text_area, buf);
+            return;
+        }
+
+        let (cursor_row, cursor_col) = input.cursor();
+        let cursor_line: String = input.lines()[cursor_row].chars().take(cursor_col).collect();
+        self.scroll_to_row(cursor_row, text_area.height as usize);
+        viewport.scroll_to_col(display_width(&cursor_line, input.tab_length() as usize));
+
+        let top = viewport.top_row.get();
+        for (offset, row) in self.visible_rows(top).take(text_area.height as usize).enumerate() {
+            let y = area.y + offset as u16;
+            self.render_gutter(row, false, area.x, y, buf);
+            let cursor_col = (row == cursor_row).then_some(cursor_col);
+            let end = display_width(&input.lines()[row], input.tab_length() as usize);
+            self.render_guides(viewport.top_col.get(), end, text_area, y, buf);
+            self.render_cells(&self.row_cells(row), viewport.top_col.get(), text_area, y, cursor_col, buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Modifier};
+    use tui_textarea::{CursorMove, TextArea};
+
+    fn render_lines(buffer: &Buffer, area: Rect) -> Vec<String> {
+        let mut buf = RatBuffer::empty(area);
+        TextView::new(buffer).render(area, &mut buf);
+        (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect::<String>())
+            .collect()
+    }
+
+    #[test]
+    fn test_display_width_expands_tabs() {
+        assert_eq!(display_width("a\tb", 4), 5);
+        assert_eq!(display_width("\t\t", 4), 8);
+        assert_eq!(display_width("abc", 4), 3);
+    }
+
+    #[test]
+    fn test_center_on() {
+        let viewport = Viewport::default();
+        viewport.height.set(10);
+        viewport.center_on(50);
+        assert_eq!(viewport.top_row.get(), 45);
+        viewport.center_on(2);
+        assert_eq!(viewport.top_row.get(), 0);
+    }
+
+    #[test]
+    fn test_render_draws_line_numbers_and_text() {
+        let lines = vec!["one".to_string(), "\ttwo".to_string()];
+        let buffer = Buffer::new(Buffer::default().custom_text_area(lines), None);
+
+        let rendered = render_lines(&buffer, Rect::new(0, 0, 12, 2));
+        assert_eq!(rendered, vec![" 1 one      ", " 2     two  "]);
+
+        let config = Config { line_numbers: false, ..Config::default() };
+        let mut buf = RatBuffer::empty(Rect::new(0, 0, 12, 1));
+        TextView::new(&buffer).with_config(&config).render(Rect::new(0, 0, 12, 1), &mut buf);
+        assert_eq!((0..12).map(|x| buf[(x, 0)].symbol()).collect::<String>(), "one         ");
+    }
+
+    #[test]
+    fn test_render_folds() {
+        let lines: Vec<String> = ["a {", "  b", "  c", "}", "d"].iter().map(|line| line.to_string()).collect();
+        let mut buffer = Buffer::new(Buffer::default().custom_text_area(lines), None);
+        buffer.fold_at_cursor();
+        assert_eq!(render_lines(&buffer, Rect::new(0, 0, 10, 3)), vec![" 1 ▸2 a { ", " 4    }   ", " 5    d   "]);
+
+        buffer.soft_wrap = SoftWrap::Chars;
+        assert_eq!(render_lines(&buffer, Rect::new(0, 0, 10, 3)), vec![" 1 ▸2 a { ", " 4    }   ", " 5    d   "]);
+    }
+
+    #[test]
+    fn test_render_marks_in_gutter() {
+        let lines = vec!["one".to_string(), "two".to_string()];
+        let mut buffer = Buffer::new(Buffer::default().custom_text_area(lines), None);
+        buffer.marks.insert('a', (1, 0));
+        assert_eq!(render_lines(&buffer, Rect::new(0, 0, 8, 2)), vec![" 1 one  ", "a2 two  "]);
+
+        let config = Config { line_numbers: false, ..Config::default() };
+        let mut buf = RatBuffer::empty(Rect::new(0, 0, 8, 2));
+        TextView::new(&buffer).with_config(&config).render(Rect::new(0, 0, 8, 2), &mut buf);
+        assert_eq!((0..8).map(|x| buf[(x, 1)].symbol()).collect::<String>(), "a two   ");
+    }
+
+    #[test]
+    fn test_render_guides() {
+        let lines = vec!["\tab".to_string(), "abcdefg".to_string()];
+        let buffer = Buffer::new(TextArea::new(lines), None);
+        let config = Config { line_numbers: false, guides: vec![6, 8], ..Config::default() };
+        let area = Rect::new(0, 0, 10, 2);
+        let mut buf = RatBuffer::empty(area);
+        TextView::new(&buffer).with_config(&config).render(area, &mut buf);
+        let rendered: Vec<String> = (0..2).map(|y| (0..10).map(|x| buf[(x, y)].symbol()).collect()).collect();
+        assert_eq!(rendered, vec!["    ab│ │ ", "abcdefg │ "]);
+        assert_ne!(buf[(5, 1)].bg, Color::Red);
+        assert_eq!(buf[(6, 1)].bg, Color::Red);
+    }
+
+    #[test]
+    fn test_over_limit_uses_smallest_guide() {
+        let lines = vec!["abcdefghij".to_string()];
+        let buffer = Buffer::new(TextArea::new(lines), None);
+        let config = Config { line_numbers: false, guides: vec![8, 4], ..Config::default() };
+        let area = Rect::new(0, 0, 12, 1);
+        let mut buf = RatBuffer::empty(area);
+        TextView::new(&buffer).with_config(&config).render(area, &mut buf);
+        assert_ne!(buf[(3, 0)].bg, Color::Red);
+        assert_eq!(buf[(4, 0)].bg, Color::Red);
+    }
+
+    #[test]
+    fn test_matching_brackets_are_highlighted() {
+        let lines = vec!["f(a)".to_string()];
+        let mut buffer = Buffer::new(TextArea::new(lines), None);
+        buffer.move_cursor_to(0, 1);
+
+        let cells = TextView::new(&buffer).row_cells(0);
+        assert!(cells[1].style.add_modifier.contains(Modifier::BOLD));
+        assert!(cells[3].style.add_modifier.contains(Modifier::BOLD));
+        assert!(!cells[2].style.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn test_misspelled_words_are_underlined() {
+        let mut buffer = Buffer::new(Buffer::default().custom_text_area(vec!["a wrold".to_string()]), None);
+        let dictionary = Dictionary::from_words("world\n");
+        let underlined = |buffer: &Buffer| {
+            let cells = TextView::new(buffer).with_spelling(Some(&dictionary)).row_cells(0);
+            cells.iter().map(|cell| cell.style.add_modifier.contains(Modifier::UNDERLINED)).collect::<Vec<_>>()
+        };
+        assert!(underlined(&buffer).iter().all(|&underlined| !underlined));
+
+        buffer.spell_check = true;
+        assert_eq!(underlined(&buffer)[..7], [false, false, true, true, true, true, true]);
+    }
+
+    fn cells(line: &str) -> Vec<StyledCell> {
+        line_cells_with_end(line, 4, Style::default())
+    }
+
+    #[test]
+    fn test_wrap_cells() {
+        assert_eq!(wrap_cells(&cells("abcdefg"), 3, SoftWrap::Chars), vec![0..3, 3..6, 6..8]);
+        assert_eq!(wrap_cells(&cells(""), 3, SoftWrap::Chars), vec![0..1]);
+    }
+
+    #[test]
+    fn test_wrap_cells_at_words() {
+        assert_eq!(wrap_cells(&cells("ab cd ef"), 6, SoftWrap::Words), vec![0..6, 6..9]);
+        assert_eq!(wrap_cells(&cells("abcdefgh"), 3, SoftWrap::Words), vec![0..3, 3..6, 6..9]);
+    }
+
+    #[test]
+    fn test_render_soft_wrap() {
+        let lines = vec!["one two three".to_string(), "x".to_string()];
+        let mut buffer = Buffer::new(Buffer::default().custom_text_area(lines), None);
+        buffer.soft_wrap = SoftWrap::Words;
+
+        let rendered = render_lines(&buffer, Rect::new(0, 0, 11, 4));
+        assert_eq!(rendered, vec![" 1 one two ", " ↪ three   ", " 2 x       ", "           "]);
+    }
+
+    #[test]
+    fn test_soft_wrap_scrolls_by_visual_rows() {
+        let lines = vec!["a".repeat(10), "b".to_string(), "c".to_string()];
+        let mut buffer = Buffer::new(TextArea::new(lines), None);
+        buffer.soft_wrap = SoftWrap::Chars;
+        buffer.move_cursor_to(2, 0);
+
+        let rendered = render_lines(&buffer, Rect::new(0, 0, 4, 3));
+        assert_eq!(buffer.viewport.top_row.get(), 1);
+        assert_eq!(rendered[0], "b   ");
+    }
+
+    #[test]
+    fn test_render_follows_cursor() {
+        let lines = (1..=20).map(|i| i.to_string()).collect();
+        let mut buffer = Buffer::new(TextArea::new(lines), None);
+        buffer.input.move_cursor(CursorMove::Bottom);
+
+        let rendered = render_lines(&buffer, Rect::new(0, 0, 4, 5));
+        assert_eq!(buffer.viewport.top_row.get(), 15);
+        assert_eq!(rendered[4], "20  ");
+        assert_eq!(buffer.viewport.cursor_position.get(), Some((0, 4)));
+    }
+}
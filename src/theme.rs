@@ -0,0 +1,68 @@
+use ratatui::style::{Color, Modifier, Style};
+
+// Styles the text view draws on top of the text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub selection: Style,
+    pub matching_bracket: Style,
+    pub misspelled: Style,
+    pub line_number: Style,
+    pub mark: Style,
+    // The cursor while typing replaces text.
+    pub overwrite_cursor: Style,
+    pub guide: Style,
+    // Text past the first guide.
+    pub over_limit: Style,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        name: "dark",
+        selection: Style::new().bg(Color::LightBlue),
+        matching_bracket: Style::new().bg(Color::DarkGray).add_modifier(Modifier::BOLD),
+        misspelled: Style::new().underline_color(Color::Red).add_modifier(Modifier::UNDERLINED),
+        line_number: Style::new().fg(Color::DarkGray),
+        mark: Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        overwrite_cursor: Style::new().bg(Color::Yellow).fg(Color::Black),
+        guide: Style::new().fg(Color::DarkGray),
+        over_limit: Style::new().bg(Color::Red),
+    };
+
+    pub const LIGHT: Theme = Theme {
+        name: "light",
+        selection: Style::new().bg(Color::Blue).fg(Color::White),
+        matching_bracket: Style::new().bg(Color::Gray).add_modifier(Modifier::BOLD),
+        misspelled: Style::new().underline_color(Color::Red).add_modifier(Modifier::UNDERLINED),
+        line_number: Style::new().fg(Color::Gray),
+        mark: Style::new().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+        overwrite_cursor: Style::new().bg(Color::Magenta).fg(Color::White),
+        guide: Style::new().fg(Color::Gray),
+        over_limit: Style::new().bg(Color::LightRed),
+    };
+
+    // No colors, for terminals that only do attributes.
+    pub const MONOCHROME: Theme = Theme {
+        name: "monochrome",
+        selection: Style::new().add_modifier(Modifier::REVERSED),
+        matching_bracket: Style::new().add_modifier(Modifier::BOLD.union(Modifier::UNDERLINED)),
+        misspelled: Style::new().add_modifier(Modifier::UNDERLINED),
+        line_number: Style::new().add_modifier(Modifier::DIM),
+        mark: Style::new().add_modifier(Modifier::BOLD),
+        overwrite_cursor: Style::new().add_modifier(Modifier::BOLD.union(Modifier::UNDERLINED)),
+        guide: Style::new().add_modifier(Modifier::DIM),
+        over_limit: Style::new().add_modifier(Modifier::ITALIC.union(Modifier::UNDERLINED)),
+    };
+
+    pub const ALL: [Theme; 3] = [Theme::DARK, Theme::LIGHT, Theme::MONOCHROME];
+
+    pub fn by_name(name: &str) -> Option<Theme> {
+        Theme::ALL.into_iter().find(|theme| theme.name == name)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::DARK
+    }
+}
@@ -0,0 +1,265 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    SortLexical,
+    SortNumeric,
+    SortReverse,
+    SortCaseInsensitive,
+    RemoveDuplicates,
+    ReverseLines,
+    Upper,
+    Lower,
+    Title,
+    Snake,
+    Camel,
+    Kebab,
+    Trim,
+    TrimTrailing,
+}
+
+pub const TRANSFORMS: [Transform; 14] = [
+    Transform::SortLexical,
+    Transform::SortNumeric,
+    Transform::SortReverse,
+    Transform::SortCaseInsensitive,
+    Transform::RemoveDuplicates,
+    Transform::ReverseLines,
+    Transform::Upper,
+    Transform::Lower,
+    Transform::Title,
+    Transform::Snake,
+    Transform::Camel,
+    Transform::Kebab,
+    Transform::Trim,
+    Transform::TrimTrailing,
+];
+
+impl Transform {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Transform::SortLexical => "Sort lines",
+            Transform::SortNumeric => "Sort lines numerically",
+            Transform::SortReverse => "Sort lines in reverse",
+            Transform::SortCaseInsensitive => "Sort lines ignoring case",
+            Transform::RemoveDuplicates => "Remove duplicate lines",
+            Transform::ReverseLines => "Reverse lines",
+            Transform::Upper => "UPPER CASE",
+            Transform::Lower => "lower case",
+            Transform::Title => "Title Case",
+            Transform::Snake => "snake_case",
+            Transform::Camel => "camelCase",
+            Transform::Kebab => "kebab-case",
+            Transform::Trim => "Trim whitespace",
+            Transform::TrimTrailing => "Trim trailing whitespace",
+        }
+    }
+
+    // Line transforms work on whole lines, the others on the selected text only.
+    pub fn is_line_transform(&self) -> bool {
+        matches!(
+            self,
+            Transform::SortLexical
+                | Transform::SortNumeric
+                | Transform::SortReverse
+                | Transform::SortCaseInsensitive
+                | Transform::RemoveDuplicates
+                | Transform::ReverseLines
+                | Transform::Trim
+                | Transform::TrimTrailing
+        )
+    }
+
+    pub fn apply_lines(&self, lines: &[String]) -> Vec<String> {
+        let mut lines = lines.to_vec();
+        match self {
+            Transform::SortLexical => lines.sort(),
+            Transform::SortNumeric => lines.sort_by(|a, b| {
+                leading_number(a).partial_cmp(&leading_number(b)).unwrap_or(Ordering::Equal)
+            }),
+            Transform::SortReverse => lines.sort_by(|a, b| b.cmp(a)),
+            Transform::SortCaseInsensitive => lines.sort_by_key(|line| line.to_lowercase()),
+            Transform::RemoveDuplicates => {
+                let mut seen = HashSet::new();
+                lines.retain(|line| seen.insert(line.clone()));
+            }
+            Transform::ReverseLines => lines.reverse(),
+            Transform::Trim => lines = lines.iter().map(|line| line.trim().to_string()).collect(),
+            Transform::TrimTrailing => lines = lines.iter().map(|line| line.trim_end().to_string()).collect(),
+            _ => lines = lines.iter().map(|line| self.apply_text(line)).collect(),
+        }
+        lines
+    }
+
+    pub fn apply_text(&self, text: &str) -> String {
+        match self {
+            Transform::Upper => text.to_uppercase(),
+            Transform::Lower => text.to_lowercase(),
+            Transform::Title => title_case(text),
+            Transform::Snake => map_identifiers(text, is_identifier_char, |words| words.join("_").to_lowercase()),
+            Transform::Kebab => map_identifiers(text, is_kebab_identifier_char, |words| words.join("-").to_lowercase()),
+            Transform::Camel => map_identifiers(text, is_identifier_char, camel_case),
+            _ => self.apply_lines(&text.split('\n').map(String::from).collect::<Vec<_>>()).join("\n"),
+        }
+    }
+}
+
+// Like `sort -n`, lines that do not start with a number sort as zero.
+fn leading_number(line: &str) -> f64 {
+    let line = line.trim_start();
+    let end = line
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && c == '-')))
+        .map(|(i, _)| i)
+        .unwrap_or(line.len());
+    line[..end].parse().unwrap_or(0.0)
+}
+
+fn title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut word_start = true;
+    for c in text.chars() {
+        if word_start {
+            result.extend(c.to_uppercase());
+        } else {
+            result.extend(c.to_lowercase());
+        }
+        word_start = !c.is_alphanumeric() && c != '\'';
+    }
+    result
+}
+
+fn camel_case(words: &[String]) -> String {
+    let mut result = String::new();
+    for (i, word) in words.iter().enumerate() {
+        let word = word.to_lowercase();
+        if i == 0 {
+            result.push_str(&word);
+        } else {
+            result.push_str(&title_case(&word));
+        }
+    }
+    result
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// '-' only joins words when converting to kebab-case; elsewhere "a-b" is a subtraction.
+fn is_kebab_identifier_char(c: char) -> bool {
+    is_identifier_char(c) || c == '-'
+}
+
+// Applies `convert` to every identifier in `text`, split into its words.
+fn map_identifiers(text: &str, is_identifier: fn(char) -> bool, convert: impl Fn(&[String]) -> String) -> String {
+    let flush = |identifier: &mut String, result: &mut String| {
+        let words = split_words(identifier);
+        if words.is_empty() {
+            result.push_str(identifier);
+        } else {
+            result.push_str(&convert(&words));
+        }
+        identifier.clear();
+    };
+
+    let mut result = String::with_capacity(text.len());
+    let mut identifier = String::new();
+    for c in text.chars() {
+        if is_identifier(c) {
+            identifier.push(c);
+        } else {
+            flush(&mut identifier, &mut result);
+            result.push(c);
+        }
+    }
+    flush(&mut identifier, &mut result);
+    result
+}
+
+// "parseHTTPRequest_v2" -> ["parse", "HTTP", "Request", "v2"]
+fn split_words(identifier: &str) -> Vec<String> {
+    let chars: Vec<char> = identifier.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' || c == '-' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+        let boundary = c.is_uppercase()
+            && prev.is_some_and(|p| {
+                p.is_lowercase() || p.is_ascii_digit() || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+            });
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_sorts() {
+        let input = lines(&["b", "10 c", "A", "2 a"]);
+        assert_eq!(Transform::SortLexical.apply_lines(&input), lines(&["10 c", "2 a", "A", "b"]));
+        assert_eq!(Transform::SortNumeric.apply_lines(&input), lines(&["b", "A", "2 a", "10 c"]));
+        assert_eq!(Transform::SortReverse.apply_lines(&input), lines(&["b", "A", "2 a", "10 c"]));
+        assert_eq!(Transform::SortCaseInsensitive.apply_lines(&input), lines(&["10 c", "2 a", "A", "b"]));
+    }
+
+    #[test]
+    fn test_remove_duplicates_and_reverse() {
+        let input = lines(&["a", "b", "a", "c", "b"]);
+        assert_eq!(Transform::RemoveDuplicates.apply_lines(&input), lines(&["a", "b", "c"]));
+        assert_eq!(Transform::ReverseLines.apply_lines(&input), lines(&["b", "c", "a", "b", "a"]));
+    }
+
+    #[test]
+    fn test_trim() {
+        let input = lines(&["  a  ", "\tb"]);
+        assert_eq!(Transform::Trim.apply_lines(&input), lines(&["a", "b"]));
+        assert_eq!(Transform::TrimTrailing.apply_lines(&input), lines(&["  a", "\tb"]));
+    }
+
+    #[test]
+    fn test_case_transforms() {
+        assert_eq!(Transform::Upper.apply_text("Hello world"), "HELLO WORLD");
+        assert_eq!(Transform::Lower.apply_text("Hello World"), "hello world");
+        assert_eq!(Transform::Title.apply_text("hELLO big-world, it's"), "Hello Big-World, It's");
+    }
+
+    #[test]
+    fn test_identifier_case_transforms() {
+        let text = "let parseHTTPRequest = user_id + max-value2;";
+        assert_eq!(Transform::Snake.apply_text(text), "let parse_http_request = user_id + max-value2;");
+        assert_eq!(Transform::Kebab.apply_text(text), "let parse-http-request = user-id + max-value2;");
+        assert_eq!(Transform::Camel.apply_text(text), "let parseHttpRequest = userId + max-value2;");
+        // Subtractions are left alone, only kebab-case treats '-' as part of a name.
+        assert_eq!(Transform::Snake.apply_text("a-b"), "a-b");
+        assert_eq!(Transform::Camel.apply_text("a-b"), "a-b");
+        assert_eq!(Transform::Kebab.apply_text("fooBar-baz_qux"), "foo-bar-baz-qux");
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words("parseHTTPRequest_v2"), vec!["parse", "HTTP", "Request", "v2"]);
+        assert_eq!(split_words("__"), Vec::<String>::new());
+    }
+}
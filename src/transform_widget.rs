@@ -0,0 +1,169 @@
+use crate::action_bar::ActionWidget;
+use crate::error_type::ErrorType;
+use crate::popup::popup_area;
+use crate::prompt::render_message;
+use crate::state::State;
+use crate::transform::{Transform, TRANSFORMS};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::widgets::{Block, Borders, Clear, HighlightSpacing, List, ListItem, ListState, StatefulWidget, Widget};
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+const SELECT_STYLE: Style = Style::new().bg(Color::White).fg(Color::Black);
+const ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz";
+
+#[derive(Debug)]
+pub struct TransformWidget<'a> {
+    pub state: Rc<RefCell<State<'a>>>,
+    pub list_state: ListState,
+    pub error: ErrorType,
+}
+
+impl<'a> TransformWidget<'a> {
+    pub fn new(state: Rc<RefCell<State<'a>>>) -> TransformWidget<'a> {
+        TransformWidget { state, list_state: ListState::default(), error: ErrorType::NONE }
+    }
+
+    fn select_by_letter(&mut self, letter: char) {
+        if let Some(index) = ALPHABET.chars().take(TRANSFORMS.len()).position(|c| c == letter) {
+            self.list_state.select(Some(index));
+        }
+    }
+
+    pub fn apply(&mut self, transform: Transform) {
+        let mut state = self.state.borrow_mut();
+        let index = state.current_buffer;
+        let Some(buffer) = state.buffer_list.get_mut(index) else {
+            self.error = ErrorType::NoBuffer;
+            return;
+        };
+
+        if transform.is_line_transform() {
+            buffer.transform_lines(|lines| transform.apply_lines(lines));
+        } else {
+            buffer.transform_text(|text| transform.apply_text(text));
+        }
+    }
+
+    fn render_content(&self, area: Rect, buf: &mut Buffer) {
+        let area = popup_area(area, 40, TRANSFORMS.len() as u16 + 2);
+        Clear.render(area, buf);
+
+        if self.error != ErrorType::NONE {
+            render_message(&self.error.to_string(), popup_area(area, 40, 3), buf);
+            return;
+        }
+
+        let block = Block::default()
+            .bold()
+            .title("Transform")
+            .borders(Borders::ALL);
+
+        let items: Vec<ListItem> = TRANSFORMS
+            .iter()
+            .zip(ALPHABET.chars())
+            .map(|(transform, letter)| ListItem::new(format!("{} {}", letter, transform.label())))
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECT_STYLE)
+            .highlight_symbol(">> ")
+            .highlight_spacing(HighlightSpacing::Always);
+
+        StatefulWidget::render(list, area, buf, &mut self.list_state.clone());
+    }
+}
+
+impl ActionWidget for TransformWidget<'_> {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.render_content(area, buf);
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
+        if self.error != ErrorType::NONE {
+            self.error = ErrorType::NONE;
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Left => self.list_state.select_previous(),
+            KeyCode::Down | KeyCode::Right => self.list_state.select_next(),
+            KeyCode::Home => self.list_state.select_first(),
+            KeyCode::End => self.list_state.select(Some(TRANSFORMS.len() - 1)),
+            KeyCode::Char(c) if c.is_ascii_alphabetic() => self.select_by_letter(c.to_ascii_lowercase()),
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn has_error(&self) -> bool {
+        self.error != ErrorType::NONE
+    }
+
+    fn process_action(&mut self) -> Result<bool, io::Error> {
+        let Some(transform) = self.list_state.selected().and_then(|index| TRANSFORMS.get(index)) else {
+            return Ok(false);
+        };
+        self.apply(*transform);
+        Ok(self.error == ErrorType::NONE)
+    }
+
+    fn init_action(&mut self) {}
+
+    fn reset(&mut self) {
+        self.error = ErrorType::NONE;
+        self.list_state.select(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::state_with_buffer;
+    use crossterm::event::KeyModifiers;
+
+    fn create_widget(lines: &[&str]) -> TransformWidget<'static> {
+        let state = state_with_buffer(lines);
+        TransformWidget::new(state)
+    }
+
+    #[test]
+    fn test_select_by_letter_and_apply() {
+        let mut widget = create_widget(&["b", "a"]);
+        widget.state.borrow_mut().buffer_list[0].input.select_all();
+
+        widget.handle_input(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE)).unwrap();
+        assert_eq!(widget.list_state.selected(), Some(0));
+        assert!(widget.process_action().unwrap());
+        assert_eq!(widget.state.borrow().buffer_list[0].input.lines(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_text_transform_on_current_line() {
+        let mut widget = create_widget(&["user_id", "keep_me"]);
+        let camel = TRANSFORMS.iter().position(|t| *t == Transform::Camel).unwrap();
+        widget.list_state.select(Some(camel));
+
+        assert!(widget.process_action().unwrap());
+        assert_eq!(widget.state.borrow().buffer_list[0].input.lines(), vec!["userId", "keep_me"]);
+    }
+
+    #[test]
+    fn test_nothing_selected() {
+        let mut widget = create_widget(&["a"]);
+        assert!(!widget.process_action().unwrap());
+    }
+
+    #[test]
+    fn test_no_buffer() {
+        let mut widget = TransformWidget::new(Rc::new(RefCell::new(State::default())));
+        widget.list_state.select(Some(0));
+        assert!(!widget.process_action().unwrap());
+        assert_eq!(widget.error, ErrorType::NoBuffer);
+    }
+}
@@ -0,0 +1,836 @@
+use crate::buffer::Buffer;
+use crate::completion::is_word_char;
+use crate::indent::leading_whitespace;
+use crate::snippets::{end_of, Pos};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Mode {
+    #[default]
+    Normal,
+    Insert,
+    Visual,
+    VisualLine,
+}
+
+impl Mode {
+    pub fn is_visual(self) -> bool {
+        matches!(self, Mode::Visual | Mode::VisualLine)
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mode::Normal => write!(f, "NORMAL"),
+            Mode::Insert => write!(f, "INSERT"),
+            Mode::Visual => write!(f, "VISUAL"),
+            Mode::VisualLine => write!(f, "VISUAL LINE"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordForward,
+    WordBackward,
+    WordEnd,
+    LineEnd,
+    LineStart,
+    FirstLine,
+    LastLine,
+}
+
+impl Motion {
+    fn is_linewise(self) -> bool {
+        matches!(self, Motion::Up | Motion::Down | Motion::FirstLine | Motion::LastLine)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextObject {
+    Word,
+    Quote,
+    Paragraph,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Motion(Motion),
+    Object(TextObject),
+    // dd, cc and yy
+    Lines,
+    Selection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InsertAt {
+    Cursor,
+    After,
+    LineStart,
+    LineEnd,
+    LineBelow,
+    LineAbove,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Move(Motion),
+    Operate(Operator, Target),
+    Insert(InsertAt),
+    DeleteChar,
+    Put { before: bool },
+    Undo,
+    Repeat,
+    Visual(Mode),
+    Select(TextObject),
+}
+
+impl Command {
+    // Changes are what `.` repeats.
+    fn is_change(self) -> bool {
+        matches!(
+            self,
+            Command::Operate(Operator::Delete | Operator::Change, _) | Command::Insert(_) | Command::DeleteChar | Command::Put { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Parse {
+    Done(Option<usize>, Command),
+    Pending,
+    Invalid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Span {
+    Chars(Pos, Pos),
+    Lines(usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Register {
+    pub text: String,
+    pub linewise: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Handled,
+    Ignored,
+    // Keys of the last change, to be fed through the editor again.
+    Replay(Vec<KeyEvent>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CharClass {
+    Space,
+    Word,
+    Punctuation,
+}
+
+fn class_of(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Space
+    } else if is_word_char(c) {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+fn line_len(lines: &[String], row: usize) -> usize {
+    lines[row].chars().count()
+}
+
+// The end of a line counts as a space.
+fn class_at(lines: &[String], (row, col): Pos) -> CharClass {
+    lines[row].chars().nth(col).map_or(CharClass::Space, class_of)
+}
+
+fn is_empty_line(lines: &[String], (row, col): Pos) -> bool {
+    col == 0 && lines[row].is_empty()
+}
+
+fn step_forward(lines: &[String], (row, col): Pos) -> Option<Pos> {
+    if col < line_len(lines, row) {
+        Some((row, col + 1))
+    } else if row + 1 < lines.len() {
+        Some((row + 1, 0))
+    } else {
+        None
+    }
+}
+
+fn step_back(lines: &[String], (row, col): Pos) -> Option<Pos> {
+    if col > 0 {
+        Some((row, col - 1))
+    } else if row > 0 {
+        Some((row - 1, line_len(lines, row - 1)))
+    } else {
+        None
+    }
+}
+
+fn first_non_blank(lines: &[String], row: usize) -> Pos {
+    (row, leading_whitespace(&lines[row]).chars().count())
+}
+
+fn next_word_start(lines: &[String], pos: Pos) -> Pos {
+    let mut pos = pos;
+    while let Some(next) = step_forward(lines, pos) {
+        let previous = class_at(lines, pos);
+        pos = next;
+        let class = class_at(lines, pos);
+        if is_empty_line(lines, pos) || (class != CharClass::Space && class != previous) {
+            return pos;
+        }
+    }
+    pos
+}
+
+fn previous_word_start(lines: &[String], pos: Pos) -> Pos {
+    let mut pos = pos;
+    loop {
+        let Some(previous) = step_back(lines, pos) else {
+            return pos;
+        };
+        pos = previous;
+        if is_empty_line(lines, pos) {
+            return pos;
+        }
+        if class_at(lines, pos) != CharClass::Space {
+            break;
+        }
+    }
+    let class = class_at(lines, pos);
+    while let Some(previous) = step_back(lines, pos).filter(|previous| previous.0 == pos.0 && class_at(lines, *previous) == class) {
+        pos = previous;
+    }
+    pos
+}
+
+// Last character of the current word, staying on the line.
+fn current_word_end(lines: &[String], pos: Pos) -> Pos {
+    let class = class_at(lines, pos);
+    let mut pos = pos;
+    while let Some(next) = step_forward(lines, pos).filter(|next| next.0 == pos.0 && class_at(lines, *next) == class) {
+        pos = next;
+    }
+    pos
+}
+
+fn word_end(lines: &[String], pos: Pos) -> Pos {
+    let mut pos = pos;
+    loop {
+        let Some(next) = step_forward(lines, pos) else {
+            return pos;
+        };
+        pos = next;
+        if class_at(lines, pos) != CharClass::Space {
+            break;
+        }
+    }
+    current_word_end(lines, pos)
+}
+
+pub fn motion_target(lines: &[String], cursor: Pos, motion: Motion, count: Option<usize>) -> Pos {
+    let n = count.unwrap_or(1);
+    let (row, col) = cursor;
+    let last_row = lines.len() - 1;
+    let repeat = |step: fn(&[String], Pos) -> Pos| (0..n).fold(cursor, |pos, _| step(lines, pos));
+    match motion {
+        Motion::Left => (row, col.saturating_sub(n)),
+        Motion::Right => (row, (col + n).min(line_len(lines, row))),
+        Motion::Up => {
+            let row = row.saturating_sub(n);
+            (row, col.min(line_len(lines, row)))
+        }
+        Motion::Down => {
+            let row = (row + n).min(last_row);
+            (row, col.min(line_len(lines, row)))
+        }
+        Motion::WordForward => repeat(next_word_start),
+        Motion::WordBackward => repeat(previous_word_start),
+        Motion::WordEnd => repeat(word_end),
+        Motion::LineEnd => {
+            let row = (row + n - 1).min(last_row);
+            (row, line_len(lines, row))
+        }
+        Motion::LineStart => (row, 0),
+        Motion::FirstLine => first_non_blank(lines, count.map_or(0, |n| n - 1).min(last_row)),
+        Motion::LastLine => first_non_blank(lines, count.map_or(last_row, |n| n - 1).min(last_row)),
+    }
+}
+
+// The text covered by an operator with a motion, following vi's special cases for w.
+pub fn motion_span(lines: &[String], cursor: Pos, motion: Motion, count: Option<usize>, operator: Operator) -> Span {
+    let target = motion_target(lines, cursor, motion, count);
+    if motion.is_linewise() {
+        return Span::Lines(cursor.0.min(target.0), cursor.0.max(target.0));
+    }
+    let after = |(row, col): Pos| (row, (col + 1).min(line_len(lines, row)));
+    match motion {
+        // cw changes to the end of the word, like ce.
+        Motion::WordForward if operator == Operator::Change && class_at(lines, cursor) != CharClass::Space => {
+            let n = count.unwrap_or(1);
+            let end = (1..n).fold(current_word_end(lines, cursor), |pos, _| word_end(lines, pos));
+            Span::Chars(cursor, after(end))
+        }
+        // dw on the last word of a line stops at the end of the line.
+        Motion::WordForward if target.0 > cursor.0 => Span::Chars(cursor, (cursor.0, line_len(lines, cursor.0).max(cursor.1))),
+        Motion::WordEnd => Span::Chars(cursor, after(target)),
+        _ => Span::Chars(cursor.min(target), cursor.max(target)),
+    }
+}
+
+pub fn text_object(lines: &[String], (row, col): Pos, object: TextObject) -> Option<Span> {
+    let chars: Vec<char> = lines[row].chars().collect();
+    match object {
+        TextObject::Word => {
+            let col = col.min(chars.len().checked_sub(1)?);
+            let class = class_of(chars[col]);
+            let start = chars[..col].iter().rposition(|&c| class_of(c) != class).map_or(0, |i| i + 1);
+            let end = chars[col..].iter().position(|&c| class_of(c) != class).map_or(chars.len(), |i| col + i);
+            Some(Span::Chars((row, start), (row, end)))
+        }
+        TextObject::Quote => {
+            let quotes: Vec<usize> = (0..chars.len()).filter(|&i| chars[i] == '"' && (i == 0 || chars[i - 1] != '\\')).collect();
+            let (open, close) = quotes
+                .chunks_exact(2)
+                .map(|pair| (pair[0], pair[1]))
+                .find(|&(_, close)| col <= close)?;
+            Some(Span::Chars((row, open + 1), (row, close)))
+        }
+        TextObject::Paragraph => {
+            let blank = |row: usize| lines[row].trim().is_empty();
+            let kind = blank(row);
+            let first = (0..row).rev().take_while(|&row| blank(row) == kind).last().unwrap_or(row);
+            let last = (row + 1..lines.len()).take_while(|&row| blank(row) == kind).last().unwrap_or(row);
+            Some(Span::Lines(first, last))
+        }
+    }
+}
+
+// Leading count, which cannot start with 0 since 0 is a motion.
+fn split_count(keys: &[char]) -> (Option<usize>, &[char]) {
+    let digits = keys.iter().enumerate().take_while(|&(i, c)| c.is_ascii_digit() && (i > 0 || *c != '0')).count();
+    let count = keys[..digits].iter().collect::<String>().parse().ok();
+    (count, &keys[digits..])
+}
+
+fn parse_motion(c: char) -> Option<Motion> {
+    match c {
+        'h' => Some(Motion::Left),
+        'l' => Some(Motion::Right),
+        'k' => Some(Motion::Up),
+        'j' => Some(Motion::Down),
+        'w' => Some(Motion::WordForward),
+        'b' => Some(Motion::WordBackward),
+        'e' => Some(Motion::WordEnd),
+        '$' => Some(Motion::LineEnd),
+        '0' => Some(Motion::LineStart),
+        'G' => Some(Motion::LastLine),
+        _ => None,
+    }
+}
+
+fn parse_object(c: char) -> Option<TextObject> {
+    match c {
+        'w' => Some(TextObject::Word),
+        '"' => Some(TextObject::Quote),
+        'p' => Some(TextObject::Paragraph),
+        _ => None,
+    }
+}
+
+fn parse_operator(c: char) -> Option<Operator> {
+    match c {
+        'd' => Some(Operator::Delete),
+        'c' => Some(Operator::Change),
+        'y' => Some(Operator::Yank),
+        _ => None,
+    }
+}
+
+fn parse_target(keys: &[char], operator_key: char) -> Option<Option<Target>> {
+    let target = match keys {
+        [] | ['i'] | ['g'] => return Some(None),
+        ['g', 'g'] => Target::Motion(Motion::FirstLine),
+        ['i', object] => Target::Object(parse_object(*object)?),
+        [c] if *c == operator_key => Target::Lines,
+        [c] => Target::Motion(parse_motion(*c)?),
+        _ => return None,
+    };
+    Some(Some(target))
+}
+
+// Parses the keys typed so far in normal or visual mode.
+pub fn parse(keys: &[char], visual: bool) -> Parse {
+    let (count, rest) = split_count(keys);
+    let done = |command| Parse::Done(count, command);
+    let command = match rest {
+        [] | ['g'] => return Parse::Pending,
+        ['i'] if visual => return Parse::Pending,
+        ['g', 'g'] => Command::Move(Motion::FirstLine),
+        ['i', object] if visual => match parse_object(*object) {
+            Some(object) => Command::Select(object),
+            None => return Parse::Invalid,
+        },
+        ['x'] if visual => Command::Operate(Operator::Delete, Target::Selection),
+        [c] if visual && parse_operator(*c).is_some() => Command::Operate(parse_operator(*c).unwrap(), Target::Selection),
+        [c, after @ ..] if parse_operator(*c).is_some() => {
+            let (second_count, after) = split_count(after);
+            return match parse_target(after, *c) {
+                Some(Some(target)) => {
+                    let count = match (count, second_count) {
+                        (None, None) => None,
+                        (first, second) => Some(first.unwrap_or(1) * second.unwrap_or(1)),
+                    };
+                    Parse::Done(count, Command::Operate(parse_operator(*c).unwrap(), target))
+                }
+                Some(None) => Parse::Pending,
+                None => Parse::Invalid,
+            };
+        }
+        // Visual mode only moves, selects and operates on the selection.
+        ['a' | 'I' | 'A' | 'o' | 'O' | 'p' | 'P' | 'u' | '.'] if visual => return Parse::Invalid,
+        [c] => match c {
+            'i' => Command::Insert(InsertAt::Cursor),
+            'a' => Command::Insert(InsertAt::After),
+            'I' => Command::Insert(InsertAt::LineStart),
+            'A' => Command::Insert(InsertAt::LineEnd),
+            'o' => Command::Insert(InsertAt::LineBelow),
+            'O' => Command::Insert(InsertAt::LineAbove),
+            'x' => Command::DeleteChar,
+            'p' => Command::Put { before: false },
+            'P' => Command::Put { before: true },
+            'u' => Command::Undo,
+            '.' => Command::Repeat,
+            'v' => Command::Visual(Mode::Visual),
+            'V' => Command::Visual(Mode::VisualLine),
+            c => match parse_motion(*c) {
+                Some(motion) => Command::Move(motion),
+                None => return Parse::Invalid,
+            },
+        },
+        _ => return Parse::Invalid,
+    };
+    done(command)
+}
+
+// Arrow and navigation keys work like their vi motions.
+fn command_char(key: &KeyEvent) -> Option<char> {
+    if key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+        return None;
+    }
+    match key.code {
+        KeyCode::Char(c) => Some(c),
+        KeyCode::Left | KeyCode::Backspace => Some('h'),
+        KeyCode::Right => Some('l'),
+        KeyCode::Up => Some('k'),
+        KeyCode::Down => Some('j'),
+        KeyCode::Home => Some('0'),
+        KeyCode::End => Some('$'),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Vi {
+    pub mode: Mode,
+    pub register: Register,
+    pending: Vec<KeyEvent>,
+    last_change: Vec<KeyEvent>,
+    // Keys of a change still being typed in insert mode.
+    insert_keys: Option<Vec<KeyEvent>>,
+    visual_anchor: Pos,
+    visual_cursor: Pos,
+}
+
+impl Vi {
+    pub fn handle_key(&mut self, key: KeyEvent, buffer: &mut Buffer) -> Outcome {
+        if self.mode == Mode::Insert {
+            return self.handle_insert_key(key, buffer);
+        }
+
+        if command_char(&key).is_none() {
+            return match key.code {
+                KeyCode::Esc => {
+                    self.pending.clear();
+                    if self.mode.is_visual() {
+                        self.leave_visual(buffer, self.visual_cursor);
+                    }
+                    Outcome::Handled
+                }
+                _ if key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => Outcome::Ignored,
+                KeyCode::PageUp | KeyCode::PageDown => Outcome::Ignored,
+                _ => Outcome::Handled,
+            };
+        }
+
+        self.pending.push(key);
+        let keys: Vec<char> = self.pending.iter().filter_map(command_char).collect();
+        let visual = self.mode.is_visual();
+        match parse(&keys, visual) {
+            Parse::Pending => Outcome::Handled,
+            Parse::Invalid => {
+                self.pending.clear();
+                Outcome::Handled
+            }
+            Parse::Done(count, command) => {
+                let keys = std::mem::take(&mut self.pending);
+                let outcome = self.execute(command, count, buffer);
+                if command.is_change() && !visual {
+                    if self.mode == Mode::Insert {
+                        self.insert_keys = Some(keys);
+                    } else {
+                        self.last_change = keys;
+                    }
+                }
+                outcome
+            }
+        }
+    }
+
+    fn handle_insert_key(&mut self, key: KeyEvent, buffer: &mut Buffer) -> Outcome {
+        if key.code != KeyCode::Esc {
+            if let Some(keys) = &mut self.insert_keys {
+                keys.push(key);
+            }
+            return Outcome::Ignored;
+        }
+        if let Some(mut keys) = self.insert_keys.take() {
+            keys.push(key);
+            self.last_change = keys;
+        }
+        self.mode = Mode::Normal;
+        let (row, col) = buffer.input.cursor();
+        buffer.move_cursor_to(row, col.saturating_sub(1));
+        Outcome::Handled
+    }
+
+    fn execute(&mut self, command: Command, count: Option<usize>, buffer: &mut Buffer) -> Outcome {
+        let n = count.unwrap_or(1);
+        let cursor = if self.mode.is_visual() { self.visual_cursor } else { buffer.input.cursor() };
+        let lines = buffer.input.lines();
+        let (row, col) = cursor;
+        let len = line_len(lines, row);
+
+        match command {
+            Command::Move(motion) => {
+                let target = motion_target(lines, cursor, motion, count);
+                if self.mode.is_visual() {
+                    self.visual_cursor = target;
+                } else {
+                    buffer.move_cursor_to(target.0, target.1);
+                }
+            }
+            Command::Operate(operator, target) => {
+                let span = match target {
+                    Target::Motion(motion) => motion_span(lines, cursor, motion, count, operator),
+                    Target::Object(object) => match text_object(lines, cursor, object) {
+                        Some(span) => span,
+                        None => return Outcome::Handled,
+                    },
+                    Target::Lines => Span::Lines(row, (row + n - 1).min(lines.len() - 1)),
+                    Target::Selection => self.selection_span(lines),
+                };
+                if self.mode.is_visual() {
+                    self.leave_visual(buffer, cursor);
+                }
+                self.operate(operator, span, buffer);
+            }
+            Command::Insert(at) => {
+                match at {
+                    InsertAt::Cursor => (),
+                    InsertAt::After => buffer.move_cursor_to(row, (col + 1).min(len)),
+                    InsertAt::LineStart => buffer.move_cursor_to(row, first_non_blank(lines, row).1),
+                    InsertAt::LineEnd => buffer.move_cursor_to(row, len),
+                    InsertAt::LineBelow => {
+                        buffer.move_cursor_to(row, len);
+                        buffer.insert_newline_with_indent();
+                    }
+                    InsertAt::LineAbove => buffer.insert_line_above(),
+                }
+                self.mode = Mode::Insert;
+            }
+            Command::DeleteChar if len > 0 => self.operate(Operator::Delete, Span::Chars(cursor, (row, (col + n).min(len))), buffer),
+            Command::DeleteChar => (),
+            Command::Put { before } => self.put(before, n, buffer),
+            Command::Undo => (0..n).for_each(|_| buffer.undo()),
+            Command::Repeat => {
+                let keys: Vec<KeyEvent> = (0..n).flat_map(|_| self.last_change.iter().copied()).collect();
+                return Outcome::Replay(keys);
+            }
+            Command::Visual(mode) if self.mode == mode => self.leave_visual(buffer, cursor),
+            Command::Visual(mode) => {
+                if !self.mode.is_visual() {
+                    self.visual_anchor = cursor;
+                    self.visual_cursor = cursor;
+                }
+                self.mode = mode;
+            }
+            Command::Select(object) => match text_object(lines, cursor, object) {
+                Some(Span::Chars(start, end)) if end > start => {
+                    self.visual_anchor = start;
+                    self.visual_cursor = (end.0, end.1 - 1);
+                }
+                Some(Span::Lines(first, last)) => {
+                    self.mode = Mode::VisualLine;
+                    self.visual_anchor = (first, 0);
+                    self.visual_cursor = (last, 0);
+                }
+                _ => (),
+            },
+        }
+
+        if self.mode.is_visual() {
+            self.show_selection(buffer);
+        } else if self.mode == Mode::Normal {
+            clamp_cursor(buffer);
+        }
+        Outcome::Handled
+    }
+
+    fn selection_span(&self, lines: &[String]) -> Span {
+        let (start, end) = (self.visual_anchor.min(self.visual_cursor), self.visual_anchor.max(self.visual_cursor));
+        match self.mode {
+            Mode::VisualLine => Span::Lines(start.0, end.0),
+            _ => Span::Chars(start, (end.0, (end.1 + 1).min(line_len(lines, end.0)))),
+        }
+    }
+
+    // The selection includes the character under the cursor, or whole lines in visual line mode.
+    fn show_selection(&self, buffer: &mut Buffer) {
+        let lines = buffer.input.lines();
+        let (anchor, cursor) = match self.selection_span(lines) {
+            Span::Lines(first, last) if self.visual_cursor.0 < self.visual_anchor.0 => ((last, line_len(lines, last)), (first, 0)),
+            Span::Lines(first, last) => ((first, 0), (last, line_len(lines, last))),
+            Span::Chars(start, end) if self.visual_cursor < self.visual_anchor => (end, start),
+            Span::Chars(start, end) => (start, end),
+        };
+        buffer.restore_selection(Some(anchor), cursor);
+    }
+
+    fn leave_visual(&mut self, buffer: &mut Buffer, cursor: Pos) {
+        self.mode = Mode::Normal;
+        buffer.restore_selection(None, cursor);
+    }
+
+    fn operate(&mut self, operator: Operator, span: Span, buffer: &mut Buffer) {
+        let lines = buffer.input.lines();
+        match span {
+            Span::Chars(start, end) => {
+                self.register = Register { text: buffer.text_in_range(start, end), linewise: false };
+                match operator {
+                    Operator::Yank => buffer.move_cursor_to(start.0, start.1),
+                    Operator::Delete | Operator::Change => buffer.replace_range(start, end, "", start),
+                }
+            }
+            Span::Lines(first, last) => {
+                self.register = Register { text: lines[first..=last].join("\n"), linewise: true };
+                let last_len = line_len(lines, last);
+                match operator {
+                    Operator::Yank => buffer.move_cursor_to(first, 0),
+                    Operator::Change => {
+                        let indent = leading_whitespace(&lines[first]).to_string();
+                        let cursor = (first, indent.chars().count());
+                        buffer.replace_range((first, 0), (last, last_len), &indent, cursor);
+                    }
+                    Operator::Delete if last + 1 < lines.len() => buffer.replace_range((first, 0), (last + 1, 0), "", (first, 0)),
+                    Operator::Delete if first > 0 => {
+                        let end = (first - 1, line_len(lines, first - 1));
+                        buffer.replace_range(end, (last, last_len), "", (first - 1, 0));
+                    }
+                    Operator::Delete => buffer.replace_range((0, 0), (last, last_len), "", (0, 0)),
+                }
+                if operator == Operator::Delete {
+                    let row = buffer.input.cursor().0;
+                    let (row, col) = first_non_blank(buffer.input.lines(), row);
+                    buffer.move_cursor_to(row, col);
+                }
+            }
+        }
+        if operator == Operator::Change {
+            self.mode = Mode::Insert;
+        }
+    }
+
+    fn put(&mut self, before: bool, count: usize, buffer: &mut Buffer) {
+        if self.register.text.is_empty() && !self.register.linewise {
+            return;
+        }
+        let (row, col) = buffer.input.cursor();
+        let len = line_len(buffer.input.lines(), row);
+        let text = vec![self.register.text.as_str(); count];
+        if self.register.linewise {
+            let text = text.join("\n");
+            if before {
+                buffer.replace_range((row, 0), (row, 0), &format!("{}\n", text), (row, 0));
+            } else {
+                buffer.replace_range((row, len), (row, len), &format!("\n{}", text), (row + 1, 0));
+            }
+        } else {
+            let text = text.concat();
+            let at = if before { (row, col) } else { (row, (col + 1).min(len)) };
+            let end = end_of(at, &text);
+            buffer.replace_range(at, at, &text, (end.0, end.1.saturating_sub(1)));
+        }
+    }
+}
+
+// The cursor rests on a character in normal mode, not past the end of the line.
+fn clamp_cursor(buffer: &mut Buffer) {
+    let (row, col) = buffer.input.cursor();
+    let len = line_len(buffer.input.lines(), row);
+    if len > 0 && col >= len {
+        buffer.move_cursor_to(row, len - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui_textarea::TextArea;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn chars(keys: &str) -> Vec<char> {
+        keys.chars().collect()
+    }
+
+    // Feeds `keys` to a buffer holding `text`, as plain key presses with "\x1b" for Esc.
+    fn run(text: &[&str], cursor: Pos, keys: &str) -> (Vec<String>, Pos, Vi) {
+        let mut buffer = Buffer::new(TextArea::new(lines(text)), None);
+        buffer.move_cursor_to(cursor.0, cursor.1);
+        let mut vi = Vi::default();
+        let mut queue: Vec<KeyEvent> = keys
+            .chars()
+            .map(|c| if c == '\x1b' { KeyEvent::from(KeyCode::Esc) } else { KeyEvent::from(KeyCode::Char(c)) })
+            .collect();
+        queue.reverse();
+        while let Some(key) = queue.pop() {
+            match vi.handle_key(key, &mut buffer) {
+                Outcome::Ignored => {
+                    buffer.handle_key(key);
+                }
+                Outcome::Replay(keys) => queue.extend(keys.into_iter().rev()),
+                Outcome::Handled => (),
+            }
+        }
+        (buffer.input.lines().to_vec(), buffer.input.cursor(), vi)
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(&chars("3"), false), Parse::Pending);
+        assert_eq!(parse(&chars("0"), false), Parse::Done(None, Command::Move(Motion::LineStart)));
+        assert_eq!(parse(&chars("10j"), false), Parse::Done(Some(10), Command::Move(Motion::Down)));
+        assert_eq!(parse(&chars("2d3w"), false), Parse::Done(Some(6), Command::Operate(Operator::Delete, Target::Motion(Motion::WordForward))));
+        assert_eq!(parse(&chars("dd"), false), Parse::Done(None, Command::Operate(Operator::Delete, Target::Lines)));
+        assert_eq!(parse(&chars("ci"), false), Parse::Pending);
+        assert_eq!(parse(&chars("ci\""), false), Parse::Done(None, Command::Operate(Operator::Change, Target::Object(TextObject::Quote))));
+        assert_eq!(parse(&chars("ygg"), false), Parse::Done(None, Command::Operate(Operator::Yank, Target::Motion(Motion::FirstLine))));
+        assert_eq!(parse(&chars("dz"), false), Parse::Invalid);
+        assert_eq!(parse(&chars("d"), true), Parse::Done(None, Command::Operate(Operator::Delete, Target::Selection)));
+        assert_eq!(parse(&chars("a"), true), Parse::Invalid);
+        assert_eq!(parse(&chars("p"), true), Parse::Invalid);
+        assert_eq!(parse(&chars("u"), true), Parse::Invalid);
+    }
+
+    #[test]
+    fn test_word_motions() {
+        let text = lines(&["foo.bar  baz", "", "qux"]);
+        assert_eq!(motion_target(&text, (0, 0), Motion::WordForward, None), (0, 3));
+        assert_eq!(motion_target(&text, (0, 0), Motion::WordForward, Some(3)), (0, 9));
+        assert_eq!(motion_target(&text, (0, 9), Motion::WordForward, None), (1, 0));
+        assert_eq!(motion_target(&text, (2, 0), Motion::WordBackward, None), (1, 0));
+        assert_eq!(motion_target(&text, (0, 9), Motion::WordBackward, None), (0, 4));
+        assert_eq!(motion_target(&text, (0, 0), Motion::WordEnd, None), (0, 2));
+        assert_eq!(motion_target(&text, (0, 9), Motion::WordEnd, None), (0, 11));
+        assert_eq!(motion_target(&text, (0, 11), Motion::WordEnd, None), (2, 2));
+    }
+
+    #[test]
+    fn test_text_objects() {
+        let text = lines(&["say \"hi there\" now", "", "a", "b"]);
+        assert_eq!(text_object(&text, (0, 1), TextObject::Word), Some(Span::Chars((0, 0), (0, 3))));
+        assert_eq!(text_object(&text, (0, 7), TextObject::Quote), Some(Span::Chars((0, 5), (0, 13))));
+        assert_eq!(text_object(&text, (0, 0), TextObject::Quote), Some(Span::Chars((0, 5), (0, 13))));
+        assert_eq!(text_object(&text, (3, 0), TextObject::Paragraph), Some(Span::Lines(2, 3)));
+    }
+
+    #[test]
+    fn test_operators_with_motions() {
+        assert_eq!(run(&["one two three"], (0, 0), "dw").0, lines(&["two three"]));
+        assert_eq!(run(&["one two three"], (0, 0), "2dw").0, lines(&["three"]));
+        assert_eq!(run(&["one two", "x"], (0, 4), "dw").0, lines(&["one ", "x"]));
+        assert_eq!(run(&["one two"], (0, 4), "d0").0, lines(&["two"]));
+        assert_eq!(run(&["one two"], (0, 1), "d$").0, lines(&["o"]));
+        assert_eq!(run(&["a", "b", "c"], (1, 0), "dG").0, lines(&["a"]));
+        assert_eq!(run(&["a", "b", "c"], (1, 0), "dgg").0, lines(&["c"]));
+        assert_eq!(run(&["a", "b", "c"], (0, 0), "2dd").0, lines(&["c"]));
+        assert_eq!(run(&["one two"], (0, 0), "cwxy\x1b").0, lines(&["xy two"]));
+    }
+
+    #[test]
+    fn test_change_text_objects_and_repeat() {
+        let (text, _, _) = run(&["x = \"old\"", "y = \"old\""], (0, 0), "ci\"new\x1bj0.");
+        assert_eq!(text, lines(&["x = \"new\"", "y = \"new\""]));
+
+        let (text, cursor, _) = run(&["a b c d"], (0, 0), "dw..");
+        assert_eq!(text, lines(&["d"]));
+        assert_eq!(cursor, (0, 0));
+
+        assert_eq!(run(&["p1", "p1", "", "p2"], (0, 0), "dip").0, lines(&["", "p2"]));
+        assert_eq!(run(&["foo bar"], (0, 5), "diw").0, lines(&["foo "]));
+    }
+
+    #[test]
+    fn test_yank_put_and_undo() {
+        assert_eq!(run(&["a", "b"], (0, 0), "yyp").0, lines(&["a", "a", "b"]));
+        assert_eq!(run(&["a", "b"], (1, 0), "yyP").0, lines(&["a", "b", "b"]));
+        assert_eq!(run(&["ab"], (0, 0), "xp").0, lines(&["ba"]));
+        assert_eq!(run(&["ab"], (0, 0), "xu").0, lines(&["ab"]));
+    }
+
+    #[test]
+    fn test_visual_modes() {
+        let (text, _, vi) = run(&["one two three"], (0, 4), "vey");
+        assert_eq!(vi.register.text, "two");
+        assert_eq!(vi.mode, Mode::Normal);
+        assert_eq!(text, lines(&["one two three"]));
+
+        assert_eq!(run(&["a", "b", "c"], (0, 0), "Vjd").0, lines(&["c"]));
+        assert_eq!(run(&["one two"], (0, 0), "viwcx\x1b").0, lines(&["x two"]));
+        assert_eq!(run(&["one", "two"], (0, 1), "vj\x1b").2.mode, Mode::Normal);
+
+        let (text, _, vi) = run(&["ab"], (0, 0), "vax");
+        assert_eq!(text, lines(&["b"]));
+        assert_eq!(vi.mode, Mode::Normal);
+    }
+
+    #[test]
+    fn test_insert_commands_and_counts() {
+        assert_eq!(run(&["ab"], (0, 0), "ax\x1bAy\x1bIz\x1b").0, lines(&["zaxby"]));
+        assert_eq!(run(&["  a"], (0, 0), "ob\x1bOc\x1b").0, lines(&["  a", "c", "  b"]));
+        assert_eq!(run(&["a", "b", "c", "d"], (0, 0), "2jx").0, lines(&["a", "b", "", "d"]));
+        assert_eq!(run(&["abc"], (0, 0), "$").1, (0, 2));
+    }
+}
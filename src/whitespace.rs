@@ -0,0 +1,115 @@
+use std::fmt;
+
+// What is cleaned up when a buffer is saved; nothing unless the config or
+// .editorconfig turns it on.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Cleanup {
+    pub trim_trailing: bool,
+    pub final_newline: bool,
+    pub collapse_blank_lines: bool,
+}
+
+impl Cleanup {
+    // The configured cleanup, less what would damage files of this type.
+    pub fn for_file_type(self, file_type: Option<&str>) -> Cleanup {
+        match file_type {
+            // Two trailing spaces are a line break in Markdown.
+            Some("md" | "markdown") => Cleanup { trim_trailing: false, ..self },
+            // Whitespace is content in patches.
+            Some("diff" | "patch") => Cleanup::default(),
+            _ => self,
+        }
+    }
+
+    // The buffer lines after cleaning; the final newline is added when writing.
+    pub fn apply(&self, lines: &[String]) -> Vec<String> {
+        let mut cleaned: Vec<String> = if self.trim_trailing {
+            lines.iter().map(|line| line.trim_end().to_string()).collect()
+        } else {
+            lines.to_vec()
+        };
+        if self.collapse_blank_lines {
+            let content_end = cleaned.iter().rposition(|line| !line.trim().is_empty()).map_or(0, |row| row + 1);
+            cleaned.truncate(content_end.max(1));
+        }
+        cleaned
+    }
+
+    pub fn file_content(&self, lines: &[String]) -> String {
+        let mut content = lines.join("\n");
+        // An empty buffer stays an empty file.
+        if self.final_newline && (lines.len() > 1 || !content.is_empty()) {
+            content.push('\n');
+        }
+        content
+    }
+
+    // One line per change that saving would make.
+    pub fn preview(&self, lines: &[String]) -> Vec<String> {
+        let cleaned = self.apply(lines);
+        let mut changes: Vec<String> = (0..cleaned.len())
+            .filter(|&row| cleaned[row] != lines[row])
+            .map(|row| format!("{}: remove {} trailing whitespace characters", row + 1, lines[row].chars().count() - cleaned[row].chars().count()))
+            .collect();
+        let blank = lines.len() - cleaned.len();
+        if blank > 0 {
+            changes.push(format!("{}-{}: remove {} trailing blank lines", cleaned.len() + 1, lines.len(), blank));
+        }
+        if self.file_content(&cleaned) != cleaned.join("\n") {
+            changes.push(format!("{}: add final newline", cleaned.len()));
+        }
+        changes
+    }
+}
+
+impl fmt::Display for Cleanup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        write!(
+            f,
+            "t: trailing whitespace {} | n: final newline {} | b: blank lines {}",
+            on_off(self.trim_trailing),
+            on_off(self.final_newline),
+            on_off(self.collapse_blank_lines)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: Cleanup = Cleanup { trim_trailing: true, final_newline: true, collapse_blank_lines: true };
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_apply() {
+        let text = lines(&["a  ", "\tb\t", "", "  ", ""]);
+        assert_eq!(ALL.apply(&text), lines(&["a", "\tb"]));
+        assert_eq!(ALL.for_file_type(Some("md")).apply(&text), lines(&["a  ", "\tb\t"]));
+        assert_eq!(Cleanup::default().apply(&text), text);
+        assert_eq!(ALL.apply(&lines(&["", " "])), lines(&[""]));
+    }
+
+    #[test]
+    fn test_file_content() {
+        assert_eq!(ALL.file_content(&lines(&["a", "b"])), "a\nb\n");
+        assert_eq!(ALL.file_content(&lines(&["a", ""])), "a\n\n");
+        assert_eq!(ALL.file_content(&lines(&[""])), "");
+        assert_eq!(Cleanup::default().file_content(&lines(&["a", "b"])), "a\nb");
+    }
+
+    #[test]
+    fn test_preview() {
+        let text = lines(&["a\u{a0}\u{3000}", "b", "", ""]);
+        assert_eq!(
+            ALL.preview(&text),
+            vec!["1: remove 2 trailing whitespace characters", "3-4: remove 2 trailing blank lines", "2: add final newline"]
+        );
+        assert!(ALL.for_file_type(Some("patch")).preview(&text).is_empty());
+        assert!(Cleanup::default().preview(&text).is_empty());
+    }
+}
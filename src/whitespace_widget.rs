@@ -0,0 +1,147 @@
+use crate::action_bar::ActionWidget;
+use crate::error_type::ErrorType;
+use crate::popup::popup_area;
+use crate::prompt::render_message;
+use crate::state::State;
+use crate::whitespace::Cleanup;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+const MAX_VISIBLE: u16 = 12;
+const NOTHING_TO_CLEAN: &str = "Nothing to clean up";
+
+// Previews the whitespace cleanup done on save, toggles its options and applies it.
+#[derive(Debug)]
+pub struct WhitespaceWidget<'a> {
+    pub state: Rc<RefCell<State<'a>>>,
+    pub cleanup: Cleanup,
+    pub changes: Vec<String>,
+    pub scroll: u16,
+    pub error: ErrorType,
+}
+
+impl<'a> WhitespaceWidget<'a> {
+    pub fn new(state: Rc<RefCell<State<'a>>>) -> WhitespaceWidget<'a> {
+        WhitespaceWidget { state, cleanup: Cleanup::default(), changes: Vec::new(), scroll: 0, error: ErrorType::NONE }
+    }
+
+    fn refresh(&mut self) {
+        let state = self.state.borrow();
+        if let Some(buffer) = state.buffer_list.get(state.current_buffer) {
+            self.cleanup = buffer.cleanup;
+            self.changes = buffer.cleanup.preview(buffer.input.lines());
+        }
+    }
+
+    fn toggle(&mut self, option: fn(&mut Cleanup) -> &mut bool) {
+        let mut state = self.state.borrow_mut();
+        let current = state.current_buffer;
+        if let Some(buffer) = state.buffer_list.get_mut(current) {
+            let value = option(&mut buffer.cleanup);
+            *value = !*value;
+        }
+        drop(state);
+        self.refresh();
+    }
+
+    fn render_content(&self, area: Rect, buf: &mut Buffer) {
+        let height = (self.changes.len() as u16).clamp(1, MAX_VISIBLE) + 4;
+        let area = popup_area(area, 70, height);
+        Clear.render(area, buf);
+
+        if self.error != ErrorType::NONE {
+            render_message(&self.error.to_string(), popup_area(area, 70, 3), buf);
+            return;
+        }
+
+        let block = Block::default()
+            .bold()
+            .title("Whitespace cleanup on save (Enter: apply now)")
+            .borders(Borders::ALL);
+
+        let mut lines = vec![Line::from(self.cleanup.to_string()).italic(), Line::default()];
+        if self.changes.is_empty() {
+            lines.push(Line::from(NOTHING_TO_CLEAN));
+        }
+        lines.extend(self.changes.iter().skip(self.scroll as usize).map(|change| Line::from(change.as_str())));
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+impl ActionWidget for WhitespaceWidget<'_> {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.render_content(area, buf);
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> Result<(), io::Error> {
+        if self.error != ErrorType::NONE {
+            self.error = ErrorType::NONE;
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Char('t') => self.toggle(|cleanup| &mut cleanup.trim_trailing),
+            KeyCode::Char('n') => self.toggle(|cleanup| &mut cleanup.final_newline),
+            KeyCode::Char('b') => self.toggle(|cleanup| &mut cleanup.collapse_blank_lines),
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll = (self.scroll + 1).min(self.changes.len().saturating_sub(1) as u16),
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn has_error(&self) -> bool {
+        self.error != ErrorType::NONE
+    }
+
+    fn process_action(&mut self) -> Result<bool, io::Error> {
+        let mut state = self.state.borrow_mut();
+        let current = state.current_buffer;
+        let Some(buffer) = state.buffer_list.get_mut(current) else {
+            self.error = ErrorType::NoBuffer;
+            return Ok(false);
+        };
+        buffer.clean_whitespace();
+        Ok(true)
+    }
+
+    fn init_action(&mut self) {
+        self.refresh();
+    }
+
+    fn reset(&mut self) {
+        self.error = ErrorType::NONE;
+        self.scroll = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::state_with_buffer;
+
+    #[test]
+    fn test_toggle_and_apply() {
+        let state = state_with_buffer(&["a ", ""]);
+        state.borrow_mut().buffer_list[0].cleanup = Cleanup { trim_trailing: true, final_newline: true, collapse_blank_lines: true };
+
+        let mut widget = WhitespaceWidget::new(state);
+        widget.init_action();
+        assert_eq!(widget.changes.len(), 3);
+
+        widget.handle_input(KeyEvent::from(KeyCode::Char('b'))).unwrap();
+        assert!(!widget.state.borrow().buffer_list[0].cleanup.collapse_blank_lines);
+        assert_eq!(widget.changes.len(), 2);
+
+        assert!(widget.process_action().unwrap());
+        assert_eq!(widget.state.borrow().buffer_list[0].input.lines(), vec!["a", ""]);
+    }
+}